@@ -0,0 +1,146 @@
+use rusty_advent_2024::utils::{
+    cli::{Extension, Extensions},
+    file_io::{self, Input},
+    lint::{has_validator, lint},
+    vm::{
+        chronospatial::Machine,
+        debugger::{Command, Debugger},
+        disasm::disassemble,
+    },
+};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process::ExitCode,
+};
+
+const EXTENSIONS: [Extension; 2] = [
+    Extension {
+        name: "day",
+        default: "0",
+    },
+    Extension {
+        name: "input",
+        default: "",
+    },
+];
+
+const USAGE: &str = "Usage: aoc lint-input --day N [--input PATH]
+       aoc disasm --day 17 [--input PATH]
+       aoc debug 17 [--input PATH]";
+
+// The --input path, or the day's puzzle input
+fn input_path(extensions: &Extensions, day: u32) -> String {
+    match extensions.get::<String>("input") {
+        path if path.is_empty() => format!("input/input{:02}.txt", day),
+        path => path,
+    }
+}
+
+// Checks an input file for the problems the day's parser would otherwise panic on
+fn lint_input(extensions: &Extensions) -> ExitCode {
+    let day: u32 = extensions.get("day");
+    if !(1..=25).contains(&day) {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+    let path = input_path(extensions, day);
+
+    let input = match Input::from_file(&path) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if !has_validator(day) {
+        println!("{}: no validator for day {}", path, day);
+        return ExitCode::SUCCESS;
+    }
+
+    let problems = lint(day, &input);
+    for problem in &problems {
+        println!("{}: {}", path, problem);
+    }
+    if problems.is_empty() {
+        println!("{}: ok", path);
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// The day's program as a machine, reporting why it could not be loaded
+fn load_machine(extensions: &Extensions, day: u32) -> Option<Machine> {
+    if day != 17 {
+        eprintln!("Only day 17 has programs to run.");
+        return None;
+    }
+    let path = input_path(extensions, day);
+    match file_io::string_from_file(&path).map(|text| text.parse()) {
+        Ok(Ok(machine)) => Some(machine),
+        Ok(Err(err)) => {
+            eprintln!("{}: {}", path, err);
+            None
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            None
+        }
+    }
+}
+
+// Lists a day 17 program as mnemonics, with the initial registers
+fn disasm(extensions: &Extensions) -> ExitCode {
+    let Some(machine) = load_machine(extensions, extensions.get("day")) else {
+        return ExitCode::FAILURE;
+    };
+    let [a, b, c] = machine.registers();
+    println!("; A = {}, B = {}, C = {}", a, b, c);
+    println!("{}", disassemble(machine.program()));
+    ExitCode::SUCCESS
+}
+
+// Reads debugger commands from stdin until quit or end of input
+fn debug(extensions: &Extensions) -> ExitCode {
+    // the day may be given as `debug 17` as well as `--day 17`
+    let day = env::args()
+        .nth(2)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or_else(|| extensions.get("day"));
+    let Some(machine) = load_machine(extensions, day) else {
+        return ExitCode::FAILURE;
+    };
+    let mut debugger = Debugger::new(machine);
+    println!("{}", debugger.execute(Command::List));
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        print!("(debug) ");
+        io::stdout().flush().expect("Could not write to stdout.");
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(Command::Quit) => break,
+            Ok(command) => println!("{}", debugger.execute(command)),
+            Err(err) => println!("{}", err),
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let extensions = Extensions::from_args(&EXTENSIONS);
+    match env::args().nth(1).as_deref() {
+        Some("lint-input") => lint_input(&extensions),
+        Some("disasm") => disasm(&extensions),
+        Some("debug") => debug(&extensions),
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
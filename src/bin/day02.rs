@@ -1,111 +1,71 @@
 use rusty_advent_2024::utils::file_io;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum ReportType {
-    Unsafe,
-    Trivial,
-    Increasing,
-    Decreasing,
+// A report is safe if it only increases or only decreases, by min_step to max_step at a time,
+// once at most max_removals of its levels are taken out
+#[derive(Clone, Copy, Debug)]
+struct SafetyPolicy {
+    min_step: i32,
+    max_step: i32,
+    max_removals: usize,
 }
 
-impl ReportType {
-    pub fn is_safe(&self) -> bool {
-        match self {
-            ReportType::Unsafe => false,
-            _ => true,
-        }
+impl SafetyPolicy {
+    const STRICT: SafetyPolicy = SafetyPolicy {
+        min_step: 1,
+        max_step: 3,
+        max_removals: 0,
+    };
+    const DAMPENED: SafetyPolicy = SafetyPolicy {
+        max_removals: 1,
+        ..SafetyPolicy::STRICT
+    };
+
+    // direction is 1 for increasing, -1 for decreasing
+    fn is_safe_step(&self, from: i32, to: i32, direction: i32) -> bool {
+        (self.min_step..=self.max_step).contains(&((to - from) * direction))
     }
 
-    pub fn combined_with(&self, other_type: &ReportType) -> ReportType {
-        match (self, other_type) {
-            (ReportType::Unsafe, _)
-            | (_, ReportType::Unsafe)
-            | (ReportType::Decreasing, ReportType::Increasing)
-            | (ReportType::Increasing, ReportType::Decreasing) => ReportType::Unsafe,
-            (ReportType::Trivial, other_type) => *other_type,
-            (my_type, _) => *my_type,
+    // fewest[i] is the fewest levels to remove before level i if it is kept. Removals come in
+    // runs between two kept levels, so only the last max_removals + 1 levels can come before.
+    fn fewest_removals(&self, report: &[i32], direction: i32) -> usize {
+        let mut fewest: Vec<usize> = Vec::with_capacity(report.len());
+        for (i, &level) in report.iter().enumerate() {
+            let kept_before = i.saturating_sub(self.max_removals + 1)..i;
+            let removals = kept_before
+                .filter(|&j| self.is_safe_step(report[j], level, direction))
+                .map(|j| fewest[j] + i - j - 1)
+                .fold(i, usize::min);
+            fewest.push(removals);
         }
-    }
-}
-
-fn is_safe_increase(difference: i32) -> bool {
-    match difference {
-        1 | 2 | 3 => true,
-        _ => false,
-    }
-}
-
-fn is_safe_decrease(difference: i32) -> bool {
-    is_safe_increase(-difference)
-}
-
-fn report_type(report: &[i32]) -> ReportType {
-    if report.len() < 2 {
-        return ReportType::Trivial;
-    }
-
-    let mut differences = report.into_iter().zip(&report[1..]).map(|(v1, v2)| v2 - v1);
-
-    if report[1] > report[0] && differences.all(is_safe_increase) {
-        return ReportType::Increasing;
-    } else if report[1] < report[0] && differences.all(is_safe_decrease) {
-        return ReportType::Decreasing;
-    }
-    return ReportType::Unsafe;
-}
-
-fn is_safe_report(report: &[i32]) -> bool {
-    report_type(report).is_safe()
-}
-
-fn is_safe_report_with_damper(report: &[i32]) -> bool {
-    if report.len() < 3 {
-        return true;
-    }
-
-    // Deal with special cases first
-    if is_safe_report(&report[1..]) || is_safe_report(&report[..report.len() - 1]) {
-        return true;
+        fewest
+            .iter()
+            .enumerate()
+            .map(|(i, removals)| removals + report.len() - 1 - i)
+            .min()
+            .unwrap_or(0)
     }
 
-    // Try removing elements individually
-    for idx in 1..report.len() - 1 {
-        let left = &report[..idx];
-        let left_type = report_type(left);
-        if !left_type.is_safe() {
-            // if the left report is already unsafe, we cannot salvage it
-            return false;
-        }
-
-        let mid = &vec![report[idx - 1], report[idx + 1]];
-        let right_needs_type = report_type(mid).combined_with(&left_type);
-        if !right_needs_type.is_safe() {
-            continue;
-        }
-
-        let right = &report[idx + 1..];
-        let right_type = report_type(right);
-        if right_type.combined_with(&right_needs_type).is_safe() {
-            return true;
-        }
+    fn is_safe(&self, report: &[i32]) -> bool {
+        [1, -1]
+            .into_iter()
+            .any(|direction| self.fewest_removals(report, direction) <= self.max_removals)
     }
-    return false;
 }
 
-fn part1(path: &str) -> usize {
-    let reports = file_io::rows_from_file::<i32>(path);
+fn count_safe(path: &str, policy: SafetyPolicy) -> usize {
+    let reports = file_io::rows_from_file::<i32>(path).expect("Could not read input.");
     reports
         .into_iter()
-        .filter(|report: &Vec<i32>| is_safe_report(report))
+        .filter(|report: &Vec<i32>| policy.is_safe(report))
         .count()
 }
 
+fn part1(path: &str) -> usize {
+    count_safe(path, SafetyPolicy::STRICT)
+}
+
 fn part2(path: &str) -> usize {
-    let reports = file_io::rows_from_file::<i32>(path);
-    reports
-        .into_iter()
-        .filter(|report: &Vec<i32>| is_safe_report_with_damper(report))
-        .count()
+    count_safe(path, SafetyPolicy::DAMPENED)
 }
 
 fn main() {
@@ -118,26 +78,68 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert!(is_safe_report(&vec![1, 3, 4, 5, 7]));
-        assert!(is_safe_report(&vec![7, 5, 4, 3, 1]));
-        assert!(is_safe_report(&vec![7, 4, 3, 2, 1]));
-        assert!(is_safe_report(&vec![1, 3, 4, 3, 5]) == false);
-        assert!(is_safe_report(&vec![8, 4, 3, 2, 1]) == false);
-        assert_eq!(part1("input/input02.txt.test1"), 2);
+        assert!(SafetyPolicy::STRICT.is_safe(&vec![1, 3, 4, 5, 7]));
+        assert!(SafetyPolicy::STRICT.is_safe(&vec![7, 5, 4, 3, 1]));
+        assert!(SafetyPolicy::STRICT.is_safe(&vec![7, 4, 3, 2, 1]));
+        assert!(!SafetyPolicy::STRICT.is_safe(&vec![1, 3, 4, 3, 5]));
+        assert!(!SafetyPolicy::STRICT.is_safe(&vec![8, 4, 3, 2, 1]));
+        assert_eq!(part1(&test_input(2, 1)), 2);
     }
 
     #[test]
     fn test_part2() {
-        assert!(is_safe_report_with_damper(&vec![1, 3, 4, 5, 7]));
-        assert!(is_safe_report_with_damper(&vec![8, 5, 4, 2, 1]));
-        assert!(is_safe_report_with_damper(&vec![1, 3, 4, 3, 5]));
-        assert!(is_safe_report_with_damper(&vec![7, 8, 4, 3, 1]));
-        assert!(is_safe_report_with_damper(&vec![3, 4, 3, 2, 1]));
-        assert!(is_safe_report_with_damper(&vec![4, 3, 2, 1, 3]));
-        assert!(is_safe_report_with_damper(&vec![4, 3, 4, 3, 4]) == false);
-        assert_eq!(part2("input/input02.txt.test1"), 4);
+        assert!(SafetyPolicy::DAMPENED.is_safe(&vec![1, 3, 4, 5, 7]));
+        assert!(SafetyPolicy::DAMPENED.is_safe(&vec![8, 5, 4, 2, 1]));
+        assert!(SafetyPolicy::DAMPENED.is_safe(&vec![1, 3, 4, 3, 5]));
+        assert!(SafetyPolicy::DAMPENED.is_safe(&vec![7, 8, 4, 3, 1]));
+        assert!(SafetyPolicy::DAMPENED.is_safe(&vec![3, 4, 3, 2, 1]));
+        assert!(SafetyPolicy::DAMPENED.is_safe(&vec![4, 3, 2, 1, 3]));
+        assert!(!SafetyPolicy::DAMPENED.is_safe(&vec![4, 3, 4, 3, 4]));
+        assert_eq!(part2(&test_input(2, 1)), 4);
+    }
+
+    #[test]
+    fn test_removals() {
+        let policy = |max_removals| SafetyPolicy {
+            max_removals,
+            ..SafetyPolicy::STRICT
+        };
+        // the fewest removals each report needs
+        let reports: [(&[i32], usize); 7] = [
+            (&[], 0),
+            (&[5], 0),
+            (&[1, 2, 4, 7], 0),
+            (&[9, 7, 8, 6, 4], 1),
+            (&[1, 9, 2, 9, 3], 2),
+            (&[1, 2, 9, 9, 3, 4], 2),
+            (&[1, 2, 9, 9, 9, 3], 3),
+        ];
+        for (report, needed) in reports {
+            for max_removals in 0..=2 {
+                assert_eq!(
+                    policy(max_removals).is_safe(report),
+                    needed <= max_removals,
+                    "{:?} with {} removals",
+                    report,
+                    max_removals
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_range() {
+        let policy = SafetyPolicy {
+            min_step: 2,
+            max_step: 5,
+            max_removals: 1,
+        };
+        assert!(policy.is_safe(&[10, 5, 3, 0]));
+        assert!(policy.is_safe(&[1, 3, 4, 8]));
+        assert!(!policy.is_safe(&[1, 2, 3, 4]));
     }
 }
@@ -1,49 +1,23 @@
-use itertools::Itertools;
-use regex::Regex;
-use rusty_advent_2024::utils::file_io::lines_from_file;
-
-fn compute_sum(row: &str) -> i32 {
-    let pattern: Regex = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").expect("Regex pattern invalid.");
-    pattern
-        .captures_iter(&row)
-        .map(|captures| -> (i32, i32) {
-            (
-                captures
-                    .get(1)
-                    .expect("Failed to capture group 1.")
-                    .as_str()
-                    .parse::<i32>()
-                    .expect("Failed to parse match 1."),
-                captures
-                    .get(2)
-                    .expect("Failed to capture group 2.")
-                    .as_str()
-                    .parse::<i32>()
-                    .expect("Failed to parse match 2."),
-            )
-        })
-        .map(|(num1, num2)| num1 * num2)
-        .sum()
+use rusty_advent_2024::utils::{
+    file_io::strings_from_file,
+    parse::mul_program::{instructions, Machine},
+};
+
+fn run_memory(path: &str, conditionals: bool) -> i64 {
+    let mut machine = Machine::new(conditionals);
+    for line in strings_from_file(path).expect("Could not read input.") {
+        machine.run(instructions(&line));
+    }
+    machine.total()
 }
 
-fn part1(path: &str) -> i32 {
-    lines_from_file(path)
-        .map(|line| compute_sum(line.unwrap().as_str()))
-        .sum()
+fn part1(path: &str) -> i64 {
+    run_memory(path, false)
 }
 
-fn part2(path: &str) -> i32 {
-    let total_string = lines_from_file(path)
-        .map(|line| line.unwrap())
-        .collect_vec()
-        .join(" ");
-
-    // Remove anything from don't() to either do() or the string end
-    let dont_mul_pattern: Regex =
-        Regex::new(r"don\'t\(\).*?(?:do\(\)|$)").expect("Regex pattern invalid.");
-    let enabled_instructions = dont_mul_pattern.replace_all(&total_string, "");
-
-    compute_sum(&enabled_instructions)
+// A don't() holds until the next do(), even across lines
+fn part2(path: &str) -> i64 {
+    run_memory(path, true)
 }
 
 fn main() {
@@ -56,6 +30,13 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
+
+    fn compute_sum(row: &str) -> i64 {
+        let mut machine = Machine::new(false);
+        machine.run(instructions(row));
+        machine.total()
+    }
 
     #[test]
     fn test_compute_sum() {
@@ -66,11 +47,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input03.txt.test1"), 161);
+        assert_eq!(part1(&test_input(3, 1)), 161);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input03.txt.test2"), 48);
+        assert_eq!(part2(&test_input(3, 2)), 48);
     }
 }
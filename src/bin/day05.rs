@@ -1,120 +1,88 @@
 use itertools::Itertools;
-use rusty_advent_2024::utils::file_io::lines_from_file;
-use std::collections::{HashMap, HashSet};
-
-type RuleSet = HashMap<usize, HashSet<usize>>;
+use rusty_advent_2024::utils::{
+    cli,
+    file_io::{blocks_from_file, AocError},
+    ordering::PartialOrderRules,
+    viz::mermaid::{Flowchart, Shape},
+};
+
+type RuleSet = PartialOrderRules<usize>;
 type Update = Vec<usize>;
 
-fn update_rule(rules: &mut RuleSet, key: usize, value: usize) {
-    if let Some(values) = rules.get_mut(&key) {
-        values.insert(value);
-    } else {
-        rules.insert(key, HashSet::from([value]));
-    }
-}
-
 fn middle_page(update: &Vec<usize>) -> usize {
     update[update.len() / 2]
 }
 
-fn is_valid(update: &Update, rules: &RuleSet) -> bool {
-    if update.len() < 3 {
-        return true;
-    }
+fn read_in_file(path: &str) -> Result<(RuleSet, Vec<Update>), AocError> {
+    let (rule_block, update_block) = blocks_from_file(path)?
+        .collect_tuple()
+        .expect("Input should consist of a rule block and an update block.");
 
-    let mut previous_pages: HashSet<usize> = HashSet::new();
-    for page in update {
-        if let Some(successors) = rules.get(page) {
-            if !previous_pages.is_disjoint(successors) {
-                return false;
-            }
-        }
-        previous_pages.insert(*page);
-    }
-
-    true
-}
+    let mut rules = RuleSet::new();
+    for row in rule_block {
+        let (key, value): (usize, usize) = row
+            .split("|")
+            .map(|number| -> usize { number.parse().expect("Parsing {number} failed.") })
+            .collect_tuple()
+            .expect("Error collecting tuple.");
 
-fn read_in_file(path: &str) -> (RuleSet, Vec<Update>) {
-    let lines = lines_from_file(path);
-
-    let mut rules: HashMap<usize, HashSet<usize>> = HashMap::new();
-    let mut updates: Vec<Update> = Vec::new();
-
-    let mut reading_rules: bool = true;
-    for line in lines {
-        let row = line.unwrap();
-        if row.len() == 0 {
-            reading_rules = false;
-            continue;
-        }
-
-        if reading_rules {
-            let (key, value): (usize, usize) = row
-                .split("|")
-                .map(|number| -> usize { number.parse().expect("Parsing {number} failed.") })
-                .collect_tuple()
-                .expect("Error collecting tuple.");
-
-            update_rule(&mut rules, key, value);
-        } else {
-            let update: Update = row
-                .split(r",")
-                .map(|number| -> usize { number.parse().expect("Parsing {number} failed.") })
-                .collect_vec();
-            updates.push(update);
-        }
+        rules.add_rule(key, value);
     }
 
-    (rules, updates)
-}
+    let updates: Vec<Update> = update_block
+        .iter()
+        .map(|row| -> Update {
+            row.split(r",")
+                .map(|number| -> usize { number.parse().expect("Parsing {number} failed.") })
+                .collect_vec()
+        })
+        .collect();
 
-fn fix_update(update: &mut Update, rules: &RuleSet) {
-    let mut needs_sorting = true;
-
-    // put numbers in correct order
-    while needs_sorting {
-        needs_sorting = false;
-        for left in 0..update.len() - 1 {
-            for right in left..update.len() {
-                let (left_page, right_page) = (update[left], update[right]);
-                if let Some(successors) = rules.get(&right_page) {
-                    if successors.contains(&left_page) {
-                        update.swap(left, right);
-                        needs_sorting = true;
-                    }
-                }
-            }
-        }
-    }
+    Ok((rules, updates))
 }
 
 fn part1(path: &str) -> usize {
-    let (rules, updates) = read_in_file(path);
+    let (rules, updates) = read_in_file(path).expect("Could not read input.");
 
     updates
         .iter()
-        .filter(|update| is_valid(update, &rules))
+        .filter(|update| rules.is_sorted(update))
         .map(middle_page)
         .sum()
 }
 
 fn part2(path: &str) -> usize {
-    let (rules, mut updates) = read_in_file(path);
+    let (rules, mut updates) = read_in_file(path).expect("Could not read input.");
 
-    let invalid_updates = updates
-        .iter_mut()
-        .filter(|update| !is_valid(update, &rules));
+    let invalid_updates = updates.iter_mut().filter(|update| !rules.is_sorted(update));
 
     invalid_updates
         .map(|update| -> usize {
-            fix_update(update, &rules);
+            rules
+                .sort(update)
+                .unwrap_or_else(|error| panic!("Could not fix update {:?}: {}", update, error));
             middle_page(update)
         })
         .sum()
 }
 
+// The ordering rules as a Mermaid flowchart, each page pointing to the pages that must come
+// after it, e.g. `cargo run --bin day05 -- --mermaid`
+fn mermaid_diagram(path: &str) -> String {
+    let (rules, _) = read_in_file(path).expect("Could not read input.");
+    let pages = rules.items().sorted();
+    let orderings = rules.rules().sorted();
+    Flowchart::new("LR")
+        .nodes(pages, |page| page.to_string(), Shape::Circle)
+        .edges(orderings)
+        .to_string()
+}
+
 fn main() {
+    if cli::flag("mermaid") {
+        println!("{}", mermaid_diagram("input/input05.txt"));
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input05.txt"));
     println!("Answer to part 2:");
@@ -124,14 +92,38 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input05.txt.test1"), 143);
+        assert_eq!(part1(&test_input(5, 1)), 143);
+    }
+
+    #[test]
+    fn test_fix_update() {
+        let (rules, _) = read_in_file(&test_input(5, 1)).unwrap();
+        for (update, fixed) in [
+            (vec![75, 97, 47, 61, 53], vec![97, 75, 47, 61, 53]),
+            (vec![61, 13, 29], vec![61, 29, 13]),
+            (vec![97, 13, 75, 29, 47], vec![97, 75, 47, 29, 13]),
+        ] {
+            let mut update = update;
+            assert_eq!(rules.sort(&mut update), Ok(()));
+            assert_eq!(update, fixed);
+        }
+    }
+
+    #[test]
+    fn test_mermaid_diagram() {
+        let mermaid = mermaid_diagram(&test_input(5, 1));
+        assert!(mermaid.contains("    13((\"13\"))"));
+        assert!(mermaid.contains("    47 --> 53"));
+        // one edge per rule
+        assert_eq!(mermaid.matches(" --> ").count(), 21);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input05.txt.test1"), 123);
+        assert_eq!(part2(&test_input(5, 1)), 123);
     }
 }
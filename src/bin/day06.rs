@@ -1,80 +1,164 @@
+use itertools::Itertools;
 use rusty_advent_2024::utils::{
-    file_io::lines_from_file,
-    map2d::{direction::Direction, grid::Bounds, position::Position},
+    cli::{self, Extensions},
+    file_io::{grid_with_markers, AocError, HasCharConverter},
+    map2d::{
+        direction::Direction,
+        grid::{Grid, ToChar, ValidPosition},
+    },
+    viz::{
+        dump_dir,
+        image::Rgb,
+        overlay::Overlay,
+        record_path,
+        terminal::{Animation, FPS},
+        FrameDump, Recorder, FRAME_FORMAT,
+    },
 };
-use std::{collections::HashSet, hash::Hash};
+use std::{collections::HashSet, hash::Hash, iter, time::Instant};
 
 #[derive(Hash, PartialEq, Eq, Clone, Copy)]
 struct Guard {
-    pos: Position,
+    pos: ValidPosition,
     dir: Direction,
 }
 
-impl MazeState {
-    fn in_bounds(&self, position: &Position) -> bool {
-        position.0 >= 0
-            && position.1 >= 0
-            && position.0 < self.bounds.0 as i32
-            && position.1 < self.bounds.1 as i32
-    }
-
-    fn step_guard(self: &mut Self) -> Option<Position> {
-        let next_pos = self.guard.pos.step(&self.guard.dir);
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Tile {
+    Empty,
+    Obstacle,
+}
 
-        if self.obstacles.contains(&next_pos) {
-            self.guard.dir.turn_right();
-            return Some(self.guard.pos.clone());
+// The guard is taken out as a marker before conversion
+impl HasCharConverter for Tile {
+    fn convert(c: char) -> Result<Self, String> {
+        match c {
+            '#' => Ok(Self::Obstacle),
+            '.' => Ok(Self::Empty),
+            _ => Err(String::from("expected a lab tile ('#' or '.')")),
         }
+    }
+}
 
-        if self.in_bounds(&next_pos) {
-            self.guard.pos = next_pos;
-            Some(next_pos)
-        } else {
-            None
+impl ToChar for Tile {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Obstacle => '#',
+            Self::Empty => '.',
         }
     }
 }
 
 struct MazeState {
     guard: Guard,
-    obstacles: HashSet<Position>,
-    bounds: Bounds,
-}
-
-fn read_maze(path: &str) -> MazeState {
-    let mut guard: Guard = Guard {
-        pos: Position(0, 0),
-        dir: Direction::UP,
-    };
-    let mut obstacles: HashSet<Position> = HashSet::new();
-    let mut bounds: Bounds = Bounds(0, 0);
-    for (y, line) in lines_from_file(path).into_iter().enumerate() {
-        for (x, c) in line.unwrap().chars().enumerate() {
-            match c {
-                '#' => {
-                    obstacles.insert(Position(x as i32, y as i32));
-                }
-                '^' | '>' | 'v' | '<' => {
-                    guard = Guard {
-                        pos: Position(x as i32, y as i32),
-                        dir: c.into(),
-                    }
-                }
-                _ => {}
-            }
-            bounds = Bounds(x + 1, y + 1);
+    map: Grid<Tile>,
+}
+
+impl MazeState {
+    // Where the guard is after one more step, None once it has left. `extra` is an obstacle
+    // placed on top of the map.
+    fn next_guard(&self, guard: Guard, extra: Option<ValidPosition>) -> Option<Guard> {
+        let next_pos = guard.pos.try_step(&guard.dir, &self.map.bounds)?;
+
+        if *self.map.value(&next_pos) == Tile::Obstacle || extra == Some(next_pos) {
+            return Some(Guard {
+                pos: guard.pos,
+                dir: guard.dir.turned_right(),
+            });
+        }
+
+        Some(Guard {
+            pos: next_pos,
+            dir: guard.dir,
+        })
+    }
+
+    fn step_guard(&mut self) -> Option<ValidPosition> {
+        self.guard = self.next_guard(self.guard, None)?;
+        Some(self.guard.pos)
+    }
+}
+
+fn read_maze(path: &str) -> Result<MazeState, AocError> {
+    let guard_chars = ['^', '>', 'v', '<'];
+    let (map, markers): (Grid<Tile>, _) = grid_with_markers(path, &guard_chars)?;
+    let guard = guard_chars
+        .iter()
+        .find_map(|&c| {
+            markers[&c].first().map(|&pos| Guard {
+                pos,
+                dir: c.try_into().expect("Guard should face a valid direction."),
+            })
+        })
+        .expect("There should be a guard in the input.");
+
+    Ok(MazeState { guard, map })
+}
+
+// Obstacles, the guard pointing the way it faces, and where it has been
+fn frame(maze: &MazeState, visited: &HashSet<ValidPosition>) -> Grid<char> {
+    Grid::from_fn(maze.map.bounds, |pos| {
+        if pos == maze.guard.pos {
+            maze.guard.dir.into()
+        } else if visited.contains(&pos) {
+            'X'
+        } else {
+            maze.map.value(&pos).to_char()
         }
+    })
+}
+
+// The walk in part 1, calling `on_step` at the start and after every step
+fn patrol(path: &str, mut on_step: impl FnMut(&MazeState, &HashSet<ValidPosition>)) {
+    let mut maze = read_maze(path).expect("Could not read input.");
+    let mut visited: HashSet<ValidPosition> = HashSet::from([maze.guard.pos]);
+    loop {
+        on_step(&maze, &visited);
+        match maze.step_guard() {
+            Some(pos) => visited.insert(pos),
+            None => break,
+        };
     }
+}
 
-    MazeState {
-        guard,
-        obstacles,
-        bounds,
+fn color(c: &char) -> Rgb {
+    match c {
+        '#' => Rgb(90, 90, 100),
+        'X' => Rgb(60, 110, 200),
+        '.' => Rgb::BLACK,
+        _ => Rgb(230, 40, 40),
     }
 }
 
-fn get_visited_positions(maze: &mut MazeState) -> HashSet<Position> {
-    let mut visited: HashSet<Position> = HashSet::new();
+// The guard's way out of the maze, one state per step from the start; turning on the spot is
+// a step of its own
+fn trajectory(maze: &MazeState) -> Vec<Guard> {
+    iter::successors(Some(maze.guard), |&guard| maze.next_guard(guard, None)).collect()
+}
+
+// Where the guard turned, in order
+fn turn_points(trajectory: &[Guard]) -> Vec<ValidPosition> {
+    trajectory
+        .iter()
+        .tuple_windows()
+        .filter(|(guard, next_guard)| guard.pos == next_guard.pos)
+        .map(|(guard, _)| guard.pos)
+        .dedup()
+        .collect()
+}
+
+// The way out drawn as arrows over the map, with the turns marked
+fn trajectory_map(maze: &MazeState) -> Overlay {
+    let trajectory = trajectory(maze);
+    let route: Vec<ValidPosition> = trajectory.iter().map(|guard| guard.pos).dedup().collect();
+    let turns = turn_points(&trajectory);
+    Overlay::new(&frame(maze, &HashSet::new()))
+        .path(&route, Rgb(60, 110, 200))
+        .cells(&turns, '+', Rgb(230, 40, 40))
+}
+
+fn get_visited_positions(maze: &mut MazeState) -> HashSet<ValidPosition> {
+    let mut visited: HashSet<ValidPosition> = HashSet::new();
     visited.insert(maze.guard.pos);
 
     while let Some(new_pos) = maze.step_guard() {
@@ -84,46 +168,139 @@ fn get_visited_positions(maze: &mut MazeState) -> HashSet<Position> {
     visited
 }
 
-fn creates_loop(maze: &mut MazeState, obstacle: Position) -> bool {
-    let guard_start = maze.guard;
-    maze.obstacles.insert(obstacle);
-
-    let mut visited_guard_states: HashSet<Guard> = HashSet::new();
-    visited_guard_states.insert(maze.guard);
+// Leaves the maze as it is, so that candidates can be tried side by side
+fn creates_loop(maze: &MazeState, (mut guard, obstacle): Candidate) -> bool {
+    let mut visited_guard_states: HashSet<Guard> = HashSet::from([guard]);
 
-    let mut creates_loop: bool = false;
-
-    while let Some(_) = maze.step_guard() {
-        if !visited_guard_states.insert(maze.guard) {
-            creates_loop = true;
-            break;
+    while let Some(next_guard) = maze.next_guard(guard, Some(obstacle)) {
+        if !visited_guard_states.insert(next_guard) {
+            return true;
         }
+        guard = next_guard;
     }
 
-    maze.obstacles.remove(&obstacle);
-    maze.guard = guard_start;
+    false
+}
 
-    creates_loop
+fn count_loops_serial(maze: &MazeState, candidates: &[Candidate]) -> usize {
+    candidates
+        .iter()
+        .filter(|&&candidate| creates_loop(maze, candidate))
+        .count()
+}
+
+// Without the `rayon` feature this is count_loops_serial
+#[cfg(feature = "rayon")]
+fn count_loops(maze: &MazeState, candidates: &[Candidate]) -> usize {
+    use rayon::prelude::*;
+
+    candidates
+        .par_iter()
+        .filter(|&&candidate| creates_loop(maze, candidate))
+        .count()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn count_loops(maze: &MazeState, candidates: &[Candidate]) -> usize {
+    count_loops_serial(maze, candidates)
 }
 
 fn part1(path: &str) -> usize {
-    let mut maze = read_maze(path);
+    let mut maze = read_maze(path).expect("Could not read input.");
     get_visited_positions(&mut maze).len()
 }
 
+// An obstacle and the guard just before it would first run into it
+type Candidate = (Guard, ValidPosition);
+
+// Only obstacles straight ahead of the guard change its way. Up to the first time the guard
+// would run into one, nothing changes, so the search for a loop starts there.
+fn obstacle_candidates(path: &str) -> (MazeState, Vec<Candidate>) {
+    let maze = read_maze(path).expect("Could not read input.");
+    let mut seen: HashSet<ValidPosition> = HashSet::from([maze.guard.pos]);
+    let candidates = trajectory(&maze)
+        .into_iter()
+        .tuple_windows()
+        .filter(|(_, next_guard)| seen.insert(next_guard.pos))
+        .map(|(guard, next_guard)| (guard, next_guard.pos))
+        .collect();
+    (maze, candidates)
+}
+
 fn part2(path: &str) -> usize {
-    let mut maze = read_maze(path);
-    let guard_start = maze.guard;
-    let obstacle_candidates = get_visited_positions(&mut maze);
-    maze.guard = guard_start;
+    let (maze, candidates) = obstacle_candidates(path);
+    count_loops(&maze, &candidates)
+}
 
-    obstacle_candidates
-        .iter()
-        .filter(|&&obstacle| creates_loop(&mut maze, obstacle))
-        .count()
+// Part 2 one candidate after the other and then in parallel, e.g.
+// `cargo run --release --features rayon --bin day06 -- --bench`
+fn bench(path: &str) {
+    let (maze, candidates) = obstacle_candidates(path);
+    let serial = Instant::now();
+    let loops = count_loops_serial(&maze, &candidates);
+    let serial = serial.elapsed();
+    println!("serial:   {} loops in {:?}", loops, serial);
+    // count_loops would just be the serial version again
+    if !cfg!(feature = "rayon") {
+        println!("parallel: skipped, the rayon feature is disabled");
+        return;
+    }
+
+    let parallel = Instant::now();
+    let loops = count_loops(&maze, &candidates);
+    let parallel = parallel.elapsed();
+    println!("parallel: {} loops in {:?}", loops, parallel);
+    println!(
+        "speedup:  {:.1}x",
+        serial.as_secs_f64() / parallel.as_secs_f64()
+    );
 }
 
 fn main() {
+    // e.g. `cargo run --bin day06 -- --trajectory`
+    if cli::flag("trajectory") {
+        let maze = read_maze("input/input06.txt").expect("Could not read input.");
+        println!("{}", trajectory_map(&maze).to_ansi());
+        return;
+    }
+    if cli::flag("bench") {
+        bench("input/input06.txt");
+        return;
+    }
+    // The guard's patrol, e.g. `cargo run --bin day06 -- --visualize --fps 30`; Enter pauses
+    if cli::flag("visualize") {
+        let extensions = Extensions::from_args(&[FPS]);
+        let mut animation = Animation::new(extensions.get("fps")).pause_on_enter();
+        patrol("input/input06.txt", |maze, visited| {
+            let caption = format!("{} positions visited", visited.len());
+            animation
+                .draw(&frame(maze, visited), &caption)
+                .expect("Could not draw frame.");
+        });
+        return;
+    }
+    // e.g. `cargo run --release --bin day06 -- --record patrol.gif --fps 50`
+    if let Some(path) = record_path() {
+        let extensions = Extensions::from_args(&[FPS]);
+        let mut recorder = Recorder::new(4, extensions.get("fps"));
+        patrol("input/input06.txt", |maze, visited| {
+            recorder.record(&frame(maze, visited).map(color))
+        });
+        recorder.save(path).expect("Could not write animation.");
+        return;
+    }
+    // e.g. `cargo run --release --bin day06 -- --dump-frames frames/ --frame-format png`
+    if let Some(dir) = dump_dir() {
+        let extensions = Extensions::from_args(&[FRAME_FORMAT]);
+        let format: String = extensions.get("frame-format");
+        let mut frames = FrameDump::new(dir, &format, 4).expect("Could not create directory.");
+        patrol("input/input06.txt", |maze, visited| {
+            frames
+                .dump(&frame(maze, visited), color)
+                .expect("Could not write frame.");
+        });
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input06.txt"));
     println!("Answer to part 2:");
@@ -133,14 +310,55 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input06.txt.test1"), 41);
+        assert_eq!(part1(&test_input(6, 1)), 41);
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut maze = read_maze(&test_input(6, 1)).unwrap();
+        let visited = get_visited_positions(&mut maze);
+        let frame = frame(&maze, &visited).pretty_print_string();
+        assert_eq!(frame.matches('X').count(), visited.len() - 1);
+        assert_eq!(frame.lines().last(), Some("......#v.."));
+    }
+
+    #[test]
+    fn test_trajectory() {
+        let maze = read_maze(&test_input(6, 1)).unwrap();
+        let trajectory = trajectory(&maze);
+        let visited: HashSet<ValidPosition> = trajectory.iter().map(|guard| guard.pos).collect();
+        assert_eq!(visited.len(), 41);
+        assert_eq!(trajectory[0].pos, ValidPosition(4, 6));
+        let turns = turn_points(&trajectory);
+        assert_eq!(turns[..2], [ValidPosition(4, 1), ValidPosition(8, 1)]);
+        // one step per move and one per turn
+        assert_eq!(trajectory.len(), 1 + 44 + turns.len());
+
+        let map = trajectory_map(&maze).chars().pretty_print_string();
+        assert_eq!(map.lines().nth(1), Some("....+>>>+#"));
+    }
+
+    #[test]
+    fn test_patrol() {
+        let mut steps = 0;
+        let mut last_visited = 0;
+        patrol(&test_input(6, 1), |_, visited| {
+            steps += 1;
+            last_visited = visited.len();
+        });
+        assert_eq!(last_visited, 41);
+        // turns take a step without moving
+        assert!(steps > 41);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input06.txt.test1"), 6);
+        assert_eq!(part2(&test_input(6, 1)), 6);
+        let (maze, candidates) = obstacle_candidates(&test_input(6, 1));
+        assert_eq!(count_loops_serial(&maze, &candidates), 6);
     }
 }
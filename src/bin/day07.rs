@@ -1,86 +1,162 @@
 use itertools::Itertools;
-use rusty_advent_2024::utils::file_io::lines_from_file;
+use rusty_advent_2024::utils::{
+    cli,
+    file_io::{parse_lines, AocError},
+};
+use std::str::FromStr;
 
 struct Equation {
     target: usize,
     numbers: Vec<usize>,
 }
 
-fn equation_possible(target: usize, numbers: &[usize], concatenation_allowed: bool) -> bool {
-    if numbers.len() == 1 {
-        return target == numbers[0];
+// An operator, applied left to right, and its inverse: the left operand that gives the result
+// with the right operand, if there is one. Equations are solved backwards from the target, so
+// the inverse both prunes the search and replaces trying every left operand.
+#[derive(Clone, Copy)]
+struct Operator {
+    symbol: &'static str,
+    apply: fn(usize, usize) -> Option<usize>,
+    inverse: fn(usize, usize) -> Option<usize>,
+}
+
+// 10 to the number of digits, e.g. 100 for 47
+fn concatenation_shift(number: usize) -> usize {
+    match number {
+        0 => 10,
+        x => 10_usize.pow(x.ilog10() + 1),
     }
+}
+
+const ADD: Operator = Operator {
+    symbol: "+",
+    apply: |left, right| left.checked_add(right),
+    inverse: |result, right| result.checked_sub(right),
+};
+
+// Anything times 0 is 0, which has no single inverse; such a step is never taken
+const MULTIPLY: Operator = Operator {
+    symbol: "*",
+    apply: |left, right| left.checked_mul(right),
+    inverse: |result, right| (right != 0 && result % right == 0).then(|| result / right),
+};
+
+const CONCATENATE: Operator = Operator {
+    symbol: "||",
+    apply: |left, right| {
+        left.checked_mul(concatenation_shift(right))?
+            .checked_add(right)
+    },
+    inverse: |result, right| {
+        let shift = concatenation_shift(right);
+        let rest = result.checked_sub(right)?;
+        (rest % shift == 0).then(|| rest / shift)
+    },
+};
+
+const PART1_OPERATORS: [Operator; 2] = [ADD, MULTIPLY];
+const PART2_OPERATORS: [Operator; 3] = [ADD, MULTIPLY, CONCATENATE];
+
+// The operators that go between the numbers to give the target, if there are any
+fn solve(target: usize, numbers: &[usize], operators: &[Operator]) -> Option<Vec<Operator>> {
+    let (&last, rest) = numbers.split_last()?;
+    if rest.is_empty() {
+        return (target == last).then(Vec::new);
+    }
+
+    operators.iter().find_map(|operator| {
+        let left = (operator.inverse)(target, last)?;
+        let mut assignment = solve(left, rest, operators)?;
+        assignment.push(*operator);
+        Some(assignment)
+    })
+}
+
+fn equation_possible(target: usize, numbers: &[usize], operators: &[Operator]) -> bool {
+    solve(target, numbers, operators).is_some()
+}
+
+fn evaluate(numbers: &[usize], assignment: &[Operator]) -> Option<usize> {
+    let (&first, rest) = numbers.split_first()?;
+    rest.iter()
+        .zip(assignment)
+        .try_fold(first, |left, (&right, operator)| {
+            (operator.apply)(left, right)
+        })
+}
 
-    let number = numbers[numbers.len() - 1];
-
-    target >= number
-        && ((number != 0
-            && target % number == 0
-            && equation_possible(
-                target / number,
-                &numbers[..numbers.len() - 1],
-                concatenation_allowed,
-            ))
-            || equation_possible(
-                target - number,
-                &numbers[..numbers.len() - 1],
-                concatenation_allowed,
-            )
-            || (concatenation_allowed && {
-                let divisor = match number {
-                    0 => 10,
-                    x => (10 as usize).pow(x.ilog10() + 1),
-                };
-
-                ((target - number) % divisor == 0)
-                    && equation_possible(
-                        (target - number) / divisor,
-                        &numbers[..numbers.len() - 1],
-                        concatenation_allowed,
-                    )
-            }))
+// e.g. "3267 = 81 + 40 * 27", or None if no operators fit
+fn explain(equation: &Equation, operators: &[Operator]) -> Option<String> {
+    let assignment = solve(equation.target, &equation.numbers, operators)?;
+    debug_assert_eq!(
+        evaluate(&equation.numbers, &assignment),
+        Some(equation.target)
+    );
+    let mut terms = vec![equation.numbers[0].to_string()];
+    for (number, operator) in equation.numbers[1..].iter().zip(&assignment) {
+        terms.push(operator.symbol.to_string());
+        terms.push(number.to_string());
+    }
+    Some(format!("{} = {}", equation.target, terms.join(" ")))
 }
 
-fn equations_from_file(path: &str) -> Vec<Equation> {
-    lines_from_file(path)
-        .map(|line| line.unwrap())
-        .filter_map(|line: String| -> Option<Equation> {
-            line.split_once(": ").map(|(target, numbers)| -> Equation {
-                Equation {
-                    target: target.trim().parse().expect("Error parsing target number."),
-                    numbers: numbers
-                        .split_whitespace()
-                        .map(|substr| substr.trim().parse().expect("Error parsing numbers."))
-                        .collect_vec(),
-                }
-            })
+impl FromStr for Equation {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (target, numbers) = line
+            .split_once(": ")
+            .ok_or("Target and numbers should be separated by ': '.")?;
+        Ok(Equation {
+            target: target
+                .trim()
+                .parse()
+                .map_err(|_| "Error parsing target number.")?,
+            numbers: numbers
+                .split_whitespace()
+                .map(|substr| substr.parse())
+                .try_collect()
+                .map_err(|_| "Error parsing numbers.")?,
         })
-        .collect_vec()
+    }
+}
+
+fn equations_from_file(path: &str) -> Result<Vec<Equation>, AocError> {
+    parse_lines(path)
 }
 
 fn part1(path: &str) -> usize {
-    let equations = equations_from_file(path);
+    let equations = equations_from_file(path).expect("Could not read input.");
     equations
         .iter()
         .filter(|Equation { target, numbers }| -> bool {
-            equation_possible(*target, numbers, false)
+            equation_possible(*target, numbers, &PART1_OPERATORS)
         })
         .map(|Equation { target, numbers: _ }| target)
         .sum()
 }
 
 fn part2(path: &str) -> usize {
-    let equations = equations_from_file(path);
+    let equations = equations_from_file(path).expect("Could not read input.");
     equations
         .iter()
         .filter(|Equation { target, numbers }| -> bool {
-            equation_possible(*target, numbers, true)
+            equation_possible(*target, numbers, &PART2_OPERATORS)
         })
         .map(|Equation { target, numbers: _ }| target)
         .sum()
 }
 
 fn main() {
+    if cli::flag("explain") {
+        let equations = equations_from_file("input/input07.txt").expect("Could not read input.");
+        for equation in &equations {
+            if let Some(explanation) = explain(equation, &PART2_OPERATORS) {
+                println!("{}", explanation);
+            }
+        }
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input07.txt"));
     println!("Answer to part 2:");
@@ -90,27 +166,63 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert!(equation_possible(5, &[5], false));
-        assert!(equation_possible(50, &[5, 2, 5], false));
-        assert!(!equation_possible(111, &[5, 2, 5, 6, 11, 22], false));
-        assert!(!equation_possible(0, &[1, 4, 3], false));
-        assert!(equation_possible(8, &[1, 4, 3], false));
-        assert!(!equation_possible(14, &[1, 4, 3], false));
-        assert!(equation_possible(15, &[1, 4, 3], false));
-        assert_eq!(part1("input/input07.txt.test1"), 3749);
+        assert!(equation_possible(5, &[5], &PART1_OPERATORS));
+        assert!(equation_possible(50, &[5, 2, 5], &PART1_OPERATORS));
+        assert!(!equation_possible(
+            111,
+            &[5, 2, 5, 6, 11, 22],
+            &PART1_OPERATORS
+        ));
+        assert!(!equation_possible(0, &[1, 4, 3], &PART1_OPERATORS));
+        assert!(equation_possible(8, &[1, 4, 3], &PART1_OPERATORS));
+        assert!(!equation_possible(14, &[1, 4, 3], &PART1_OPERATORS));
+        assert!(equation_possible(15, &[1, 4, 3], &PART1_OPERATORS));
+        assert_eq!(part1(&test_input(7, 1)), 3749);
     }
 
     #[test]
     fn test_part2() {
-        assert!(equation_possible(50, &[5, 0], true));
-        assert!(equation_possible(1150, &[10, 1, 50], true));
-        assert!(equation_possible(15, &[5, 3], true));
-        assert!(equation_possible(3511, &[5, 7, 11], true));
-        assert!(equation_possible(5147, &[5, 100, 47], true));
-        assert!(!equation_possible(5148, &[5, 100, 47], true));
-        assert_eq!(part2("input/input07.txt.test1"), 11387);
+        assert!(equation_possible(50, &[5, 0], &PART2_OPERATORS));
+        assert!(equation_possible(1150, &[10, 1, 50], &PART2_OPERATORS));
+        assert!(equation_possible(15, &[5, 3], &PART2_OPERATORS));
+        assert!(equation_possible(3511, &[5, 7, 11], &PART2_OPERATORS));
+        assert!(equation_possible(5147, &[5, 100, 47], &PART2_OPERATORS));
+        assert!(!equation_possible(5148, &[5, 100, 47], &PART2_OPERATORS));
+        assert_eq!(part2(&test_input(7, 1)), 11387);
+    }
+
+    #[test]
+    fn test_explain() {
+        let equation: Equation = "3267: 81 40 27".parse().unwrap();
+        let explanation = explain(&equation, &PART1_OPERATORS).unwrap();
+        assert!(["3267 = 81 + 40 * 27", "3267 = 81 * 40 + 27"].contains(&explanation.as_str()));
+
+        let equation: Equation = "7290: 6 8 6 15".parse().unwrap();
+        assert_eq!(explain(&equation, &PART1_OPERATORS), None);
+        assert_eq!(
+            explain(&equation, &PART2_OPERATORS).unwrap(),
+            "7290 = 6 * 8 || 6 * 15"
+        );
+    }
+
+    // Not in the puzzle; results may not go below zero
+    const SUBTRACT: Operator = Operator {
+        symbol: "-",
+        apply: |left, right| left.checked_sub(right),
+        inverse: |result, right| result.checked_add(right),
+    };
+
+    #[test]
+    fn test_subtraction() {
+        let operators = [ADD, SUBTRACT];
+        let assignment = solve(4, &[10, 7, 1], &operators).unwrap();
+        assert_eq!(evaluate(&[10, 7, 1], &assignment), Some(4));
+        assert!(!equation_possible(4, &[10, 7, 1], &[ADD]));
+        // 1 - 7 would go below zero
+        assert!(!equation_possible(4, &[1, 7, 10], &operators));
     }
 }
@@ -85,9 +85,7 @@ impl City {
                     continue;
                 }
 
-                let distance = *pos2 - *pos1;
-                let gcd = gcd(distance.0.abs() as usize, distance.1.abs() as usize) as i32;
-                let delta = distance / gcd;
+                let delta = (*pos2 - *pos1).primitive();
 
                 let mut antinode = pos1.clone();
                 while let Some(pos) = antinode.in_bounds(&self.bounds) {
@@ -121,15 +119,9 @@ impl From<Grid<char>> for City {
     }
 }
 
-fn gcd(a: usize, b: usize) -> usize {
-    match (a, b) {
-        (x, 0) | (0, x) => x,
-        _ => gcd(b, a % b),
-    }
-}
-
 fn scan_city(path: &str) -> City {
-    let map: Grid<char> = file_io::strings_from_file(path).collect_vec().into();
+    let map: Grid<char> =
+        Grid::from_rows(file_io::strings_from_file(path).expect("Could not read input."));
     City::from(map)
 }
 
@@ -153,6 +145,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_mirroring() {
@@ -165,22 +158,13 @@ mod tests {
         assert_eq!(pos3.mirrored_across(&pos1), Position(0, -2));
     }
 
-    #[test]
-    fn test_gcd() {
-        assert_eq!(gcd(20, 5), 5);
-        assert_eq!(gcd(5, 20), 5);
-        assert_eq!(gcd(0, 8), 8);
-        assert_eq!(gcd(3824, 218), 2);
-        assert_eq!(gcd(91, 26), 13);
-    }
-
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input08.txt.test1"), 14);
+        assert_eq!(part1(&test_input(8, 1)), 14);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input08.txt.test1"), 34);
+        assert_eq!(part2(&test_input(8, 1)), 34);
     }
 }
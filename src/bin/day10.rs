@@ -1,6 +1,7 @@
-use itertools::Itertools;
-use rusty_advent_2024::utils::file_io;
+use rusty_advent_2024::utils::cli;
+use rusty_advent_2024::utils::file_io::{self, AocError};
 use rusty_advent_2024::utils::map2d::grid::{Grid, ValidPosition};
+use rusty_advent_2024::utils::viz::{heatmap, image, terminal::to_ansi_256};
 use std::collections::HashSet;
 use std::ops::Deref;
 
@@ -16,98 +17,190 @@ impl Deref for Topography {
 }
 
 impl Topography {
-    fn from_file(path: &str) -> Self {
-        Topography(file_io::strings_from_file(path).collect_vec().into())
+    fn from_file(path: &str) -> Result<Self, AocError> {
+        Ok(Topography(Grid::from_rows(file_io::strings_from_file(
+            path,
+        )?)))
     }
 
-    fn targets_reachable_by_trail(
+    // A value for every cell from the values of its neighbours one step higher, going from the
+    // summits down, so trails shared by many cells are only followed once
+    fn sweep_down<S: Default>(
         &self,
+        at_summit: impl Fn(ValidPosition) -> S,
+        from_higher: impl Fn(Vec<&S>) -> S,
+    ) -> Grid<S> {
+        let mut values: Grid<S> = Grid::from_fn(self.bounds, |_| S::default());
+        for pos in self.find(&9) {
+            *values.value_mut(&pos) = at_summit(pos);
+        }
+        for height in (0..9).rev() {
+            for pos in self.find(&height) {
+                let higher = pos
+                    .valid_neighbours(&self.bounds)
+                    .into_iter()
+                    .filter(|next| *self.value(next) == height + 1)
+                    .map(|next| values.value(&next))
+                    .collect();
+                *values.value_mut(&pos) = from_higher(higher);
+            }
+        }
+        values
+    }
+
+    // The summits each cell can climb to
+    fn reachable_summits(&self) -> Grid<HashSet<ValidPosition>> {
+        self.sweep_down(
+            |summit| HashSet::from([summit]),
+            |higher| higher.into_iter().flatten().copied().collect(),
+        )
+    }
+
+    // The number of distinct trails from each cell to a summit
+    fn ratings(&self) -> Grid<usize> {
+        self.sweep_down(|_| 1, |higher| higher.into_iter().sum())
+    }
+
+    fn trail_score(&self) -> usize {
+        let summits = self.reachable_summits();
+        self.find(&0)
+            .iter()
+            .map(|zero| -> usize { summits.value(zero).len() })
+            .sum()
+    }
+
+    fn trail_rating(&self) -> usize {
+        let ratings = self.ratings();
+        self.par_fold(
+            || 0,
+            |rating, pos, &height| match height {
+                0 => rating + ratings.value(&pos),
+                _ => rating,
+            },
+            |a, b| a + b,
+        )
+    }
+}
+
+fn part1(path: &str) -> usize {
+    Topography::from_file(path)
+        .expect("Could not read input.")
+        .trail_score()
+}
+
+fn part2(path: &str) -> usize {
+    Topography::from_file(path)
+        .expect("Could not read input.")
+        .trail_rating()
+}
+
+// The heights, e.g. `cargo run --bin day10 -- --heatmap`, or add `--render heights.png`
+fn show_heights(path: &str) {
+    let topography = Topography::from_file(path).expect("Could not read input.");
+    let colors = heatmap(&topography.map(|&height| height as usize));
+    match image::render_path() {
+        Some(image_path) => image::save(&colors, image_path, 4).expect("Could not write image."),
+        None => println!("{}", to_ansi_256(&colors)),
+    }
+}
+
+fn main() {
+    if cli::flag("heatmap") {
+        show_heights("input/input10.txt");
+        return;
+    }
+    println!("Answer to part 1:");
+    println!("{}", part1("input/input10.txt"));
+    println!("Answer to part 2:");
+    println!("{}", part2("input/input10.txt"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_advent_2024::utils::{fixtures::test_input, map2d::grid::Bounds};
+
+    // The recursive search the sweep replaced, following every trail separately
+    fn targets_reachable_by_trail(
+        topography: &Topography,
         start: ValidPosition,
         target_value: Height,
     ) -> HashSet<ValidPosition> {
-        let start_value = *self.value(&start);
+        let start_value = *topography.value(&start);
         if start_value == target_value {
             return HashSet::from([start]);
         }
 
         start
-            .valid_neighbours(&self.bounds)
+            .valid_neighbours(&topography.bounds)
             .iter()
             .filter(|&next_pos| -> bool {
                 if target_value > start_value {
-                    *self.value(next_pos) == start_value + 1
+                    *topography.value(next_pos) == start_value + 1
                 } else {
-                    *self.value(next_pos) == start_value - 1
+                    *topography.value(next_pos) == start_value - 1
                 }
             })
             .map(|next_pos| -> HashSet<ValidPosition> {
-                self.targets_reachable_by_trail(*next_pos, target_value)
+                targets_reachable_by_trail(topography, *next_pos, target_value)
             })
             .flatten()
             .collect()
     }
 
-    fn trail_score(&self) -> usize {
-        self.find(&0)
-            .iter()
-            .map(|&zero| -> usize { self.targets_reachable_by_trail(zero, 9).len() })
-            .sum()
-    }
-
-    fn partial_trail_rating(&self, start: ValidPosition, target_value: Height) -> usize {
-        let start_value = *self.value(&start);
+    fn partial_trail_rating(
+        topography: &Topography,
+        start: ValidPosition,
+        target_value: Height,
+    ) -> usize {
+        let start_value = *topography.value(&start);
         if start_value == target_value {
             return 1;
         }
 
         start
-            .valid_neighbours(&self.bounds)
+            .valid_neighbours(&topography.bounds)
             .iter()
             .filter(|&next_pos| -> bool {
                 if target_value > start_value {
-                    *self.value(next_pos) == start_value + 1
+                    *topography.value(next_pos) == start_value + 1
                 } else {
-                    *self.value(next_pos) == start_value - 1
+                    *topography.value(next_pos) == start_value - 1
                 }
             })
-            .map(|next_pos| -> usize { self.partial_trail_rating(*next_pos, target_value) })
+            .map(|next_pos| -> usize { partial_trail_rating(topography, *next_pos, target_value) })
             .sum()
     }
 
-    fn trail_rating(&self) -> usize {
-        self.find(&0)
-            .iter()
-            .map(|&zero| -> usize { self.partial_trail_rating(zero, 9) })
-            .sum()
+    #[test]
+    fn test_sweep_against_recursion() {
+        // every cell one higher than the one above and the one to its left, up to the summits
+        let dense = Topography(Grid::from_fn(Bounds(10, 10), |ValidPosition(x, y)| {
+            (x + y).min(9) as Height
+        }));
+        let example = Topography::from_file(&test_input(10, 1)).unwrap();
+        for topography in [dense, example] {
+            let (summits, ratings) = (topography.reachable_summits(), topography.ratings());
+            for zero in topography.find(&0) {
+                assert_eq!(
+                    *summits.value(&zero),
+                    targets_reachable_by_trail(&topography, zero, 9)
+                );
+                assert_eq!(
+                    *ratings.value(&zero),
+                    partial_trail_rating(&topography, zero, 9)
+                );
+            }
+        }
     }
-}
-
-fn part1(path: &str) -> usize {
-    Topography::from_file(path).trail_score()
-}
-
-fn part2(path: &str) -> usize {
-    Topography::from_file(path).trail_rating()
-}
-
-fn main() {
-    println!("Answer to part 1:");
-    println!("{}", part1("input/input10.txt"));
-    println!("Answer to part 2:");
-    println!("{}", part2("input/input10.txt"));
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input10.txt.test1"), 36);
+        assert_eq!(part1(&test_input(10, 1)), 36);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input10.txt.test1"), 81);
+        assert_eq!(part2(&test_input(10, 1)), 81);
     }
 }
@@ -1,34 +1,32 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use rusty_advent_2024::utils::file_io;
+use rusty_advent_2024::utils::file_io::{self, AocError};
 
 type BigNumber = u64;
 type StoneList = Vec<BigNumber>;
 type StoneMap = HashMap<BigNumber, usize>;
 
-fn stone_list_from_file(path: &str) -> StoneList {
-    file_io::lines_from_file(path)
+fn stone_list_from_file(path: &str) -> Result<StoneList, AocError> {
+    Ok(file_io::strings_from_file(path)?
         .map(|line| {
-            line.unwrap()
-                .split_whitespace()
+            line.split_whitespace()
                 .map(|word| -> BigNumber { word.parse().expect("Error parsing word {word}.") })
                 .collect_vec()
         })
         .flatten()
-        .collect()
+        .collect())
 }
 
-fn stone_map_from_file(path: &str) -> StoneMap {
-    file_io::lines_from_file(path)
+fn stone_map_from_file(path: &str) -> Result<StoneMap, AocError> {
+    Ok(file_io::strings_from_file(path)?
         .map(|line| {
-            line.unwrap()
-                .split_whitespace()
+            line.split_whitespace()
                 .map(|word| -> BigNumber { word.parse().expect("Error parsing word {word}.") })
                 .collect_vec()
         })
         .flatten()
-        .counts()
+        .counts())
 }
 
 fn even_number_of_digits(value: &BigNumber) -> bool {
@@ -86,7 +84,7 @@ fn blink_list(stone_list: StoneList) -> StoneList {
 }
 
 fn part1(path: &str) -> usize {
-    let mut stone_list: StoneList = stone_list_from_file(path);
+    let mut stone_list: StoneList = stone_list_from_file(path).expect("Could not read input.");
     for _ in 1..=25 {
         stone_list = blink_list(stone_list);
     }
@@ -94,7 +92,7 @@ fn part1(path: &str) -> usize {
 }
 
 fn part2(path: &str) -> usize {
-    let mut stone_map: StoneMap = stone_map_from_file(path);
+    let mut stone_map: StoneMap = stone_map_from_file(path).expect("Could not read input.");
 
     for _ in 1..=75 {
         stone_map = blink_map(stone_map);
@@ -113,6 +111,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_blink() {
@@ -124,6 +123,6 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input11.txt.test1"), 55312);
+        assert_eq!(part1(&test_input(11, 1)), 55312);
     }
 }
@@ -1,84 +1,16 @@
-use itertools::Itertools;
 use rusty_advent_2024::utils::file_io;
-use rusty_advent_2024::utils::map2d::direction::Direction;
 use rusty_advent_2024::utils::map2d::grid::Grid;
 use rusty_advent_2024::utils::map2d::position::Position;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use rusty_advent_2024::utils::map2d::region::Region;
+use rusty_advent_2024::utils::viz::image::{self, Hues, Palette, Rgb};
+use std::collections::{HashMap, HashSet};
 
 type Plant = char;
 type Field = Grid<Plant>;
 #[derive(Debug)]
 struct Plot {
     _plant_type: char,
-    plants: HashSet<Position>,
-}
-
-impl Plot {
-    fn area(&self) -> usize {
-        self.plants.len()
-    }
-
-    fn perimeter(&self) -> usize {
-        self.plants
-            .iter()
-            .map(|plant| -> usize {
-                plant
-                    .neighbours()
-                    .iter()
-                    .filter(|pos| !self.plants.contains(pos))
-                    .count()
-            })
-            .sum()
-    }
-
-    // For each Direction, store the positions who have a boundary that way
-    fn boundary_map(&self) -> HashMap<Direction, HashSet<Position>> {
-        let mut boundary_map: HashMap<Direction, HashSet<Position>> = HashMap::new();
-
-        for direction in Direction::iter_all() {
-            boundary_map.insert(
-                direction,
-                self.plants
-                    .iter()
-                    .copied()
-                    .filter(|pos| !self.plants.contains(&pos.step(&direction)))
-                    .collect(),
-            );
-        }
-
-        boundary_map
-    }
-
-    fn sides(&self) -> usize {
-        let boundary_map = self.boundary_map();
-        let mut sides: HashMap<Direction, usize> = HashMap::new();
-        // now find contiguous groups in the boundary_map
-        // easier to search as we only go straight, no flooding needed
-        for (dir, set) in boundary_map {
-            let mut visited: HashSet<Position> = HashSet::new();
-            let search_dirs = [dir.turned_left(), dir.turned_right()];
-            for pos in &set {
-                if !visited.insert(pos.clone()) {
-                    continue;
-                }
-
-                // explore side
-                for search_dir in search_dirs {
-                    let mut search_pos = pos.clone();
-                    while set.contains(&search_pos) {
-                        visited.insert(search_pos);
-                        search_pos = search_pos.step(&search_dir);
-                    }
-                }
-
-                // record side
-                *sides.entry(dir).or_insert(0) += 1;
-            }
-        }
-
-        sides.values().sum()
-    }
+    region: Region,
 }
 
 fn find_plots(field: &Field) -> Vec<Plot> {
@@ -91,14 +23,14 @@ fn find_plots(field: &Field) -> Vec<Plot> {
 
         let plot = Plot {
             _plant_type: *field.value(&pos),
-            plants: field
+            region: field
                 .contiguous_region(&pos)
                 .iter()
                 .map(|pos| (*pos).into())
                 .collect(),
         };
 
-        recorded_plants.extend(plot.plants.iter().copied());
+        recorded_plants.extend(plot.region.positions.iter().copied());
         plots.push(plot);
     }
 
@@ -106,24 +38,43 @@ fn find_plots(field: &Field) -> Vec<Plot> {
 }
 
 fn part1(path: &str) -> usize {
-    let field: Field = Grid::from(file_io::strings_from_file(path).collect_vec());
+    let field: Field =
+        Grid::from_rows(file_io::strings_from_file(path).expect("Could not read input."));
     let plots: Vec<Plot> = find_plots(&field);
     plots
         .iter()
-        .map(|plot| -> usize { plot.area() * plot.perimeter() })
+        .map(|plot| -> usize { plot.region.area() * plot.region.perimeter() })
         .sum()
 }
 
 fn part2(path: &str) -> usize {
-    let field: Field = Grid::from(file_io::strings_from_file(path).collect_vec());
+    let field: Field =
+        Grid::from_rows(file_io::strings_from_file(path).expect("Could not read input."));
     let plots: Vec<Plot> = find_plots(&field);
     plots
         .iter()
-        .map(|plot| -> usize { plot.area() * plot.sides() })
+        .map(|plot| -> usize { plot.region.area() * plot.region.sides() })
         .sum()
 }
 
+// Each plot in a colour of its own, e.g. `cargo run --bin day12 -- --render plots.png`
+fn plot_colors(field: &Field) -> Grid<Rgb> {
+    let plot_of: HashMap<Position, usize> = find_plots(field)
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, plot)| plot.region.positions.iter().map(move |&pos| (pos, idx)))
+        .collect();
+    Grid::from_fn(field.bounds, |pos| Hues.color(plot_of[&pos.into()]))
+}
+
 fn main() {
+    if let Some(path) = image::render_path() {
+        let field: Field = Grid::from_rows(
+            file_io::strings_from_file("input/input12.txt").expect("Could not read input."),
+        );
+        image::save(&plot_colors(&field), path, 4).expect("Could not write image.");
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input12.txt"));
     println!("Answer to part 2:");
@@ -133,19 +84,44 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::{fixtures::test_input, map2d::grid::ValidPosition};
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input12.txt.test1"), 140);
-        assert_eq!(part1("input/input12.txt.test2"), 772);
-        assert_eq!(part1("input/input12.txt.test3"), 1930);
+        assert_eq!(part1(&test_input(12, 1)), 140);
+        assert_eq!(part1(&test_input(12, 2)), 772);
+        assert_eq!(part1(&test_input(12, 3)), 1930);
+    }
+
+    #[test]
+    fn test_sides_match_corners() {
+        for test in 1..=5 {
+            let field: Field =
+                Grid::from_rows(file_io::strings_from_file(test_input(12, test)).unwrap());
+            for plot in find_plots(&field) {
+                assert_eq!(plot.region.sides(), plot.region.corners());
+            }
+        }
+    }
+
+    #[test]
+    fn test_plot_colors() {
+        let field: Field = Grid::from_rows(file_io::strings_from_file(test_input(12, 2)).unwrap());
+        let colors = plot_colors(&field);
+        // the O plot surrounds four X plots
+        let distinct: HashSet<Rgb> = colors.data.iter().flatten().copied().collect();
+        assert_eq!(distinct.len(), 5);
+        assert_eq!(
+            colors.value(&ValidPosition(0, 0)),
+            colors.value(&ValidPosition(4, 4))
+        );
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input12.txt.test1"), 80);
-        assert_eq!(part2("input/input12.txt.test2"), 436);
-        assert_eq!(part2("input/input12.txt.test4"), 236);
-        assert_eq!(part2("input/input12.txt.test5"), 368);
+        assert_eq!(part2(&test_input(12, 1)), 80);
+        assert_eq!(part2(&test_input(12, 2)), 436);
+        assert_eq!(part2(&test_input(12, 4)), 236);
+        assert_eq!(part2(&test_input(12, 5)), 368);
     }
 }
@@ -1,7 +1,10 @@
 use itertools::Itertools;
 use num::Integer;
-use regex::{Captures, Regex};
-use rusty_advent_2024::utils::{file_io, math2d::IntVec2D};
+use rusty_advent_2024::utils::{
+    file_io::{self, AocError},
+    math::{diophantine, gcd},
+    mathnd::{IntVec2D, Mat2},
+};
 use std::cmp::min;
 
 type Coordinate = i128;
@@ -13,51 +16,17 @@ struct ClawMachine {
     prize: IntVec2D<Coordinate>,
 }
 
-trait IntoTuple<T> {
-    fn into_tuple(self) -> (T, T);
-}
-
-impl IntoTuple<Coordinate> for Captures<'_> {
-    fn into_tuple(self) -> (Coordinate, Coordinate) {
-        (
-            self.get(1)
-                .expect("Did not match first group.")
-                .as_str()
-                .parse()
-                .expect("Could not parse group 1."),
-            self.get(2)
-                .expect("Did not match second group.")
-                .as_str()
-                .parse()
-                .expect("Could not parse group 2."),
-        )
-    }
-}
-
 impl From<&str> for ClawMachine {
     fn from(data_string: &str) -> Self {
-        let button_a_pattern: Regex = Regex::new(r"Button A: X\+(\d+), Y\+(\d+)").unwrap();
-        let button_b_pattern: Regex = Regex::new(r"Button B: X\+(\d+), Y\+(\d+)").unwrap();
-        let prize_pattern: Regex = Regex::new(r"Prize: X=(\d+), Y=(\d+)").unwrap();
-
-        let button_a_match = button_a_pattern
-            .captures(data_string)
-            .expect("Button A data not found.");
-        let button_b_match = button_b_pattern
-            .captures(data_string)
-            .expect("Button B data not found.");
-        let prize_match = prize_pattern
-            .captures(data_string)
-            .expect("Prize data not found.");
-
-        let button_a_data: (Coordinate, Coordinate) = button_a_match.into_tuple();
-        let button_b_data: (Coordinate, Coordinate) = button_b_match.into_tuple();
-        let prize_data: (Coordinate, Coordinate) = prize_match.into_tuple();
+        let [a_0, a_1, b_0, b_1, p_0, p_1]: [i64; 6] = file_io::ints_in_line(data_string)
+            .expect("Machine data should fit into i64s.")
+            .try_into()
+            .expect("Machine data should consist of six integers.");
 
         ClawMachine {
-            a: IntVec2D::from(button_a_data),
-            b: IntVec2D::from(button_b_data),
-            prize: IntVec2D::from(prize_data),
+            a: IntVec2D(a_0 as Coordinate, a_1 as Coordinate),
+            b: IntVec2D(b_0 as Coordinate, b_1 as Coordinate),
+            prize: IntVec2D(p_0 as Coordinate, p_1 as Coordinate),
         }
     }
 }
@@ -66,29 +35,39 @@ fn cost<T: Integer + From<i32>>(press_a: T, press_b: T) -> T {
     press_a * 3.into() + press_b
 }
 
+// minimise cost(x, y) subject to a * x + b * y = p with x, y >= 0
+fn cheapest_win_1d(a: Coordinate, b: Coordinate, p: Coordinate) -> Option<Coordinate> {
+    diophantine::solve(a, b, p)
+        .and_then(|solutions| solutions.minimize(3, 1))
+        .map(|(press_a, press_b)| cost(press_a, press_b))
+}
+
 impl ClawMachine {
     fn cheapest_win(&self) -> Option<Coordinate> {
-        let IntVec2D(a_0, a_1) = self.a;
-        let IntVec2D(b_0, b_1) = self.b;
-        let a_orth = IntVec2D(-a_1, a_0);
-        let b_orth = IntVec2D(-b_1, b_0);
-
-        let determinant = b_orth.dot(self.a);
-        if determinant != 0 {
+        let buttons = Mat2(self.a, self.b);
+        if buttons.determinant() != 0 {
             // a & b are not parallel: the solution is unique if it exists
-            let numerator = IntVec2D(b_orth.dot(self.prize), -a_orth.dot(self.prize));
-
-            if numerator.0 % determinant == 0 && numerator.1 % determinant == 0 {
-                let presses = numerator / determinant;
-                if presses.0 >= 0 && presses.1 >= 0 {
-                    return Some(cost(presses.0, presses.1));
-                }
-            }
-
-            None
+            buttons
+                .solve(self.prize)
+                .filter(|presses| presses.0 >= 0 && presses.1 >= 0)
+                .map(|presses| cost(presses.0, presses.1))
         } else {
-            // thankfully not needed for my inputs :D
-            todo!()
+            // a & b are parallel: the prize must lie on their common line
+            let zero = IntVec2D(0, 0);
+            if self.a == zero && self.b == zero {
+                return (self.prize == zero).then_some(0);
+            }
+            if Mat2(self.a, self.prize).determinant() != 0
+                || Mat2(self.b, self.prize).determinant() != 0
+            {
+                return None;
+            }
+            // everything is a multiple of the same direction, so one non-zero component suffices
+            if self.a.0 != 0 || self.b.0 != 0 {
+                cheapest_win_1d(self.a.0, self.b.0, self.prize.0)
+            } else {
+                cheapest_win_1d(self.a.1, self.b.1, self.prize.1)
+            }
         }
     }
 
@@ -97,10 +76,7 @@ impl ClawMachine {
         let IntVec2D(b_0, b_1) = self.b;
         let IntVec2D(p_0, p_1) = self.prize;
 
-        let gcd_0 = a_0.extended_gcd(&b_0);
-        let gcd_1 = a_1.extended_gcd(&b_1);
-
-        if p_0 % gcd_0.gcd != 0 || p_1 % gcd_1.gcd != 0 {
+        if p_0 % gcd(a_0, b_0) != 0 || p_1 % gcd(a_1, b_1) != 0 {
             return None;
         }
 
@@ -122,18 +98,15 @@ impl ClawMachine {
     }
 }
 
-fn claw_machines_from_file(path: &str) -> Vec<ClawMachine> {
-    let lines = file_io::lines_from_file(path).map(|line| line.unwrap());
-    lines
-        .chunks(4)
-        .into_iter()
-        .map(|mut paragraph| -> String { paragraph.join(" ") })
-        .map(|data_string| ClawMachine::from(data_string.as_str()))
+fn claw_machines_from_file(path: &str) -> Result<Vec<ClawMachine>, AocError> {
+    // three lines per machine and a blank line between machines
+    file_io::chunks_of_lines(path, 4)?
+        .map_ok(|chunk| ClawMachine::from(chunk.join(" ").as_str()))
         .collect()
 }
 
 fn part1(path: &str) -> Coordinate {
-    let machines = claw_machines_from_file(path);
+    let machines = claw_machines_from_file(path).expect("Could not read input.");
     machines
         .iter()
         .filter_map(|machine| machine.cheapest_win_easy())
@@ -141,10 +114,10 @@ fn part1(path: &str) -> Coordinate {
 }
 
 fn part2(path: &str) -> Coordinate {
-    let mut machines = claw_machines_from_file(path);
-    machines.iter_mut().for_each(|machine| {
-        machine.prize = machine.prize + IntVec2D(10000000000000, 10000000000000)
-    });
+    let mut machines = claw_machines_from_file(path).expect("Could not read input.");
+    machines
+        .iter_mut()
+        .for_each(|machine| machine.prize += IntVec2D(10000000000000, 10000000000000));
 
     machines
         .iter()
@@ -162,9 +135,42 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input13.txt.test1"), 480);
+        assert_eq!(part1(&test_input(13, 1)), 480);
+    }
+
+    fn machine(
+        a: (Coordinate, Coordinate),
+        b: (Coordinate, Coordinate),
+        prize: (Coordinate, Coordinate),
+    ) -> ClawMachine {
+        ClawMachine {
+            a: a.into(),
+            b: b.into(),
+            prize: prize.into(),
+        }
+    }
+
+    #[test]
+    fn test_parallel_buttons() {
+        // B is cheaper per step
+        assert_eq!(machine((1, 1), (2, 2), (10, 10)).cheapest_win(), Some(5));
+        assert_eq!(machine((3, 3), (2, 2), (7, 7)).cheapest_win(), Some(5));
+        // A covers enough distance to be cheaper
+        assert_eq!(machine((10, 0), (1, 0), (20, 0)).cheapest_win(), Some(6));
+        assert_eq!(machine((0, 2), (0, 3), (0, 7)).cheapest_win(), Some(7));
+        // prize off the line, or not reachable in whole steps
+        assert_eq!(machine((1, 1), (2, 2), (7, 8)).cheapest_win(), None);
+        assert_eq!(machine((2, 2), (4, 4), (5, 5)).cheapest_win(), None);
+        // reachable only with negative presses
+        assert_eq!(machine((2, 2), (3, 3), (1, 1)).cheapest_win(), None);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&test_input(13, 1)), 875318608908);
     }
 }
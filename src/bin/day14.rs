@@ -1,150 +1,199 @@
-use std::collections::HashMap;
-
-use itertools::Itertools;
-use regex::Regex;
-use rusty_advent_2024::utils::{file_io, math2d::IntVec2D};
-
-type Number = i32;
-
-#[derive(Debug)]
-struct Robot {
-    pos: IntVec2D<Number>,
-    vel: IntVec2D<Number>,
-}
-
-struct Torus(Number, Number);
+use std::{collections::HashMap, ops::RangeInclusive};
+
+use rusty_advent_2024::utils::{
+    cli::{self, Extension, Extensions},
+    map2d::grid::{Bounds, Grid, ValidPosition},
+    math::{crt, stats::variance},
+    swarm::{Number, RobotSwarm},
+    viz::{
+        dump_dir,
+        image::{self, Rgb},
+        record_path,
+        terminal::{Animation, FPS},
+        FrameDump, Recorder, FRAME_FORMAT,
+    },
+};
+
+const EXTENSIONS: [Extension; 4] = [
+    Extension {
+        name: "width",
+        default: "101",
+    },
+    Extension {
+        name: "height",
+        default: "103",
+    },
+    FPS,
+    FRAME_FORMAT,
+];
+
+// The number of robots on each tile, '.' for none and '+' for more than 9
+fn robot_grid(swarm: &RobotSwarm) -> Grid<char> {
+    let mut multiplicity: HashMap<ValidPosition, u32> = HashMap::new();
+    for pos in swarm.positions() {
+        *multiplicity.entry(pos).or_insert(0) += 1;
+    }
 
-#[derive(PartialEq, Eq, Hash)]
-enum Quadrant {
-    TopLeft,
-    TopRight,
-    BottomLeft,
-    BottomRight,
+    Grid::from_fn(swarm.torus(), |pos| match multiplicity.get(&pos) {
+        None => '.',
+        Some(&num) => char::from_digit(num, 10).unwrap_or('+'),
+    })
 }
 
-impl Robot {
-    fn move_on_torus(&mut self, seconds: Number, torus: &Torus) {
-        self.pos = self.pos + self.vel * seconds;
-        self.pos.0 = (self.pos.0 % torus.0 + torus.0) % torus.0;
-        self.pos.1 = (self.pos.1 % torus.1 + torus.1) % torus.1;
-    }
+fn part1(path: &str, torus: Bounds) -> usize {
+    let mut swarm = RobotSwarm::from_file(path, torus).expect("Could not read input.");
+    swarm.advance(100);
+    swarm.safety_factor()
 }
 
-fn torus_print(robots: &Vec<Robot>, torus: &Torus) {
-    let mut multiplicity: HashMap<IntVec2D<Number>, usize> = HashMap::new();
-    for robot in robots {
-        *multiplicity.entry(robot.pos).or_insert(0) += 1;
-    }
-
-    print!(
-        "{}",
-        (0..torus.1)
-            .map(|y| -> String {
-                (0..torus.0)
-                    .map(|x| -> String {
-                        multiplicity
-                            .get(&IntVec2D(x, y))
-                            .map_or(String::from("."), |num| num.to_string())
-                    })
-                    .join("")
+// The picture clusters the robots, which shows as a drop in variance of either coordinate.
+// x repeats every width seconds and y every height seconds, so find both and combine.
+fn most_clustered_time(swarm: &RobotSwarm) -> Number {
+    let Bounds(width, height) = swarm.torus();
+    let most_clustered = |period: usize, coordinate: fn(ValidPosition) -> usize| {
+        (0..period)
+            .map(|seconds| {
+                let spread = variance(swarm.positions_after(seconds as Number).map(coordinate))
+                    .expect("Need at least one robot.");
+                (seconds, spread)
             })
-            .join("\n")
-    );
-    println!();
-    println!();
-    println!();
-    println!();
-    println!();
-    println!();
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect("Period must be positive.")
+            .0 as u128
+    };
+
+    crt(
+        &[
+            most_clustered(width, |pos| pos.0),
+            most_clustered(height, |pos| pos.1),
+        ],
+        &[width as u128, height as u128],
+    )
+    .expect("Torus dimensions should be coprime.") as Number
 }
 
-fn robots_from_file(path: &str) -> Vec<Robot> {
-    let pattern =
-        Regex::new(r"p=(.*?),(.*?) v=(.*?),(.*?)$").expect("Creation of regex pattern failed.");
-
-    let lines = file_io::lines_from_file(path).map(|line| line.unwrap());
-
-    lines
-        .map(|line| -> Robot {
-            let captures = pattern
-                .captures(line.as_str())
-                .expect("Robot data could not be detected.");
-            let integer_data: [Number; 4] = captures
-                .extract()
-                .1
-                .map(|capture| -> Number { capture.parse().expect("Could not parse integer.") });
-
-            Robot {
-                pos: IntVec2D(integer_data[0], integer_data[1]),
-                vel: IntVec2D(integer_data[2], integer_data[3]),
-            }
-        })
-        .collect()
+fn part2(path: &str, torus: Bounds) -> Number {
+    let swarm = RobotSwarm::from_file(path, torus).expect("Could not read input.");
+    most_clustered_time(&swarm)
 }
 
-fn safety_factor(robots: Vec<Robot>, torus: &Torus) -> Number {
-    let mut robots_per_quadrant: HashMap<Quadrant, Number> = HashMap::new();
-
-    for robot in robots {
-        let IntVec2D(x, y) = robot.pos;
-        if x < torus.0 / 2 {
-            if y < torus.1 / 2 {
-                *robots_per_quadrant.entry(Quadrant::TopLeft).or_insert(0) += 1;
-            } else if y > torus.1 / 2 {
-                *robots_per_quadrant.entry(Quadrant::BottomLeft).or_insert(0) += 1;
-            }
-        } else if x > torus.0 / 2 {
-            if y < torus.1 / 2 {
-                *robots_per_quadrant.entry(Quadrant::TopRight).or_insert(0) += 1;
-            } else if y > torus.1 / 2 {
-                *robots_per_quadrant
-                    .entry(Quadrant::BottomRight)
-                    .or_insert(0) += 1;
-            }
-        }
-    }
-
-    robots_per_quadrant.values().product()
+// The picture in part 2, e.g. `cargo run --bin day14 -- --render tree.png`
+fn render(path: &str, torus: Bounds, image_path: &str) {
+    let mut swarm = RobotSwarm::from_file(path, torus).expect("Could not read input.");
+    swarm.advance(most_clustered_time(&swarm));
+    let picture = robot_grid(&swarm).map(color);
+    image::save(&picture, image_path, 4).expect("Could not write image.");
 }
 
-fn advance_pack(robots: &mut [Robot], seconds: Number, torus: &Torus) {
-    for robot in robots {
-        robot.move_on_torus(seconds, &torus);
+fn color(c: &char) -> Rgb {
+    match c {
+        '.' => Rgb::BLACK,
+        _ => Rgb(40, 200, 80),
     }
 }
 
-fn part1(path: &str, torus: Torus) -> Number {
-    let mut robots = robots_from_file(path);
-    advance_pack(&mut robots, 100, &torus);
-    safety_factor(robots, &torus)
-}
-
-fn part2(path: &str, torus: Torus) -> String {
-    let mut robots = robots_from_file(path);
-    let not_the_answer = 6900;
-    advance_pack(&mut robots, not_the_answer, &torus);
-    for i in 1..=200 {
-        println!("{}:", i + 6900);
-        advance_pack(&mut robots, 1, &torus);
-        torus_print(&robots, &torus);
+// Calls `on_second` with the robots at each of the seconds, given relative to the picture in
+// part 2
+fn evolve(
+    path: &str,
+    torus: Bounds,
+    seconds: RangeInclusive<Number>,
+    mut on_second: impl FnMut(Number, &RobotSwarm),
+) {
+    let mut swarm = RobotSwarm::from_file(path, torus).expect("Could not read input.");
+    let picture = most_clustered_time(&swarm);
+    let start = (picture + seconds.start()).max(0);
+    swarm.advance(start);
+    for second in start..=picture + seconds.end() {
+        on_second(second, &swarm);
+        swarm.advance(1);
     }
-
-    String::from("Look for the ||s and =s")
 }
 
 fn main() {
+    let extensions = Extensions::from_args(&EXTENSIONS);
+    let torus = || Bounds(extensions.get("width"), extensions.get("height"));
+
+    // e.g. `cargo run --release --bin day14 -- --visualize --fps 60`; Enter pauses
+    if cli::flag("visualize") {
+        let mut animation = Animation::new(extensions.get("fps")).pause_on_enter();
+        evolve(
+            "input/input14.txt",
+            torus(),
+            Number::MIN..=0,
+            |second, swarm| {
+                let caption = format!("After {} seconds", second);
+                animation
+                    .draw(&robot_grid(swarm), &caption)
+                    .expect("Could not draw frame.");
+            },
+        );
+        return;
+    }
+    // The minute around the picture, e.g. `cargo run --bin day14 -- --record tree.gif`
+    if let Some(path) = record_path() {
+        let mut recorder = Recorder::new(2, extensions.get("fps"));
+        evolve("input/input14.txt", torus(), -30..=30, |_, swarm| {
+            recorder.record(&robot_grid(swarm).map(color))
+        });
+        recorder.save(path).expect("Could not write animation.");
+        return;
+    }
+    // Every second up to the picture, one file each, e.g.
+    // `cargo run --release --bin day14 -- --dump-frames frames/`
+    if let Some(dir) = dump_dir() {
+        let format: String = extensions.get("frame-format");
+        let mut frames = FrameDump::new(dir, &format, 2).expect("Could not create directory.");
+        evolve("input/input14.txt", torus(), Number::MIN..=0, |_, swarm| {
+            frames
+                .dump(&robot_grid(swarm), color)
+                .expect("Could not write frame.");
+        });
+        return;
+    }
+    if let Some(image_path) = image::render_path() {
+        render("input/input14.txt", torus(), &image_path);
+        return;
+    }
+
     println!("Answer to part 1:");
-    println!("{}", part1("input/input14.txt", Torus(101, 103)));
-    println!("Good luck with part 2!");
-    println!("{}", part2("input/input14.txt", Torus(101, 103)));
+    println!("{}", part1("input/input14.txt", torus()));
+    println!("Answer to part 2:");
+    println!("{}", part2("input/input14.txt", torus()));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
+    use rusty_advent_2024::utils::{fixtures::test_input, mathnd::IntVec2D, swarm::Robot};
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input14.txt.test1", Torus(11, 7)), 12);
+        assert_eq!(part1(&test_input(14, 1), Bounds(11, 7)), 12);
+    }
+
+    #[test]
+    fn test_robot_grid() {
+        let swarm = RobotSwarm::from_file(&test_input(14, 1), Bounds(11, 7)).unwrap();
+        let grid = robot_grid(&swarm).pretty_print_string();
+        // as drawn in the puzzle
+        assert_eq!(grid.lines().next(), Some("1.12......."));
+        assert_eq!(grid.lines().count(), 7);
+    }
+
+    #[test]
+    fn test_most_clustered_time() {
+        // every robot passes through the centre after 30 seconds
+        let robots: Vec<Robot> = (1..=10)
+            .cartesian_product(1..=6)
+            .map(|(vx, vy)| Robot {
+                pos: IntVec2D(5, 3),
+                vel: IntVec2D(vx, vy),
+            })
+            .collect();
+        let mut swarm = RobotSwarm::new(robots, Bounds(11, 7));
+        swarm.advance(-30);
+        assert_eq!(most_clustered_time(&swarm), 30);
     }
 }
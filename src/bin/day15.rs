@@ -1,320 +1,179 @@
-use itertools::Itertools;
 use rusty_advent_2024::utils::{
-    file_io,
-    map2d::{
-        direction::Direction,
-        grid::{Convert, Grid, ToChar, ValidPosition},
+    cli::{self, Extensions},
+    map2d::direction::Direction,
+    viz::{
+        dump_dir,
+        image::Rgb,
+        record_path,
+        terminal::{keys, Animation, Key, RawInput, FPS},
+        FrameDump, Recorder, FRAME_FORMAT,
     },
+    warehouse::{load_input, HalfTile, IsTile, Player, Tile, Warehouse},
 };
-use std::collections::HashSet;
+use std::io;
 
-#[derive(PartialEq, Clone, Copy)]
-enum Tile {
-    Empty,
-    Box,
-    Wall,
-}
+fn part1(path: &str) -> usize {
+    let (mut warehouse, instructions): (Warehouse<Tile>, _) =
+        load_input(path).expect("Could not read input.");
 
-impl From<char> for Tile {
-    fn from(c: char) -> Self {
-        match c {
-            '#' => Self::Wall,
-            'O' => Self::Box,
-            _ => Self::Empty,
-        }
+    for direction in instructions {
+        warehouse.try_step(direction);
     }
-}
 
-#[derive(PartialEq, Clone, Copy)]
-enum HalfTile {
-    Empty,
-    BoxHalfLeft,
-    BoxHalfRight,
-    Wall,
+    warehouse.gps()
 }
 
-impl ToChar for HalfTile {
-    fn to_char(self: &Self) -> char {
-        match self {
-            Self::Wall => '#',
-            Self::BoxHalfLeft => '[',
-            Self::BoxHalfRight => ']',
-            Self::Empty => '.',
-        }
-    }
-}
+// Part 2, calling `on_step` after every move with the number of moves made and the last one
+fn simulate(path: &str, mut on_step: impl FnMut(usize, Direction, &Warehouse<HalfTile>)) -> usize {
+    let (mut warehouse, instructions): (Warehouse<HalfTile>, _) =
+        load_input(path).expect("Could not read input.");
 
-impl From<char> for HalfTile {
-    fn from(c: char) -> Self {
-        match c {
-            '#' => Self::Wall,
-            '[' => Self::BoxHalfLeft,
-            ']' => Self::BoxHalfRight,
-            _ => Self::Empty,
-        }
+    for (step, direction) in instructions.into_iter().enumerate() {
+        warehouse.try_step(direction);
+        on_step(step + 1, direction, &warehouse);
     }
-}
 
-trait IsTile {
-    fn process_input_line(line: &str) -> String;
-    fn adds_to_gps(&self) -> bool;
+    warehouse.gps()
 }
-impl IsTile for Tile {
-    fn process_input_line(line: &str) -> String {
-        line.into()
-    }
 
-    fn adds_to_gps(&self) -> bool {
-        *self == Self::Box
-    }
+fn part2(path: &str) -> usize {
+    simulate(path, |_, _, _| ())
 }
-impl IsTile for HalfTile {
-    fn process_input_line(line: &str) -> String {
-        line.replace(".", "..")
-            .replace("O", "[]")
-            .replace("#", "##")
-            .replace("@", "@.")
-    }
 
-    fn adds_to_gps(&self) -> bool {
-        *self == Self::BoxHalfLeft
+fn color(c: &char) -> Rgb {
+    match c {
+        '#' => Rgb(90, 90, 100),
+        '[' | ']' | 'O' => Rgb(200, 140, 60),
+        '@' => Rgb(230, 40, 40),
+        _ => Rgb::BLACK,
     }
 }
 
-struct Warehouse<T: IsTile> {
-    room: Grid<T>,
-    robot: ValidPosition,
-}
+// Drives the robot with the arrow keys; u or backspace takes a move back, q quits
+fn play<T: IsTile>(path: &str) -> io::Result<()> {
+    let (warehouse, _): (Warehouse<T>, _) = load_input(path).expect("Could not read input.");
+    let mut player = Player::new(warehouse);
+    let mut animation = Animation::new(0);
+    let _raw_input = RawInput::enable()?;
 
-impl Warehouse<Tile> {
-    fn try_step(&mut self, direction: Direction) -> bool {
-        self.try_move(self.robot, direction)
-            .then(|| {
-                self.robot = self
-                    .robot
-                    .try_step(&direction, &self.room.bounds)
-                    .expect("Error executing robot step.")
-            })
-            .is_some()
-    }
-
-    fn try_move(&mut self, start_pos: ValidPosition, direction: Direction) -> bool {
-        let start_value = *self.room.value(&start_pos);
-        start_pos
-            .try_step(&direction, &self.room.bounds)
-            .and_then(|next_pos| {
-                let next_value = *self.room.value(&next_pos);
-                match next_value {
-                    Tile::Empty => Some(next_pos),
-                    Tile::Box if self.try_move(next_pos, direction) => Some(next_pos),
-                    _ => None,
-                }
-            })
-            .map(|next_pos| {
-                *self.room.value_mut(&next_pos) = start_value;
-            })
-            .is_some()
-    }
-}
-
-impl Warehouse<HalfTile> {
-    fn try_step(&mut self, direction: Direction) -> bool {
-        match direction {
-            Direction::RIGHT | Direction::LEFT => self.try_move_horizontally(self.robot, direction),
-            Direction::UP | Direction::DOWN => {
-                self.try_move_vertically([self.robot].into(), direction)
+    let mut keys = keys(io::stdin().lock());
+    loop {
+        let caption = format!(
+            "Moves {}    GPS {}    (arrows move, u undoes, q quits)",
+            player.moves(),
+            player.warehouse().gps()
+        );
+        animation.draw(&player.warehouse().frame(), &caption)?;
+        match keys.next() {
+            Some(Key::Arrow(direction)) => {
+                player.step(direction);
             }
-        }
-        .then(|| {
-            self.robot = self
-                .robot
-                .try_step(&direction, &self.room.bounds)
-                .expect("Error executing robot step.")
-        })
-        .is_some()
-    }
-
-    fn try_move_horizontally(&mut self, start_pos: ValidPosition, direction: Direction) -> bool {
-        let start_value = *self.room.value(&start_pos);
-        start_pos
-            .try_step(&direction, &self.room.bounds)
-            .and_then(|next_pos| {
-                let next_value = *self.room.value(&next_pos);
-                match next_value {
-                    HalfTile::Empty => Some(next_pos),
-                    HalfTile::BoxHalfLeft | HalfTile::BoxHalfRight
-                        if self.try_move_horizontally(next_pos, direction) =>
-                    {
-                        Some(next_pos)
-                    }
-                    _ => None,
-                }
-            })
-            .map(|next_pos| {
-                *self.room.value_mut(&next_pos) = start_value;
-            })
-            .is_some()
-    }
-
-    fn try_move_vertically(
-        &mut self,
-        start_positions: HashSet<ValidPosition>,
-        direction: Direction,
-    ) -> bool {
-        if start_positions.is_empty() {
-            return true;
-        }
-
-        // we go row-by-row here. nothing can move unless everything moves, so must check first, then move
-        // 1. collect obstacles in next row
-        let mut obstacles: HashSet<ValidPosition> = HashSet::new();
-        for start_pos in &start_positions {
-            let next_pos = start_pos
-                .try_step(&direction, &self.room.bounds)
-                .expect("Stepped out of bounds - invalid state.");
-            let next_value = *self.room.value(&next_pos);
-            match next_value {
-                HalfTile::Wall => return false,
-                HalfTile::BoxHalfLeft => {
-                    obstacles.insert(next_pos);
-                    obstacles.insert(
-                        next_pos
-                            .try_step(&Direction::RIGHT, &self.room.bounds)
-                            .expect("Box did not have right half - invalid state."),
-                    );
-                }
-                HalfTile::BoxHalfRight => {
-                    obstacles.insert(next_pos);
-                    obstacles.insert(
-                        next_pos
-                            .try_step(&Direction::LEFT, &self.room.bounds)
-                            .expect("Box did not have right half - invalid state."),
-                    );
-                }
-                _ => (),
-            };
-        }
-
-        // 2. try move obstacles, move if possible
-        // TODO: refactor - have already computed next_pos
-        self.try_move_vertically(obstacles, direction)
-            .then(|| {
-                for start_pos in start_positions {
-                    let next_pos = start_pos
-                        .try_step(&direction, &self.room.bounds)
-                        .expect("Stepped out of bounds - invalid state.");
-                    let start_value = *self.room.value(&start_pos);
-                    *self.room.value_mut(&next_pos) = start_value;
-                    *self.room.value_mut(&start_pos) = HalfTile::Empty;
-                }
-            })
-            .is_some()
-    }
-}
-
-impl<T: IsTile> Warehouse<T> {
-    fn gps(self) -> usize {
-        self.room
-            .position_iter()
-            .filter(|pos| T::adds_to_gps(self.room.value(pos)))
-            .map(|ValidPosition(x, y)| x + 100 * y)
-            .sum()
-    }
-}
-
-impl<T: IsTile + ToChar> Warehouse<T> {
-    fn pretty_print(&self) {
-        let ValidPosition(robo_x, robo_y) = &self.robot;
-        for y in 0..self.room.bounds.1 {
-            for x in 0..self.room.bounds.0 {
-                if (x, y) == (*robo_x, *robo_y) {
-                    print!("@");
-                } else {
-                    print!("{}", (*self.room.value(&ValidPosition(x, y))).to_char());
-                }
+            Some(Key::Char('u' | '\x7f')) => {
+                player.undo();
             }
-            print!("\n");
-        }
-    }
-}
-
-fn load_input<T: IsTile + From<char>>(path: &str) -> (Warehouse<T>, Vec<Direction>) {
-    let mut lines = file_io::strings_from_file(path);
-
-    let map: Grid<char> = lines
-        .by_ref()
-        .take_while(|line| !line.is_empty())
-        .map(|line| T::process_input_line(&line))
-        .collect_vec()
-        .into();
-
-    let instructions: Vec<Direction> = lines
-        .join("")
-        .chars()
-        .map(|c| -> Direction { c.into() })
-        .collect();
-
-    let robot: ValidPosition = map
-        .find(&'@')
-        .drain()
-        .exactly_one()
-        .expect("Could not find unique robot position.");
-
-    let warehouse = Warehouse {
-        robot,
-        room: map.convert(),
-    };
-
-    (warehouse, instructions)
-}
-
-fn part1(path: &str) -> usize {
-    let (mut warehouse, instructions): (Warehouse<Tile>, _) = load_input(path);
-
-    for direction in instructions {
-        warehouse.try_step(direction);
-    }
-
-    warehouse.gps()
-}
-
-fn part2(path: &str, debug: bool) -> usize {
-    let (mut warehouse, instructions): (Warehouse<HalfTile>, _) = load_input(path);
-
-    if debug {
-        println!("Initial:");
-        warehouse.pretty_print();
-    }
-    for direction in instructions {
-        warehouse.try_step(direction);
-        if debug {
-            println!("Step: {:?}", direction);
-            warehouse.pretty_print();
+            Some(Key::Char('q')) | None => return Ok(()),
+            _ => (),
         }
     }
-
-    warehouse.gps()
 }
 
 fn main() {
+    // e.g. `cargo run --bin day15 -- --play`, or `--play --wide` for the warehouse in part 2
+    if cli::flag("play") {
+        let path = "input/input15.txt";
+        let played = if cli::flag("wide") {
+            play::<HalfTile>(path)
+        } else {
+            play::<Tile>(path)
+        };
+        played.expect("Could not play in this terminal.");
+        return;
+    }
+    // Part 2 step by step, e.g. `cargo run --bin day15 -- --visualize --fps 30`; Enter pauses
+    if cli::flag("visualize") {
+        let extensions = Extensions::from_args(&[FPS]);
+        let mut animation = Animation::new(extensions.get("fps")).pause_on_enter();
+        simulate("input/input15.txt", |step, direction, warehouse| {
+            let caption = format!("Step {}: {}    GPS {}", step, direction, warehouse.gps());
+            animation
+                .draw(&warehouse.frame(), &caption)
+                .expect("Could not draw frame.");
+        });
+        return;
+    }
+    // e.g. `cargo run --release --bin day15 -- --record warehouse.gif --fps 50`
+    if let Some(path) = record_path() {
+        let extensions = Extensions::from_args(&[FPS]);
+        let mut recorder = Recorder::new(4, extensions.get("fps"));
+        simulate("input/input15.txt", |_, _, warehouse| {
+            recorder.record(&warehouse.frame().map(color))
+        });
+        recorder.save(path).expect("Could not write animation.");
+        return;
+    }
+    // e.g. `cargo run --release --bin day15 -- --dump-frames frames/`
+    if let Some(dir) = dump_dir() {
+        let extensions = Extensions::from_args(&[FRAME_FORMAT]);
+        let format: String = extensions.get("frame-format");
+        let mut frames = FrameDump::new(dir, &format, 4).expect("Could not create directory.");
+        simulate("input/input15.txt", |_, _, warehouse| {
+            frames
+                .dump(&warehouse.frame(), color)
+                .expect("Could not write frame.");
+        });
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input15.txt"));
     println!("Answer to part 2:");
-    println!("{}", part2("input/input15.txt", false));
+    println!("{}", part2("input/input15.txt"));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
+    use rusty_advent_2024::utils::map2d::history::GridHistory;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input15.txt.test1"), 2028);
-        assert_eq!(part1("input/input15.txt.test2"), 10092);
+        assert_eq!(part1(&test_input(15, 1)), 2028);
+        assert_eq!(part1(&test_input(15, 2)), 10092);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input15.txt.test2", false), 9021);
+        assert_eq!(part2(&test_input(15, 2)), 9021);
+        let mut recorder = Recorder::new(1, 10);
+        simulate(&test_input(15, 2), |_, _, warehouse| {
+            recorder.record(&warehouse.frame().map(color))
+        });
+        assert_eq!(recorder.frame_count(), 700);
+        assert!(recorder.to_gif().is_some());
+    }
+
+    #[test]
+    fn test_frame() {
+        let (warehouse, _): (Warehouse<Tile>, _) = load_input(&test_input(15, 1)).unwrap();
+        let frame = warehouse.frame().pretty_print_string();
+        assert_eq!(frame.lines().nth(2), Some("##@.O..#"));
+        assert_eq!(frame.matches('O').count(), 6);
+    }
+
+    #[test]
+    fn test_undo_all_steps() {
+        let (mut warehouse, instructions): (Warehouse<HalfTile>, _) =
+            load_input(&test_input(15, 2)).unwrap();
+        let initial_room = warehouse.room.clone();
+        let mut history = GridHistory::new(warehouse.room.clone());
+
+        for direction in instructions {
+            warehouse.try_step(direction);
+            history.record(&warehouse.room);
+        }
+        assert!(!initial_room.diff(history.current()).is_empty());
+
+        while history.undo().is_some() {}
+        assert!(initial_room.diff(history.current()).is_empty());
     }
 }
@@ -3,12 +3,16 @@ use std::{
     collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet},
 };
 
-use itertools::Itertools;
 use rusty_advent_2024::utils::{
-    file_io,
+    cli,
+    file_io::{self, AocError, HasCharConverter},
     map2d::{
         direction::Direction,
-        grid::{Convert, Grid, ValidPosition},
+        grid::{Grid, ToChar, ValidPosition},
+    },
+    viz::{
+        image::{self, Rgb},
+        overlay::Overlay,
     },
 };
 
@@ -18,12 +22,22 @@ enum Field {
     Wall,
 }
 
-impl From<char> for Field {
-    fn from(c: char) -> Self {
+// S and E are taken out as markers before conversion
+impl HasCharConverter for Field {
+    fn convert(c: char) -> Result<Self, String> {
         match c {
-            '#' => Self::Wall,
-            '.' | 'S' | 'E' => Self::Empty,
-            _ => panic!("Invalid character for maze field."),
+            '#' => Ok(Self::Wall),
+            '.' => Ok(Self::Empty),
+            _ => Err(String::from("expected a maze field ('#' or '.')")),
+        }
+    }
+}
+
+impl ToChar for Field {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Wall => '#',
+            Self::Empty => '.',
         }
     }
 }
@@ -94,7 +108,7 @@ impl Maze {
         reindeers
     }
 
-    fn score_and_best_seats(&self) -> (usize, usize) {
+    fn score_and_best_seats(&self) -> (usize, HashSet<ValidPosition>) {
         let mut reindeers: BinaryHeap<Reverse<Reindeer>> = BinaryHeap::new();
         let mut min_score_map: HashMap<(ValidPosition, Direction), usize> = HashMap::new();
 
@@ -140,43 +154,65 @@ impl Maze {
         }
 
         if let Some(min_total) = min_total {
-            (min_total, best_seats.len())
+            (min_total, best_seats)
         } else {
             panic!("No path found!");
         }
     }
 }
 
-fn load_maze(path: &str) -> Maze {
-    let char_grid: Grid<char> = file_io::strings_from_file(path).collect_vec().into();
-    let start = *char_grid
-        .find(&'S')
-        .iter()
-        .exactly_one()
-        .expect("There should be exactly one S in the input.");
-    let end = *char_grid
-        .find(&'E')
-        .iter()
-        .exactly_one()
-        .expect("There should be exactly one E in the input.");
-    Maze {
-        field: char_grid.convert(),
-        start,
-        end,
-    }
+fn load_maze(path: &str) -> Result<Maze, AocError> {
+    let (field, markers) = file_io::grid_with_markers(path, &['S', 'E'])?;
+    let [start] = markers[&'S'][..] else {
+        panic!("There should be exactly one S in the input.");
+    };
+    let [end] = markers[&'E'][..] else {
+        panic!("There should be exactly one E in the input.");
+    };
+    Ok(Maze { field, start, end })
 }
 
 fn part1(path: &str) -> usize {
-    let maze = load_maze(path);
+    let maze = load_maze(path).expect("Could not read input.");
     maze.score_and_best_seats().0
 }
 
 fn part2(path: &str) -> usize {
-    let maze = load_maze(path);
-    maze.score_and_best_seats().1
+    let maze = load_maze(path).expect("Could not read input.");
+    maze.score_and_best_seats().1.len()
+}
+
+// The maze with every seat on a best path highlighted
+fn best_seat_overlay(maze: &Maze) -> Grid<Rgb> {
+    let best_seats = maze.score_and_best_seats().1;
+    Grid::from_fn(maze.field.bounds, |pos| match maze.field.value(&pos) {
+        Field::Wall => Rgb(60, 60, 70),
+        Field::Empty if best_seats.contains(&pos) => Rgb(240, 170, 30),
+        Field::Empty => Rgb::WHITE,
+    })
+}
+
+// The best seats marked O, as in the puzzle
+fn best_seat_map(maze: &Maze) -> Overlay {
+    let best_seats = maze.score_and_best_seats().1;
+    Overlay::new(&maze.field)
+        .cells(&best_seats, 'O', Rgb(240, 170, 30))
+        .cells(&[maze.start], 'S', Rgb(230, 40, 40))
+        .cells(&[maze.end], 'E', Rgb(230, 40, 40))
 }
 
 fn main() {
+    if cli::flag("visualize") {
+        let maze = load_maze("input/input16.txt").expect("Could not read input.");
+        println!("{}", best_seat_map(&maze).to_ansi());
+        return;
+    }
+    // e.g. `cargo run --release --bin day16 -- --render seats.svg`
+    if let Some(path) = image::render_path() {
+        let maze = load_maze("input/input16.txt").expect("Could not read input.");
+        image::save(&best_seat_overlay(&maze), path, 4).expect("Could not write image.");
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input16.txt"));
     println!("Answer to part 2:");
@@ -186,16 +222,39 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input16.txt.test1"), 7036);
-        assert_eq!(part1("input/input16.txt.test2"), 11048);
+        assert_eq!(part1(&test_input(16, 1)), 7036);
+        assert_eq!(part1(&test_input(16, 2)), 11048);
+    }
+
+    #[test]
+    fn test_best_seat_overlay() {
+        let maze = load_maze(&test_input(16, 1)).unwrap();
+        let overlay = best_seat_overlay(&maze);
+        let highlighted = overlay
+            .data
+            .iter()
+            .flatten()
+            .filter(|&&color| color == Rgb(240, 170, 30))
+            .count();
+        assert_eq!(highlighted, 45);
+        assert_eq!(overlay.value(&maze.start), &Rgb(240, 170, 30));
+    }
+
+    #[test]
+    fn test_best_seat_map() {
+        let maze = load_maze(&test_input(16, 1)).unwrap();
+        let map = best_seat_map(&maze).chars().pretty_print_string();
+        assert_eq!(map.matches('O').count(), 43);
+        assert_eq!(map.lines().nth(13), Some("#S..#.....#OOO#"));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input16.txt.test1"), 45);
-        assert_eq!(part2("input/input16.txt.test2"), 64);
+        assert_eq!(part2(&test_input(16, 1)), 45);
+        assert_eq!(part2(&test_input(16, 2)), 64);
     }
 }
@@ -1,21 +1,46 @@
 use std::{
     cmp::{Ordering, Reverse},
-    collections::{hash_map::Entry, BinaryHeap, HashMap},
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
 };
 
-use itertools::Itertools;
-use num::abs;
 use rusty_advent_2024::utils::{
-    file_io,
-    map2d::grid::{Bounds, Grid, ValidPosition},
+    cli::{self, Extension, Extensions},
+    file_io::{self, AocError},
+    map2d::grid::{Bounds, Grid, ToChar, ValidPosition},
+    viz::{
+        heatmap,
+        image::{self, Rgb},
+        overlay::Overlay,
+        terminal::to_ansi_256,
+    },
 };
 
+const EXTENSIONS: [Extension; 2] = [
+    Extension {
+        name: "grid_size",
+        default: "71",
+    },
+    Extension {
+        name: "fallen_bytes",
+        default: "1024",
+    },
+];
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Memory {
     Working,
     Corrupted,
 }
 
+impl ToChar for Memory {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Working => '.',
+            Self::Corrupted => '#',
+        }
+    }
+}
+
 #[derive(Debug)]
 struct MemorySpace {
     field: Grid<Memory>,
@@ -26,6 +51,7 @@ struct MemorySpace {
 #[derive(Debug)]
 struct Runner {
     pos: ValidPosition,
+    from: Option<ValidPosition>,
     time_elapsed: usize,
     time_expected: usize,
 }
@@ -58,7 +84,7 @@ impl Ord for Runner {
 
 impl MemorySpace {
     fn new(width: usize, height: usize) -> Self {
-        let field = Grid::new(Bounds(width, height), Memory::Working);
+        let field = Grid::filled(Bounds(width, height), Memory::Working);
         MemorySpace {
             field,
             start: ValidPosition(0, 0),
@@ -71,8 +97,7 @@ impl MemorySpace {
     }
 
     fn heuristic(&self, pos: ValidPosition) -> usize {
-        (abs(pos.0 as isize - self.end.0 as isize) + abs(pos.1 as isize - self.end.1 as isize))
-            as usize
+        pos.manhattan_distance(&self.end)
     }
 
     fn next_steps(&self, runner: Runner) -> Vec<Runner> {
@@ -83,6 +108,7 @@ impl MemorySpace {
             .filter_map(|&pos| match self.field.value(&pos) {
                 Memory::Working => Some(Runner {
                     pos: pos.clone(),
+                    from: Some(runner.pos),
                     time_elapsed: runner.time_elapsed + 1,
                     time_expected: self.heuristic(pos),
                 }),
@@ -92,19 +118,32 @@ impl MemorySpace {
     }
 
     fn shortest_path(&self) -> Option<usize> {
+        self.shortest_route().map(|route| route.len() - 1)
+    }
+
+    // From start to end, both included
+    fn shortest_route(&self) -> Option<Vec<ValidPosition>> {
         let mut runners: BinaryHeap<Reverse<Runner>> = BinaryHeap::new();
         let mut fastest_arrival_map: HashMap<ValidPosition, usize> = HashMap::new();
+        let mut came_from: HashMap<ValidPosition, ValidPosition> = HashMap::new();
 
         runners.push(Reverse(Runner {
             pos: self.start,
+            from: None,
             time_elapsed: 0,
             time_expected: self.heuristic(self.start),
         }));
 
         while let Some(Reverse(runner)) = runners.pop() {
-            //dbg!(&runner);
             if runner.pos == self.end {
-                return Some(runner.time_elapsed);
+                let mut route = vec![self.end];
+                let mut pos = runner.from;
+                while let Some(prev) = pos {
+                    route.push(prev);
+                    pos = came_from.get(&prev).copied();
+                }
+                route.reverse();
+                return Some(route);
             }
 
             // 2. check in minimal score hashmap
@@ -119,6 +158,9 @@ impl MemorySpace {
                     empty_entry.insert(runner.time_elapsed);
                 }
             }
+            if let Some(from) = runner.from {
+                came_from.insert(runner.pos, from);
+            }
 
             for next_runner in self.next_steps(runner) {
                 runners.push(Reverse(next_runner));
@@ -128,6 +170,24 @@ impl MemorySpace {
         None
     }
 
+    // The number of steps from the start to every cell it can reach
+    fn distances(&self) -> Grid<Option<usize>> {
+        let mut distances = self.field.map(|_| None);
+        let mut queue = VecDeque::from([(self.start, 0)]);
+        *distances.value_mut(&self.start) = Some(0);
+        while let Some((pos, distance)) = queue.pop_front() {
+            for next_pos in pos.valid_neighbours(&self.field.bounds) {
+                if *self.field.value(&next_pos) == Memory::Working
+                    && distances.value(&next_pos).is_none()
+                {
+                    *distances.value_mut(&next_pos) = Some(distance + 1);
+                    queue.push_back((next_pos, distance + 1));
+                }
+            }
+        }
+        distances
+    }
+
     fn bulk_corrupt(&mut self, corruptions: &[(usize, usize)]) {
         for cor in corruptions {
             self.corrupt(&ValidPosition(cor.0, cor.1));
@@ -153,48 +213,103 @@ fn find_blocking_byte((width, height): (usize, usize), corruptions: &[(usize, us
     right
 }
 
-fn load_corruptions(path: &str) -> Vec<(usize, usize)> {
-    file_io::strings_from_file(path)
-        .map(|s| -> (usize, usize) {
-            s.split(",")
-                .map(|num| num.parse().expect("Number values should be parsable."))
-                .collect_tuple()
-                .expect("Each line should contain a pair of comma-separated numbers.")
-        })
-        .collect_vec()
+fn load_corruptions(path: &str) -> Result<Vec<(usize, usize)>, AocError> {
+    Ok(file_io::tuples_from_file::<usize, 2>(path, ",")?
+        .into_iter()
+        .map(|[x, y]| (x, y))
+        .collect())
 }
 
 fn part1(path: &str, (width, height): (usize, usize), fallen_bytes: usize) -> usize {
     let mut memory = MemorySpace::new(width, height);
-    let corruptions = load_corruptions(path);
+    let corruptions = load_corruptions(path).expect("Could not read input.");
     memory.bulk_corrupt(&corruptions[0..fallen_bytes]);
     memory.shortest_path().expect("No shortest path found!")
 }
 
 fn part2(path: &str, (width, height): (usize, usize)) -> (usize, usize) {
-    let corruptions = load_corruptions(path);
+    let corruptions = load_corruptions(path).expect("Could not read input.");
     let byte_idx = find_blocking_byte((width, height), &corruptions);
     corruptions[byte_idx]
 }
 
+// The route in part 1 over the corrupted memory
+fn route_map(path: &str, (width, height): (usize, usize), fallen_bytes: usize) -> Overlay {
+    let mut memory = MemorySpace::new(width, height);
+    let corruptions = load_corruptions(path).expect("Could not read input.");
+    memory.bulk_corrupt(&corruptions[0..fallen_bytes]);
+    let route = memory.shortest_route().unwrap_or_default();
+    Overlay::new(&memory.field).path(&route, Rgb(40, 200, 80))
+}
+
+// How far the start is in part 1, e.g. `cargo run --bin day18 -- --heatmap`, or add
+// `--render distances.png`
+fn show_distances(path: &str, (width, height): (usize, usize), fallen_bytes: usize) {
+    let mut memory = MemorySpace::new(width, height);
+    let corruptions = load_corruptions(path).expect("Could not read input.");
+    memory.bulk_corrupt(&corruptions[0..fallen_bytes]);
+    let colors = heatmap(&memory.distances());
+    match image::render_path() {
+        Some(image_path) => image::save(&colors, image_path, 4).expect("Could not write image."),
+        None => println!("{}", to_ansi_256(&colors)),
+    }
+}
+
 fn main() {
+    let extensions = Extensions::from_args(&EXTENSIONS);
+    let grid_size: usize = extensions.get("grid_size");
+    let fallen_bytes: usize = extensions.get("fallen_bytes");
+
+    if cli::flag("heatmap") {
+        show_distances("input/input18.txt", (grid_size, grid_size), fallen_bytes);
+        return;
+    }
+
+    if cli::flag("visualize") {
+        let map = route_map("input/input18.txt", (grid_size, grid_size), fallen_bytes);
+        println!("{}", map.to_ansi());
+        return;
+    }
+
     println!("Answer to part 1:");
-    println!("{}", part1("input/input18.txt", (71, 71), 1024));
+    println!(
+        "{}",
+        part1("input/input18.txt", (grid_size, grid_size), fallen_bytes)
+    );
     println!("Answer to part 2:");
-    println!("{:?}", part2("input/input18.txt", (71, 71)));
+    println!("{:?}", part2("input/input18.txt", (grid_size, grid_size)));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input18.txt.test1", (7, 7), 12), 22);
+        assert_eq!(part1(&test_input(18, 1), (7, 7), 12), 22);
+    }
+
+    #[test]
+    fn test_route_map() {
+        let map = route_map(&test_input(18, 1), (7, 7), 12);
+        let map = map.chars().pretty_print_string();
+        // every step but the last is an arrow
+        assert_eq!(map.matches(['^', '>', 'v', '<']).count(), 22);
+        assert!(map.starts_with(['>', 'v']));
+    }
+
+    #[test]
+    fn test_distances() {
+        let mut memory = MemorySpace::new(7, 7);
+        memory.bulk_corrupt(&load_corruptions(&test_input(18, 1)).unwrap()[0..12]);
+        let distances = memory.distances();
+        assert_eq!(*distances.value(&memory.end), Some(22));
+        assert_eq!(*distances.value(&ValidPosition(3, 0)), None);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input18.txt.test1", (7, 7)), (6, 1));
+        assert_eq!(part2(&test_input(18, 1), (7, 7)), (6, 1));
     }
 }
@@ -1,4 +1,7 @@
-use rusty_advent_2024::utils::file_io;
+use rusty_advent_2024::utils::{
+    file_io::{self, AocError},
+    graph::paths::count_paths_by,
+};
 use std::collections::HashMap;
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
@@ -79,34 +82,16 @@ impl PatternTrie {
         (1..pattern.len()).any(|i| self.contains(&pattern[i..]) && self.can_make(&pattern[..i]))
     }
 
+    // Splits of the design into towels are paths from offset 0 to the end
     fn ways_to_make(&self, pattern: SubPattern) -> usize {
-        let mut cache = HashMap::new();
-        self.cached_ways_to_make(pattern, &mut cache)
-    }
-
-    fn cached_ways_to_make(
-        &self,
-        pattern: SubPattern,
-        cache: &mut HashMap<Pattern, usize>,
-    ) -> usize {
-        if let Some(&stored_number) = cache.get(pattern) {
-            return stored_number;
-        }
-
-        if pattern.len() <= 1 {
-            return self.contains(&pattern).into();
-        }
-
-        let ways_to_make = (1..=pattern.len())
-            .map(|i| pattern.split_at(i))
-            .filter_map(|(left, right)| {
-                self.contains(left)
-                    .then_some(self.cached_ways_to_make(right, cache))
-            })
-            .sum();
-
-        cache.insert(pattern.to_vec(), ways_to_make);
-        return ways_to_make;
+        count_paths_by(
+            &0,
+            |&offset| offset == pattern.len(),
+            |&offset| {
+                (offset + 1..=pattern.len())
+                    .filter(move |&end| self.contains(&pattern[offset..end]))
+            },
+        )
     }
 }
 
@@ -130,28 +115,27 @@ fn pattern_from_word(word: &str) -> Pattern {
         .collect()
 }
 
-fn load_input(path: &str) -> (PatternTrie, Vec<Pattern>) {
-    let mut lines = file_io::strings_from_file(path);
+fn load_input(path: &str) -> Result<(PatternTrie, Vec<Pattern>), AocError> {
+    let (header, body) = file_io::two_sections(path)?;
 
-    let towels: Vec<Pattern> = lines
-        .next()
-        .unwrap()
+    let towels: Vec<Pattern> = header
         .split(",")
         .map(|word| -> Pattern { pattern_from_word(word) })
         .collect();
 
     let towel_trie: PatternTrie = PatternTrie::from(&towels);
 
-    let designs: Vec<Pattern> = lines
+    let designs: Vec<Pattern> = body
+        .lines()
         .filter(|line| !line.is_empty())
-        .map(|line| pattern_from_word(&line))
+        .map(pattern_from_word)
         .collect();
 
-    (towel_trie, designs)
+    Ok((towel_trie, designs))
 }
 
 fn part1(path: &str) -> usize {
-    let (towel_trie, designs) = load_input(path);
+    let (towel_trie, designs) = load_input(path).expect("Could not read input.");
 
     designs
         .iter()
@@ -160,7 +144,7 @@ fn part1(path: &str) -> usize {
 }
 
 fn part2(path: &str) -> usize {
-    let (towel_trie, designs) = load_input(path);
+    let (towel_trie, designs) = load_input(path).expect("Could not read input.");
 
     designs
         .iter()
@@ -178,6 +162,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     fn trie_from_string(pattern_string: &str) -> PatternTrie {
         let patterns: Vec<Pattern> = pattern_string
@@ -247,11 +232,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input19.txt.test1"), 6);
+        assert_eq!(part1(&test_input(19, 1)), 6);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input19.txt.test1"), 16);
+        assert_eq!(part2(&test_input(19, 1)), 16);
     }
 }
@@ -1,26 +1,44 @@
 use itertools::Itertools;
-use num::abs;
 use rusty_advent_2024::utils::{
-    file_io,
-    map2d::{
-        grid::{Convert, Grid, ValidPosition},
-        position::Position,
+    cli::{self, Extension, Extensions},
+    file_io::{self, AocError, HasCharConverter},
+    map2d::grid::{Grid, ToChar, ValidPosition},
+    viz::{
+        heatmap,
+        image::{self, Rgb},
+        overlay::{elbow, Overlay},
+        terminal::to_ansi_256,
     },
 };
 use std::collections::{HashMap, HashSet};
 
+const EXTENSIONS: [Extension; 1] = [Extension {
+    name: "min_saving",
+    default: "100",
+}];
+
 #[derive(Debug, Eq, PartialEq)]
 enum Field {
     Empty,
     Wall,
 }
 
-impl From<char> for Field {
-    fn from(c: char) -> Self {
+// S and E are taken out as markers before conversion
+impl HasCharConverter for Field {
+    fn convert(c: char) -> Result<Self, String> {
         match c {
-            '#' => Self::Wall,
-            '.' | 'S' | 'E' => Self::Empty,
-            _ => panic!("Invalid character for racetrack field."),
+            '#' => Ok(Self::Wall),
+            '.' => Ok(Self::Empty),
+            _ => Err(String::from("expected a racetrack field ('#' or '.')")),
+        }
+    }
+}
+
+impl ToChar for Field {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Wall => '#',
+            Self::Empty => '.',
         }
     }
 }
@@ -40,8 +58,7 @@ struct Cheat {
 
 impl Cheat {
     fn min_duration(&self) -> usize {
-        (abs(self.start.0 as i32 - self.end.0 as i32)
-            + abs(self.start.1 as i32 - self.end.1 as i32)) as usize
+        self.start.manhattan_distance(&self.end)
     }
 }
 
@@ -79,143 +96,60 @@ impl RaceTrack {
             .collect()
     }
 
-    fn valid_neighbours_2(&self, pos: ValidPosition) -> Vec<ValidPosition> {
-        [
-            (2, 0),
-            (1, 1),
-            (0, 2),
-            (-1, 1),
-            (-2, 0),
-            (-1, -1),
-            (0, -2),
-            (1, -1),
-        ]
-        .iter()
-        .map(|(dx, dy)| Position(pos.0 as i32 + dx, pos.1 as i32 + dy))
-        .filter_map(|pos| pos.in_bounds(&self.field.bounds))
-        .collect()
-    }
-
-    const DX_DY_NEIGHBOURS_20: [(i32, i32); 841] = {
-        let mut arr = [(0, 0); 21 * 21 + 20 * 20];
-        let mut idx = 0;
-        let mut x = -20;
-        while x <= 20 {
-            let max_y: i32;
-            if x > 0 {
-                max_y = 20 - x;
-            } else {
-                max_y = 20 + x;
-            }
-            let mut y = -max_y;
-            while y <= max_y {
-                arr[idx] = (x, y);
-
-                idx += 1;
-                y += 1;
-            }
-            x += 1;
-        }
-        arr
-    };
-
-    fn valid_neighbours_20(&self, pos: ValidPosition) -> Vec<ValidPosition> {
-        Self::DX_DY_NEIGHBOURS_20
-            .iter()
-            .map(|(dx, dy)| Position(pos.0 as i32 + dx, pos.1 as i32 + dy))
-            .filter_map(|pos| pos.in_bounds(&self.field.bounds))
-            .collect()
-    }
-
-    fn cheats(&self) -> HashMap<usize, HashSet<Cheat>> {
+    fn cheats(&self, radius: usize) -> HashMap<usize, HashSet<Cheat>> {
         let timestamps = self.timestamp_map();
-        let mut cheats: HashMap<usize, HashSet<Cheat>> = HashMap::new();
-        for (start_pos, start_time) in &timestamps {
-            self.valid_neighbours_2(*start_pos)
-                .iter()
-                .filter_map(|end_pos| -> Option<(ValidPosition, usize)> {
-                    timestamps
-                        .get(end_pos)
-                        .and_then(|&time| Some((*end_pos, time)))
-                })
-                .filter_map(|(end_pos, end_time)| -> Option<(usize, Cheat)> {
-                    if end_time > start_time + 2 {
-                        Some((
-                            end_time - (start_time + 2),
-                            Cheat {
-                                start: *start_pos,
-                                end: end_pos,
-                            },
-                        ))
-                    } else {
-                        None
-                    }
-                })
-                .for_each(|(time_save, cheat)| {
-                    cheats
-                        .entry(time_save)
-                        .or_insert(HashSet::new())
-                        .insert(cheat);
-                })
-        }
-        cheats
-    }
-
-    fn big_cheats(&self) -> HashMap<usize, HashSet<Cheat>> {
-        let timestamps = self.timestamp_map();
-        let mut big_cheats: HashMap<usize, HashSet<Cheat>> = HashMap::new();
-        for (start_pos, start_time) in &timestamps {
-            self.valid_neighbours_20(*start_pos)
-                .iter()
-                .filter_map(|end_pos| -> Option<(ValidPosition, usize)> {
-                    timestamps
-                        .get(end_pos)
-                        .and_then(|&time| Some((*end_pos, time)))
-                })
-                .filter_map(|(end_pos, end_time)| -> Option<(usize, Cheat)> {
-                    let cheat = Cheat {
-                        start: *start_pos,
-                        end: end_pos,
-                    };
-                    if end_time > start_time + cheat.min_duration() {
-                        Some((end_time - (start_time + cheat.min_duration()), cheat))
-                    } else {
-                        None
-                    }
-                })
-                .for_each(|(time_save, cheat)| {
-                    big_cheats
-                        .entry(time_save)
-                        .or_insert(HashSet::new())
-                        .insert(cheat);
-                })
-        }
-        big_cheats
+        self.field.par_fold(
+            HashMap::new,
+            |mut cheats: HashMap<usize, HashSet<Cheat>>, start_pos, _| {
+                let Some(&start_time) = timestamps.get(&start_pos) else {
+                    return cheats;
+                };
+                start_pos
+                    .within_manhattan(radius, &self.field.bounds)
+                    .iter()
+                    .filter_map(|end_pos| -> Option<(ValidPosition, usize)> {
+                        timestamps.get(end_pos).map(|&time| (*end_pos, time))
+                    })
+                    .filter_map(|(end_pos, end_time)| -> Option<(usize, Cheat)> {
+                        let cheat = Cheat {
+                            start: start_pos,
+                            end: end_pos,
+                        };
+                        if end_time > start_time + cheat.min_duration() {
+                            Some((end_time - (start_time + cheat.min_duration()), cheat))
+                        } else {
+                            None
+                        }
+                    })
+                    .for_each(|(time_save, cheat)| {
+                        cheats.entry(time_save).or_default().insert(cheat);
+                    });
+                cheats
+            },
+            |mut cheats, other_cheats| {
+                for (time_save, cheat_set) in other_cheats {
+                    cheats.entry(time_save).or_default().extend(cheat_set);
+                }
+                cheats
+            },
+        )
     }
 }
 
-fn load_track(path: &str) -> RaceTrack {
-    let char_grid: Grid<char> = file_io::strings_from_file(path).collect_vec().into();
-    let start = *char_grid
-        .find(&'S')
-        .iter()
-        .exactly_one()
-        .expect("There should be exactly one S in the input.");
-    let end = *char_grid
-        .find(&'E')
-        .iter()
-        .exactly_one()
-        .expect("There should be exactly one E in the input.");
-    RaceTrack {
-        field: char_grid.convert(),
-        start,
-        end,
-    }
+fn load_track(path: &str) -> Result<RaceTrack, AocError> {
+    let (field, markers) = file_io::grid_with_markers(path, &['S', 'E'])?;
+    let [start] = markers[&'S'][..] else {
+        panic!("There should be exactly one S in the input.");
+    };
+    let [end] = markers[&'E'][..] else {
+        panic!("There should be exactly one E in the input.");
+    };
+    Ok(RaceTrack { field, start, end })
 }
 
 fn part1(path: &str, min_time_save: usize) -> usize {
-    let race_track = load_track(path);
-    let cheats = race_track.cheats();
+    let race_track = load_track(path).expect("Could not read input.");
+    let cheats = race_track.cheats(2);
     cheats
         .iter()
         .filter(|(&time_save, _)| time_save >= min_time_save)
@@ -224,8 +158,8 @@ fn part1(path: &str, min_time_save: usize) -> usize {
 }
 
 fn part2(path: &str, min_time_save: usize) -> usize {
-    let race_track = load_track(path);
-    let cheats = race_track.big_cheats();
+    let race_track = load_track(path).expect("Could not read input.");
+    let cheats = race_track.cheats(20);
     cheats
         .iter()
         .filter(|(&time_save, _)| time_save >= min_time_save)
@@ -233,21 +167,72 @@ fn part2(path: &str, min_time_save: usize) -> usize {
         .sum()
 }
 
+// The track with the cheat in part 2 that saves the most time drawn across it
+fn cheat_map(path: &str) -> Overlay {
+    let race_track = load_track(path).expect("Could not read input.");
+    let cheats = race_track.cheats(20);
+    let best_cheat = cheats
+        .iter()
+        .max_by_key(|(&time_save, _)| time_save)
+        .and_then(|(_, cheat_set)| {
+            cheat_set
+                .iter()
+                .min_by_key(|cheat| (cheat.start.1, cheat.start.0, cheat.end.1, cheat.end.0))
+        });
+    let overlay =
+        Overlay::new(&race_track.field).path(&race_track.single_path(), Rgb(60, 110, 200));
+    let overlay = match best_cheat {
+        Some(cheat) => overlay.path(&elbow(cheat.start, cheat.end), Rgb(230, 40, 40)),
+        None => overlay,
+    };
+    overlay
+        .cells([&race_track.start], 'S', Rgb(230, 40, 40))
+        .cells([&race_track.end], 'E', Rgb(230, 40, 40))
+}
+
+// When the track is passed without cheating, e.g. `cargo run --bin day20 -- --heatmap`, or add
+// `--render timestamps.png`
+fn show_timestamps(path: &str) {
+    let race_track = load_track(path).expect("Could not read input.");
+    let timestamps = race_track.timestamp_map();
+    let colors = heatmap(&Grid::from_fn(race_track.field.bounds, |pos| {
+        timestamps.get(&pos).copied()
+    }));
+    match image::render_path() {
+        Some(image_path) => image::save(&colors, image_path, 4).expect("Could not write image."),
+        None => println!("{}", to_ansi_256(&colors)),
+    }
+}
+
 fn main() {
+    let extensions = Extensions::from_args(&EXTENSIONS);
+    let min_saving: usize = extensions.get("min_saving");
+
+    if cli::flag("heatmap") {
+        show_timestamps("input/input20.txt");
+        return;
+    }
+
+    if cli::flag("visualize") {
+        println!("{}", cheat_map("input/input20.txt").to_ansi());
+        return;
+    }
+
     println!("Answer to part 1:");
-    println!("{}", part1("input/input20.txt", 100));
+    println!("{}", part1("input/input20.txt", min_saving));
     println!("Answer to part 2:");
-    println!("{}", part2("input/input20.txt", 100));
+    println!("{}", part2("input/input20.txt", min_saving));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        let race_track = load_track("input/input20.txt.test1");
-        let cheats = race_track.cheats();
+        let race_track = load_track(&test_input(20, 1)).unwrap();
+        let cheats = race_track.cheats(2);
         let cheat_nrs: HashMap<usize, usize> = cheats
             .iter()
             .map(|(&time_save, cheat_set)| (time_save, cheat_set.len()))
@@ -268,10 +253,19 @@ mod tests {
         assert_eq!(cheat_nrs.values().sum::<usize>(), 44);
     }
 
+    #[test]
+    fn test_cheat_map() {
+        let map = cheat_map(&test_input(20, 1)).chars().pretty_print_string();
+        // one of the 76 picosecond cheats, from S through the walls to just before E
+        assert_eq!(map.lines().nth(3), Some("#S>v>^#v#^#v<<#"));
+        assert_eq!(map.lines().nth(6), Some("###v###v#^###v#"));
+        assert_eq!(map.lines().nth(7), Some("###>>E#>>^#v<<#"));
+    }
+
     #[test]
     fn test_part2() {
-        let race_track = load_track("input/input20.txt.test1");
-        let cheats = race_track.big_cheats();
+        let race_track = load_track(&test_input(20, 1)).unwrap();
+        let cheats = race_track.cheats(20);
         let cheat_nrs: HashMap<usize, usize> = cheats
             .iter()
             .map(|(&time_save, cheat_set)| (time_save, cheat_set.len()))
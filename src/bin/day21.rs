@@ -4,8 +4,10 @@ use std::{
 };
 
 use itertools::Itertools;
-use num::abs;
-use rusty_advent_2024::utils::{file_io, math2d::IntVec2D};
+use rusty_advent_2024::utils::{
+    file_io::{self, AocError},
+    mathnd::IntVec2D,
+};
 use std::hash::Hash;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -127,15 +129,28 @@ impl From<char> for DirectionalKey {
 }
 
 impl DirectionalKey {
-    fn step(&self, pos: IntVec2D<i32>) -> IntVec2D<i32> {
+    // The unit step the key moves by; A stays put
+    fn offset(&self) -> IntVec2D<i32> {
         match self {
-            DirectionalKey::A => pos,
-            DirectionalKey::Right => IntVec2D(pos.0 + 1, pos.1),
-            DirectionalKey::Left => IntVec2D(pos.0 - 1, pos.1),
-            DirectionalKey::Up => IntVec2D(pos.0, pos.1 + 1),
-            DirectionalKey::Down => IntVec2D(pos.0, pos.1 - 1),
+            DirectionalKey::A => IntVec2D(0, 0),
+            DirectionalKey::Right => IntVec2D(1, 0),
+            DirectionalKey::Left => IntVec2D(-1, 0),
+            DirectionalKey::Up => IntVec2D(0, 1),
+            DirectionalKey::Down => IntVec2D(0, -1),
         }
     }
+
+    // The key for a unit step along one axis, or A for no step at all
+    fn from_offset(offset: IntVec2D<i32>) -> Self {
+        [Self::Right, Self::Left, Self::Up, Self::Down]
+            .into_iter()
+            .find(|key| key.offset() == offset)
+            .unwrap_or(Self::A)
+    }
+
+    fn step(&self, pos: IntVec2D<i32>) -> IntVec2D<i32> {
+        pos + self.offset()
+    }
 }
 
 trait KeypadKey:
@@ -145,40 +160,18 @@ trait KeypadKey:
         let start_pos: IntVec2D<i32> = start.clone().into();
         let end_pos: IntVec2D<i32> = end.clone().into();
 
-        let IntVec2D(dx, dy) = end_pos - start_pos;
-
-        if dy >= 0 {
-            if dx >= 0 {
-                // dx >= 0, dy >= 0 - move right then up
-                [
-                    [DirectionalKey::Right].repeat(dx as usize),
-                    [DirectionalKey::Up].repeat(dy as usize),
-                ]
-            } else {
-                // if legal, move left then up
-                [
-                    [DirectionalKey::Left].repeat(-dx as usize),
-                    [DirectionalKey::Up].repeat(dy as usize),
-                ]
-            }
-        } else {
-            if dx >= 0 {
-                // dx >= 0, dy < 0 - move right then down
-                [
-                    [DirectionalKey::Right].repeat(dx as usize),
-                    [DirectionalKey::Down].repeat(-dy as usize),
-                ]
-            } else {
-                // dx < 0, dy < 0 - move down then left
-                [
-                    [DirectionalKey::Down].repeat(-dy as usize),
-                    [DirectionalKey::Left].repeat(-dx as usize),
-                ]
-            }
-        }
+        let delta = end_pos - start_pos;
+        let IntVec2D(dx, dy) = delta;
+        let IntVec2D(step_x, step_y) = delta.signum();
+
+        // every order of the horizontal and vertical moves, as long as it stays on the keypad
+        [
+            [DirectionalKey::from_offset(IntVec2D(step_x, 0))].repeat(dx.unsigned_abs() as usize),
+            [DirectionalKey::from_offset(IntVec2D(0, step_y))].repeat(dy.unsigned_abs() as usize),
+        ]
         .concat()
         .into_iter()
-        .permutations(abs(dx) as usize + abs(dy) as usize)
+        .permutations(delta.manhattan() as usize)
         .filter(|seq| Self::is_valid_sequence(start_pos, seq))
         .map(|seq| [seq, vec![DirectionalKey::A]].concat())
         .collect()
@@ -330,8 +323,8 @@ impl<T: KeypadKey> Keypad<T> {
     }
 }
 
-fn load_data(path: &str) -> (Vec<Sequence<NumericKey>>, Vec<usize>) {
-    let strings = file_io::strings_from_file(path).collect_vec();
+fn load_data(path: &str) -> Result<(Vec<Sequence<NumericKey>>, Vec<usize>), AocError> {
+    let strings = file_io::strings_from_file(path)?.collect_vec();
     let codes: Vec<Sequence<NumericKey>> = strings
         .clone()
         .iter()
@@ -348,7 +341,7 @@ fn load_data(path: &str) -> (Vec<Sequence<NumericKey>>, Vec<usize>) {
                 .expect("First three characters of code must parse to number.")
         })
         .collect_vec();
-    (codes, numeric_parts)
+    Ok((codes, numeric_parts))
 }
 
 fn complexity(
@@ -375,7 +368,7 @@ fn _pretty_print(control_sequence: &Sequence<DirectionalKey>) {
 }
 
 fn part1(path: &str) -> usize {
-    let (codes, numeric_parts) = load_data(path);
+    let (codes, numeric_parts) = load_data(path).expect("Could not read input.");
 
     let handheld_keypad: Keypad<DirectionalKey> = Keypad::new();
     let freezing_keypad: Keypad<DirectionalKey> = Keypad::new().with_controller(handheld_keypad);
@@ -392,7 +385,7 @@ fn part1(path: &str) -> usize {
 }
 
 fn part2(path: &str) -> usize {
-    let (codes, numeric_parts) = load_data(path);
+    let (codes, numeric_parts) = load_data(path).expect("Could not read input.");
 
     let handheld_keypad: Keypad<DirectionalKey> = Keypad::new();
     let mut previous_keypad = handheld_keypad;
@@ -425,6 +418,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_2_keypads() {
@@ -441,6 +435,6 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input21.txt.test1"), 126384);
+        assert_eq!(part1(&test_input(21, 1)), 126384);
     }
 }
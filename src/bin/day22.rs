@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
-use rusty_advent_2024::utils::file_io;
+use rusty_advent_2024::utils::file_io::{self, AocError};
 
 const PRUNE_MASK: u32 = 0b111111111111111111111111;
 
@@ -42,18 +42,12 @@ fn sequence_scores(prices: &[i8]) -> HashMap<(i8, i8, i8, i8), u32> {
     scores
 }
 
-fn load_secrets(path: &str) -> Vec<u32> {
-    file_io::lines_from_file(path)
-        .map(|word| -> u32 {
-            word.unwrap()
-                .parse()
-                .expect("Each line should be a number.")
-        })
-        .collect()
+fn load_secrets(path: &str) -> Result<Vec<u32>, AocError> {
+    file_io::parse_lines(path)
 }
 
 fn part1(path: &str) -> u128 {
-    let mut secrets = load_secrets(path);
+    let mut secrets = load_secrets(path).expect("Could not read input.");
 
     for _ in 0..2000 {
         secrets.iter_mut().for_each(|secret| {
@@ -65,7 +59,7 @@ fn part1(path: &str) -> u128 {
 }
 
 fn part2(path: &str) -> u32 {
-    let secrets = load_secrets(path);
+    let secrets = load_secrets(path).expect("Could not read input.");
     let price_lists = secrets
         .iter()
         .map(|&secret| next_2000_prices(secret))
@@ -104,14 +98,15 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input22.txt.test1"), 37327623);
+        assert_eq!(part1(&test_input(22, 1)), 37327623);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input22.txt.test2"), 23);
+        assert_eq!(part2(&test_input(22, 2)), 23);
     }
 }
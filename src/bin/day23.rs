@@ -1,17 +1,26 @@
 use std::{
-    collections::{HashMap, HashSet},
-    hash::Hash,
+    collections::HashSet,
+    fmt::{self, Display},
 };
 
 use itertools::Itertools;
-use rusty_advent_2024::utils::file_io;
+use rusty_advent_2024::utils::{
+    cli,
+    file_io::{self, AocError},
+    graph::{
+        cliques::{max_clique, triangles},
+        dot::to_dot,
+        Graph,
+    },
+    viz::mermaid::{Flowchart, Shape},
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
 struct Computer(char, char);
 
 #[derive(Debug)]
 struct ComputerGraph {
-    data: HashMap<Computer, HashSet<Computer>>,
+    graph: Graph<Computer>,
 }
 
 impl From<(char, char)> for Computer {
@@ -20,9 +29,15 @@ impl From<(char, char)> for Computer {
     }
 }
 
+impl Display for Computer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.0, self.1)
+    }
+}
+
 impl ComputerGraph {
-    fn from_file(path: &str) -> ComputerGraph {
-        let edges: Vec<(Computer, Computer)> = file_io::strings_from_file(path)
+    fn from_file(path: &str) -> Result<ComputerGraph, AocError> {
+        let graph: Graph<Computer> = file_io::strings_from_file(path)?
             .map(|line: String| -> (Computer, Computer) {
                 line.split_once("-")
                     .map(|(str1, str2)| -> (Computer, Computer) {
@@ -43,99 +58,23 @@ impl ComputerGraph {
                     })
                     .expect("Computer names should be split by a single dash.")
             })
-            .collect_vec();
-
-        let mut graph: HashMap<Computer, HashSet<Computer>> = HashMap::new();
-        for (c1, c2) in edges {
-            graph.entry(c1).or_insert(HashSet::new()).insert(c2);
-            graph.entry(c2).or_insert(HashSet::new()).insert(c1);
-        }
-
-        ComputerGraph { data: graph }
-    }
-
-    fn find_threeway_games(&self, initial: char) -> HashSet<[Computer; 3]> {
-        let possible_computers = self
-            .data
-            .keys()
-            .filter(|Computer(init, _)| init == &initial);
-
-        let mut threeways: HashSet<[Computer; 3]> = HashSet::new();
-        for c1 in possible_computers {
-            let connected_computers = self.data.get(c1).unwrap();
-            for c in connected_computers.into_iter().combinations(2) {
-                let (c2, c3) = (c[0], c[1]);
-                if self
-                    .data
-                    .get(c2)
-                    .expect(
-                        "Every graph node should have its connections recorded in the graph data.",
-                    )
-                    .contains(c3)
-                {
-                    let mut threeway = [c1.clone(), c2.clone(), c3.clone()];
-                    threeway.sort();
-                    threeways.insert(threeway);
-                }
-            }
-        }
-
-        threeways
-    }
+            .collect();
 
-    fn pruned_bron_kerbosch(
-        &self,
-        clique: HashSet<Computer>,
-        candidates: HashSet<Computer>,
-        largest_found: usize,
-    ) -> Option<HashSet<Computer>> {
-        if clique.len() + candidates.len() <= largest_found {
-            // cannot find larger clique here
-            return None;
-        } else if candidates.is_empty() {
-            // unlike in normal bron_kerbosch, we don't need to check if forbiddens is empty here:
-            // this would already be handled by the previous if statement
-            return Some(clique);
-        }
-
-        let mut next_clique: HashSet<Computer> = clique.clone();
-        let mut best_clique: Option<HashSet<Computer>> = None;
-        let mut future_candidates = candidates.clone();
-        for c in candidates {
-            let largest_found = best_clique.as_ref().map_or(0, |best| best.len());
-
-            next_clique.insert(c);
-            let next_candidates: HashSet<Computer> = future_candidates
-                .intersection(self.data.get(&c).unwrap())
-                .cloned()
-                .collect();
-            if let Some(clique) =
-                self.pruned_bron_kerbosch(next_clique.clone(), next_candidates, largest_found)
-            {
-                if clique.len() > largest_found {
-                    best_clique = Some(clique);
-                }
-            }
-            next_clique.remove(&c);
-            future_candidates.remove(&c);
-        }
-
-        best_clique.clone()
+        Ok(ComputerGraph { graph })
     }
 
     fn largest_clique(&self) -> HashSet<Computer> {
-        self.pruned_bron_kerbosch(HashSet::new(), self.data.keys().cloned().collect(), 0)
-            .unwrap()
+        max_clique(&self.graph)
     }
 }
 
 fn part1(path: &str) -> usize {
-    let graph = ComputerGraph::from_file(path);
-    graph.find_threeway_games('t').len()
+    let graph = ComputerGraph::from_file(path).expect("Could not read input.");
+    triangles(&graph.graph, |Computer(initial, _)| *initial == 't').len()
 }
 
 fn part2(path: &str) -> String {
-    let graph = ComputerGraph::from_file(path);
+    let graph = ComputerGraph::from_file(path).expect("Could not read input.");
 
     graph
         .largest_clique()
@@ -145,7 +84,49 @@ fn part2(path: &str) -> String {
         .join(",")
 }
 
+// The network in Graphviz DOT, e.g. `cargo run --bin day23 -- --dot | dot -Tsvg > day23.svg`
+fn print_dot(path: &str) {
+    let graph = ComputerGraph::from_file(path).expect("Could not read input.");
+    println!(
+        "{}",
+        to_dot(&graph.graph, |Computer(c1, c2)| format!("{}{}", c1, c2))
+    );
+}
+
+// The network as a Mermaid flowchart, with the LAN party in a subgraph of its own
+fn mermaid_diagram(path: &str) -> String {
+    let graph = ComputerGraph::from_file(path).expect("Could not read input.");
+    let computers: Vec<&Computer> = graph.graph.nodes().sorted().collect();
+    // each edge is stored at both ends, but drawn once
+    let connections = computers.iter().flat_map(|&computer| {
+        graph
+            .graph
+            .neighbors(computer)
+            .filter(move |&other| computer < other)
+            .sorted()
+            .map(move |other| (computer, other))
+    });
+    Flowchart::new("LR")
+        .undirected()
+        .nodes(
+            computers.iter(),
+            |computer| computer.to_string(),
+            Shape::Box,
+        )
+        .subgraph("lan_party", graph.largest_clique().iter().sorted())
+        .edges(connections)
+        .to_string()
+}
+
 fn main() {
+    if cli::flag("dot") {
+        print_dot("input/input23.txt");
+        return;
+    }
+    if cli::flag("mermaid") {
+        println!("{}", mermaid_diagram("input/input23.txt"));
+        return;
+    }
     println!("Answer to part 1:");
     println!("{}", part1("input/input23.txt"));
     println!("Answer to part 2:");
@@ -155,14 +136,25 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::fixtures::test_input;
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input23.txt.test1"), 7);
+        assert_eq!(part1(&test_input(23, 1)), 7);
+    }
+
+    #[test]
+    fn test_mermaid_diagram() {
+        let mermaid = mermaid_diagram(&test_input(23, 1));
+        assert!(mermaid.contains(
+            "    subgraph lan_party\n        co\n        de\n        ka\n        ta\n    end"
+        ));
+        assert!(mermaid.contains("    co --- de"));
+        assert_eq!(mermaid.matches(" --- ").count(), 32);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2("input/input23.txt.test1"), "co,de,ka,ta");
+        assert_eq!(part2(&test_input(23, 1)), "co,de,ka,ta");
     }
 }
@@ -1,5 +1,5 @@
 use itertools::{Either, Itertools};
-use rusty_advent_2024::utils::file_io;
+use rusty_advent_2024::utils::file_io::{self, AocError};
 use std::{
     collections::{HashMap, HashSet},
     hash::Hash,
@@ -56,26 +56,16 @@ struct LockSmith {
 }
 
 impl LockSmith {
-    fn from_file(path: &str) -> Self {
-        let (locks, keys) = file_io::strings_from_file(path)
-            .chunk_by(|line| line.is_empty())
-            .into_iter()
-            .filter_map(|(is_empty, chunk)| {
-                if is_empty {
-                    None
-                } else {
-                    Some(chunk.collect_vec())
-                }
-            })
-            .partition_map(|block| {
-                if LockSmith::is_lock(&block) {
-                    Either::Left(LockSmith::get_counts(&block))
-                } else {
-                    Either::Right(LockSmith::get_counts(&block))
-                }
-            });
+    fn from_file(path: &str) -> Result<Self, AocError> {
+        let (locks, keys) = file_io::blocks_from_file(path)?.partition_map(|block| {
+            if LockSmith::is_lock(&block) {
+                Either::Left(LockSmith::get_counts(&block))
+            } else {
+                Either::Right(LockSmith::get_counts(&block))
+            }
+        });
 
-        LockSmith::new(locks, keys)
+        Ok(LockSmith::new(locks, keys))
     }
 
     fn new(locks: Vec<Lock>, keys: Vec<Key>) -> Self {
@@ -130,7 +120,8 @@ impl LockSmith {
         counts
     }
 
-    fn matching_locks(&self, key: &Key) -> usize {
+    // The locks the key fits: those that fit each of its pins
+    fn fitting_locks(&self, key: &Key) -> HashSet<Lock> {
         let mut sorted_lock_sets = pins(key)
             .iter()
             .map(|pin| self.locks_that_fit_pin.get(&pin))
@@ -148,7 +139,11 @@ impl LockSmith {
             }
         }
 
-        fitting_locks.len()
+        fitting_locks
+    }
+
+    fn matching_locks(&self, key: &Key) -> usize {
+        self.fitting_locks(key).len()
     }
 
     fn fitting_combinations(&mut self) -> usize {
@@ -157,7 +152,7 @@ impl LockSmith {
 }
 
 fn part1(path: &str) -> usize {
-    let mut locksmith = LockSmith::from_file(path);
+    let mut locksmith = LockSmith::from_file(path).expect("Could not read input.");
 
     locksmith.fitting_combinations()
 }
@@ -172,9 +167,60 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusty_advent_2024::utils::{fixtures::test_input, graph::matching::max_bipartite_matching};
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1("input/input25.txt.test1"), 3);
+        assert_eq!(part1(&test_input(25, 1)), 3);
+    }
+
+    #[test]
+    fn test_compatibility_graph() {
+        let locksmith = LockSmith::from_file(&test_input(25, 1)).unwrap();
+        assert_eq!(locksmith.locks, vec![[0, 5, 3, 4, 3], [1, 2, 0, 5, 3]]);
+        assert_eq!(
+            locksmith.keys,
+            vec![[5, 0, 2, 1, 3], [4, 3, 4, 0, 2], [3, 0, 2, 0, 1]]
+        );
+        // from the puzzle: the first key fits neither lock, the second only the second lock
+        let counts = locksmith
+            .keys
+            .iter()
+            .map(|key| locksmith.matching_locks(key))
+            .collect_vec();
+        assert_eq!(counts, vec![0, 1, 2]);
+
+        // keys and locks as the two sides of a bipartite graph, with an edge when they fit,
+        // which are the pairs the puzzle lists
+        let edges: HashSet<(Key, Lock)> = locksmith
+            .keys
+            .iter()
+            .flat_map(|&key| {
+                locksmith
+                    .fitting_locks(&key)
+                    .into_iter()
+                    .map(move |lock| (key, lock))
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            HashSet::from([
+                ([4, 3, 4, 0, 2], [1, 2, 0, 5, 3]),
+                ([3, 0, 2, 0, 1], [0, 5, 3, 4, 3]),
+                ([3, 0, 2, 0, 1], [1, 2, 0, 5, 3]),
+            ])
+        );
+
+        // both locks can be opened at once, by different keys
+        let matching = max_bipartite_matching(locksmith.keys.clone(), |key: &Key| {
+            locksmith.fitting_locks(key).into_iter().sorted()
+        });
+        assert_eq!(
+            matching,
+            vec![
+                ([4, 3, 4, 0, 2], [1, 2, 0, 5, 3]),
+                ([3, 0, 2, 0, 1], [0, 5, 3, 4, 3])
+            ]
+        );
     }
 }
@@ -1,9 +1,43 @@
 pub mod utils {
+    pub mod circuit;
+    pub mod cli;
+    pub mod disk;
     pub mod file_io;
+    pub mod fixtures;
+    pub mod graph;
+    pub mod lint;
+    pub mod lists;
     pub mod map2d {
+        pub mod bitgrid;
         pub mod direction;
         pub mod grid;
+        pub mod history;
         pub mod position;
+        pub mod region;
+        pub mod transform;
     }
-    pub mod math2d;
+    pub mod map3d {
+        pub mod grid;
+        pub mod position;
+    }
+    pub mod math;
+    pub mod mathnd;
+    pub mod ordering;
+    pub mod parse {
+        pub mod captures;
+        pub mod combinators;
+        pub mod mul_program;
+        pub mod numbers;
+    }
+    pub mod swarm;
+    pub mod vm {
+        pub mod assembler;
+        pub mod chronospatial;
+        pub mod debugger;
+        pub mod disasm;
+        pub mod search;
+        pub mod solver;
+    }
+    pub mod viz;
+    pub mod warehouse;
 }
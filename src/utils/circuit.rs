@@ -0,0 +1,1080 @@
+use crate::utils::{
+    file_io::{self, AocError},
+    graph::{dot::digraph_to_dot, scc, DiGraph},
+    viz::mermaid::{Flowchart, Shape},
+};
+use itertools::Itertools;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::Path,
+    str::FromStr,
+};
+
+pub mod adder;
+
+pub use adder::verify_adder;
+
+// A logic circuit as in day 24: input wires with given values, and gates whose outputs
+// drive every other wire. Numbers are read from and written to the wires x00, x01, ...,
+// y00, ... and z00, ..., least significant bit first.
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GateType {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
+    Not,
+    // constant sources without inputs
+    True,
+    False,
+}
+
+impl GateType {
+    pub const ALL: [GateType; 9] = [
+        GateType::And,
+        GateType::Or,
+        GateType::Xor,
+        GateType::Nand,
+        GateType::Nor,
+        GateType::Xnor,
+        GateType::Not,
+        GateType::True,
+        GateType::False,
+    ];
+
+    // How many inputs the gate takes
+    pub fn arity(&self) -> usize {
+        match self {
+            GateType::Not => 1,
+            GateType::True | GateType::False => 0,
+            _ => 2,
+        }
+    }
+
+    // Expects exactly `arity` inputs
+    pub fn apply(&self, inputs: &[bool]) -> bool {
+        match (self, inputs) {
+            (GateType::And, &[a, b]) => a & b,
+            (GateType::Or, &[a, b]) => a | b,
+            (GateType::Xor, &[a, b]) => a ^ b,
+            (GateType::Nand, &[a, b]) => !(a & b),
+            (GateType::Nor, &[a, b]) => !(a | b),
+            (GateType::Xnor, &[a, b]) => a == b,
+            (GateType::Not, &[a]) => !a,
+            (GateType::True, []) => true,
+            (GateType::False, []) => false,
+            _ => panic!(
+                "{} takes {} inputs, not {}.",
+                self,
+                self.arity(),
+                inputs.len()
+            ),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            GateType::And => "AND",
+            GateType::Or => "OR",
+            GateType::Xor => "XOR",
+            GateType::Nand => "NAND",
+            GateType::Nor => "NOR",
+            GateType::Xnor => "XNOR",
+            GateType::Not => "NOT",
+            GateType::True => "TRUE",
+            GateType::False => "FALSE",
+        }
+    }
+}
+
+impl Display for GateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for GateType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GateType::ALL
+            .into_iter()
+            .find(|op| op.name() == s)
+            .ok_or_else(|| format!("unknown gate type '{}'", s))
+    }
+}
+
+// An operation and its input wires; its output wire is the key it is stored under
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Gate {
+    pub op: GateType,
+    pub inputs: Vec<String>,
+}
+
+impl Gate {
+    // A two-input gate
+    pub fn new(a: &str, op: GateType, b: &str) -> Self {
+        Gate {
+            op,
+            inputs: vec![a.into(), b.into()],
+        }
+    }
+
+    pub fn not(a: &str) -> Self {
+        Gate {
+            op: GateType::Not,
+            inputs: vec![a.into()],
+        }
+    }
+
+    pub fn constant(value: bool) -> Self {
+        Gate {
+            op: if value {
+                GateType::True
+            } else {
+                GateType::False
+            },
+            inputs: vec![],
+        }
+    }
+
+    // The same gate with its inputs reversed, which computes the same value
+    // as all two-input operations are symmetric
+    pub fn mirror(&self) -> Self {
+        Gate {
+            op: self.op,
+            inputs: self.inputs.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+// As on the left of a gate line: "a AND b", "NOT a" or "TRUE"
+impl Display for Gate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.inputs[..] {
+            [a, b] => write!(f, "{} {} {}", a, self.op, b),
+            inputs => {
+                write!(f, "{}", self.op)?;
+                inputs.iter().try_for_each(|input| write!(f, " {}", input))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CircuitError {
+    // wires whose values depend on each other, one sorted list per loop
+    FeedbackLoops(Vec<Vec<String>>),
+    // a wire that is neither an input nor driven by a gate
+    Undriven(String),
+    // more inputs than a truth table was allowed
+    TooManyInputs(usize),
+}
+
+impl Display for CircuitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitError::FeedbackLoops(loops) => write!(
+                f,
+                "feedback loops: {}",
+                loops.iter().map(|wires| wires.join(" ")).join("; ")
+            ),
+            CircuitError::Undriven(wire) => write!(f, "wire {} has no value", wire),
+            CircuitError::TooManyInputs(count) => {
+                write!(f, "{} inputs are too many to try every combination", count)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    // 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// The name of bit `bit` of number `c`, e.g. wire('z', 3) is "z03"
+pub fn wire(c: char, bit: usize) -> String {
+    format!("{c}{bit:02}")
+}
+
+// Every wire numbered, and the gates in an order where each comes after the gates driving
+// its inputs, so that one pass evaluates the whole device
+#[derive(Clone, Debug)]
+struct Schedule {
+    wires: Vec<String>,
+    index: HashMap<String, usize>,
+    // output, operation and inputs of each gate
+    steps: Vec<(usize, GateType, Vec<usize>)>,
+}
+
+impl Schedule {
+    fn new(inputs: &HashMap<String, bool>, gates: &HashMap<String, Gate>) -> Option<Self> {
+        let wires: Vec<String> = inputs
+            .keys()
+            .chain(gates.keys())
+            .chain(gates.values().flat_map(|gate| &gate.inputs))
+            .unique()
+            .cloned()
+            .collect();
+        let index: HashMap<String, usize> = wires
+            .iter()
+            .enumerate()
+            .map(|(idx, wire)| (wire.clone(), idx))
+            .collect();
+
+        // inputs take precedence over gates driving the same wire
+        let driven: Vec<&String> = gates
+            .keys()
+            .filter(|wire| !inputs.contains_key(*wire))
+            .collect();
+        let mut waiting_on: HashMap<&str, usize> = HashMap::new();
+        let mut consumers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for &output in &driven {
+            for input in &gates[output].inputs {
+                if gates.contains_key(input) && !inputs.contains_key(input) {
+                    *waiting_on.entry(output).or_default() += 1;
+                    consumers.entry(input).or_default().push(output);
+                }
+            }
+        }
+        let mut ready: Vec<&str> = driven
+            .iter()
+            .map(|wire| wire.as_str())
+            .filter(|wire| !waiting_on.contains_key(wire))
+            .collect();
+        let mut steps = Vec::with_capacity(driven.len());
+        while let Some(output) = ready.pop() {
+            let gate = &gates[output];
+            let inputs = gate.inputs.iter().map(|input| index[input]).collect();
+            steps.push((index[output], gate.op, inputs));
+            for &consumer in consumers.get(output).into_iter().flatten() {
+                let count = waiting_on
+                    .get_mut(consumer)
+                    .expect("Consumers wait on inputs.");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(consumer);
+                }
+            }
+        }
+        // gates left over are on or behind a feedback loop
+        (steps.len() == driven.len()).then_some(Schedule {
+            wires,
+            index,
+            steps,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Device {
+    inputs: HashMap<String, bool>,
+    gates: HashMap<String, Gate>,
+    // worked out when first needed after the gates change
+    schedule: Option<Result<Schedule, CircuitError>>,
+    // values by wire number since the inputs or gates last changed; empty if not evaluated
+    values: Vec<Option<bool>>,
+}
+
+impl Device {
+    // Gates are keyed by the wire they drive
+    pub fn new(inputs: HashMap<String, bool>, gates: HashMap<String, Gate>) -> Self {
+        Device {
+            inputs,
+            gates,
+            schedule: None,
+            values: Vec::new(),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AocError> {
+        let text = file_io::string_from_file(&path)?;
+        text.parse().map_err(|err: ParseError| AocError::Parse {
+            path: path.as_ref().display().to_string(),
+            line: err.line,
+            message: err.message,
+        })
+    }
+
+    pub fn gates(&self) -> impl Iterator<Item = (&String, &Gate)> {
+        self.gates.iter()
+    }
+
+    // The gate driving a wire, if it is not an input
+    pub fn gate(&self, wire: &str) -> Option<&Gate> {
+        self.gates.get(wire)
+    }
+
+    pub fn gate_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    pub fn inputs(&self) -> &HashMap<String, bool> {
+        &self.inputs
+    }
+
+    // The number of x wires; y has as many
+    pub fn input_bits(&self) -> usize {
+        self.inputs
+            .keys()
+            .filter(|name| name.starts_with('x'))
+            .count()
+    }
+
+    // The number of z wires
+    pub fn output_bits(&self) -> usize {
+        self.gates
+            .keys()
+            .filter(|name| name.starts_with('z'))
+            .count()
+    }
+
+    pub fn set_input(&mut self, wire: &str, value: bool) {
+        if self.inputs.insert(wire.into(), value).is_none() {
+            self.schedule = None;
+        }
+        self.values.clear();
+    }
+
+    // Sets all input bits of x and y
+    pub fn set_x_y(&mut self, x: u64, y: u64) {
+        for bit in 0..self.input_bits() {
+            self.set_input(&wire('x', bit), (x >> bit & 1) == 1);
+            self.set_input(&wire('y', bit), (y >> bit & 1) == 1);
+        }
+    }
+
+    fn number_from(&self, c: char) -> u64 {
+        (0..)
+            .map_while(|bit| self.inputs.get(&wire(c, bit)))
+            .enumerate()
+            .map(|(bit, &value)| (value as u64) << bit)
+            .sum()
+    }
+
+    pub fn x(&self) -> u64 {
+        self.number_from('x')
+    }
+
+    pub fn y(&self) -> u64 {
+        self.number_from('y')
+    }
+
+    // Edges run from gate inputs to gate outputs
+    pub fn wiring(&self) -> DiGraph<String> {
+        let mut graph: DiGraph<String> = self
+            .gates
+            .iter()
+            .flat_map(|(name, gate)| {
+                gate.inputs
+                    .iter()
+                    .map(move |input| (input.clone(), name.clone()))
+            })
+            .collect();
+        // unused inputs and constants have no incoming edges
+        for wire in self.inputs.keys().chain(self.gates.keys()) {
+            graph.add_node(wire.clone());
+        }
+        graph
+    }
+
+    pub fn feedback_loops(&self) -> Vec<Vec<String>> {
+        scc::cycles(&self.wiring())
+            .into_iter()
+            .map(|mut wires| {
+                wires.sort();
+                wires
+            })
+            .sorted()
+            .collect()
+    }
+
+    // Evaluates every wire in one pass over the schedule, unless that was done already.
+    // Wires depending on undriven ones are left without a value.
+    fn evaluate(&mut self) -> Result<(), CircuitError> {
+        if self.schedule.is_none() {
+            let schedule = Schedule::new(&self.inputs, &self.gates)
+                .ok_or_else(|| CircuitError::FeedbackLoops(self.feedback_loops()));
+            self.schedule = Some(schedule);
+            self.values.clear();
+        }
+        let schedule = match self.schedule.as_ref().expect("Schedule was just made.") {
+            Ok(schedule) => schedule,
+            Err(err) => return Err(err.clone()),
+        };
+        if self.values.is_empty() {
+            self.values = vec![None; schedule.wires.len()];
+            for (wire, &value) in &self.inputs {
+                self.values[schedule.index[wire]] = Some(value);
+            }
+            let mut buffer = Vec::with_capacity(2);
+            for (output, op, inputs) in &schedule.steps {
+                buffer.clear();
+                buffer.extend(inputs.iter().map_while(|&input| self.values[input]));
+                if buffer.len() == inputs.len() {
+                    self.values[*output] = Some(op.apply(&buffer));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The first wire without a value that this one depends on
+    fn undriven_source(&self, wire: &str) -> String {
+        let mut wire = wire;
+        while let Some(gate) = self.gates.get(wire) {
+            if self.inputs.contains_key(wire) {
+                break;
+            }
+            wire = gate
+                .inputs
+                .iter()
+                .find(|input| self.settled_value(input).is_none())
+                .expect("A gate without value has an input without value.");
+        }
+        wire.into()
+    }
+
+    fn settled_value(&self, wire: &str) -> Option<bool> {
+        let schedule = self.schedule.as_ref()?.as_ref().ok()?;
+        *self.values.get(*schedule.index.get(wire)?)?
+    }
+
+    // The values of all wires that can be worked out, i.e. everything that is not downstream
+    // of a feedback loop or an undriven wire. Safe to call on any device.
+    pub fn settled_values(&self) -> HashMap<String, bool> {
+        let mut consumers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (output, gate) in &self.gates {
+            for input in gate.inputs.iter().unique() {
+                consumers.entry(input).or_default().push(output);
+            }
+        }
+        let mut values = self.inputs.clone();
+        let mut ready: Vec<&str> = self.inputs.keys().map(String::as_str).collect();
+        // constants need nothing to settle
+        for (output, gate) in &self.gates {
+            if gate.inputs.is_empty() && !self.inputs.contains_key(output) {
+                values.insert(output.clone(), gate.op.apply(&[]));
+                ready.push(output);
+            }
+        }
+        while let Some(wire) = ready.pop() {
+            for &output in consumers.get(wire).into_iter().flatten() {
+                if values.contains_key(output) || self.inputs.contains_key(output) {
+                    continue;
+                }
+                let inputs: Option<Vec<bool>> = self.gates[output]
+                    .inputs
+                    .iter()
+                    .map(|input| values.get(input).copied())
+                    .collect();
+                if let Some(inputs) = inputs {
+                    values.insert(output.into(), self.gates[output].op.apply(&inputs));
+                    ready.push(output);
+                }
+            }
+        }
+        values
+    }
+
+    // The value of any wire
+    pub fn value(&mut self, wire: &str) -> Result<bool, CircuitError> {
+        self.evaluate()?;
+        self.settled_value(wire)
+            .ok_or_else(|| CircuitError::Undriven(self.undriven_source(wire)))
+    }
+
+    // The number on the z wires
+    pub fn z(&mut self) -> Result<u64, CircuitError> {
+        (0..self.output_bits()).try_fold(0, |z, bit| {
+            Ok(z | (self.value(&wire('z', bit))? as u64) << bit)
+        })
+    }
+
+    // Exchanges the gates driving two wires
+    pub fn swap_outputs(&mut self, wire1: &str, wire2: &str) {
+        let gate1 = self
+            .gates
+            .remove(wire1)
+            .unwrap_or_else(|| panic!("No gate drives {wire1}."));
+        let gate2 = self
+            .gates
+            .insert(wire2.into(), gate1)
+            .unwrap_or_else(|| panic!("No gate drives {wire2}."));
+        self.gates.insert(wire1.into(), gate2);
+        self.schedule = None;
+        self.values.clear();
+    }
+}
+
+// At most `n_pairs` swaps of gate outputs, each wire in one swap at most, after which the
+// oracle scores the device 0. The oracle rates how broken a device is, e.g. by its number of
+// wrong output bits, and prunes the search: only swaps that lower the score are followed,
+// best first. It should return quickly and cope with feedback loops.
+pub fn find_swaps(
+    device: &Device,
+    n_pairs: usize,
+    mut oracle: impl FnMut(&mut Device) -> usize,
+) -> Option<Vec<(String, String)>> {
+    let mut device = device.clone();
+    let score = oracle(&mut device);
+    let mut swaps = Vec::new();
+    search_swaps(&mut device, score, n_pairs, &mut oracle, &mut swaps).then_some(swaps)
+}
+
+fn search_swaps(
+    device: &mut Device,
+    score: usize,
+    pairs_left: usize,
+    oracle: &mut impl FnMut(&mut Device) -> usize,
+    swaps: &mut Vec<(String, String)>,
+) -> bool {
+    if score == 0 {
+        return true;
+    }
+    if pairs_left == 0 {
+        return false;
+    }
+    let swapped: HashSet<&String> = swaps.iter().flat_map(|(a, b)| [a, b]).collect();
+    let outputs: Vec<String> = device
+        .gates
+        .keys()
+        .filter(|wire| !swapped.contains(wire))
+        .sorted()
+        .cloned()
+        .collect();
+    let mut improvements = Vec::new();
+    for (a, b) in outputs.iter().tuple_combinations() {
+        device.swap_outputs(a, b);
+        let new_score = oracle(device);
+        if new_score < score {
+            improvements.push((new_score, a, b));
+        }
+        device.swap_outputs(a, b);
+    }
+    improvements.sort();
+    for (new_score, a, b) in improvements {
+        device.swap_outputs(a, b);
+        swaps.push((a.clone(), b.clone()));
+        if search_swaps(device, new_score, pairs_left - 1, oracle, swaps) {
+            return true;
+        }
+        swaps.pop();
+        device.swap_outputs(a, b);
+    }
+    false
+}
+
+// A small deterministic generator, so probes and samples are the same on every run
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+}
+
+// Sets input i to bit i of `bits`, a word per 64 inputs, least significant bits first
+fn set_inputs(device: &mut Device, inputs: &[String], bits: &[u64]) {
+    for (idx, input) in inputs.iter().enumerate() {
+        device.set_input(input, bits[idx / 64] >> (idx % 64) & 1 == 1);
+    }
+}
+
+fn sorted_inputs(device: &Device) -> Vec<String> {
+    device.inputs.keys().sorted().cloned().collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruthTable {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    // one row of output values per combination of inputs; input i is bit i of the row number
+    pub rows: Vec<Vec<bool>>,
+}
+
+// As a table with a column per wire: "x00 y00 | z00", then rows like "1   0   | 0"
+impl Display for TruthTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let row = |wires: &[String], values: &[bool]| {
+            wires
+                .iter()
+                .zip(values)
+                .map(|(wire, &value)| format!("{:<width$}", value as u8, width = wire.len()))
+                .join(" ")
+        };
+        write!(f, "{} | {}", self.inputs.join(" "), self.outputs.join(" "))?;
+        for (bits, outputs) in self.rows.iter().enumerate() {
+            let inputs: Vec<bool> = (0..self.inputs.len()).map(|i| bits >> i & 1 == 1).collect();
+            let line = format!(
+                "{} | {}",
+                row(&self.inputs, &inputs),
+                row(&self.outputs, outputs)
+            );
+            write!(f, "\n{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+// The outputs for every combination of the device's inputs, as long as there are at most
+// `max_bits` of them
+pub fn truth_table(
+    device: &Device,
+    outputs: &[String],
+    max_bits: usize,
+) -> Result<TruthTable, CircuitError> {
+    let inputs = sorted_inputs(device);
+    if inputs.len() > max_bits.min(63) {
+        return Err(CircuitError::TooManyInputs(inputs.len()));
+    }
+    let mut device = device.clone();
+    let rows = (0..1 << inputs.len())
+        .map(|bits| {
+            set_inputs(&mut device, &inputs, &[bits]);
+            outputs.iter().map(|output| device.value(output)).collect()
+        })
+        .try_collect()?;
+    Ok(TruthTable {
+        inputs,
+        outputs: outputs.to_vec(),
+        rows,
+    })
+}
+
+// Up to this many inputs, equivalent tries every combination
+const EXHAUSTIVE_BITS: usize = 16;
+
+// Whether two devices with the same inputs agree on every z wire, and on which z wires there
+// are. Tries every combination of inputs if there are few, else `samples` random ones.
+// Wires without a value only agree with wires without a value.
+pub fn equivalent(a: &Device, b: &Device, samples: usize) -> bool {
+    let inputs = sorted_inputs(a);
+    if inputs != sorted_inputs(b) || a.output_bits() != b.output_bits() {
+        return false;
+    }
+    let outputs: Vec<String> = (0..a.output_bits()).map(|bit| wire('z', bit)).collect();
+    let (mut a, mut b) = (a.clone(), b.clone());
+    let mut agree = |bits: &[u64]| {
+        set_inputs(&mut a, &inputs, bits);
+        set_inputs(&mut b, &inputs, bits);
+        outputs
+            .iter()
+            .all(|output| a.value(output).ok() == b.value(output).ok())
+    };
+    if inputs.len() <= EXHAUSTIVE_BITS {
+        (0..1 << inputs.len()).all(|bits| agree(&[bits]))
+    } else {
+        let mut rng = Lcg(inputs.len() as u64);
+        (0..samples).all(|_| {
+            let bits: Vec<u64> = (0..inputs.len().div_ceil(64)).map(|_| rng.next()).collect();
+            agree(&bits)
+        })
+    }
+}
+
+// Graphviz DOT: wires point to the gates they feed, gate outputs are labelled with their operation
+pub fn to_dot(device: &Device) -> String {
+    digraph_to_dot(&device.wiring(), |wire| match device.gate(wire) {
+        Some(gate) => format!("{} ({})", wire, gate.op),
+        None => wire.clone(),
+    })
+}
+
+// A Mermaid flowchart, labelled like to_dot, with the inputs drawn round. Subgraphs can be
+// added to it, e.g. one per full adder.
+pub fn to_mermaid(device: &Device) -> Flowchart {
+    let wiring = device.wiring();
+    let chart = wiring
+        .nodes()
+        .sorted()
+        .fold(Flowchart::new("TB"), |chart, wire| {
+            match device.gate(wire) {
+                _ if device.inputs.contains_key(wire) => chart.node(wire, wire, Shape::Stadium),
+                Some(gate) => chart.node(wire, &format!("{} ({})", wire, gate.op), Shape::Box),
+                None => chart.node(wire, wire, Shape::Box),
+            }
+        });
+    chart.edges(wiring.nodes().sorted().flat_map(|wire| {
+        wiring
+            .successors(wire)
+            .sorted()
+            .map(move |consumer| (wire, consumer))
+    }))
+}
+
+// Any word but an operation, which would make gates ambiguous
+fn wire_name(name: &str) -> Result<String, String> {
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("invalid wire name '{}'", name));
+    }
+    if name.parse::<GateType>().is_ok() {
+        return Err(format!("'{}' is a gate type, not a wire name", name));
+    }
+    Ok(name.into())
+}
+
+fn parse_input(line: &str) -> Result<(String, bool), String> {
+    let (name, value) = line
+        .split_once(": ")
+        .ok_or_else(|| String::from("expected 'wire: 0' or 'wire: 1'"))?;
+    let name = wire_name(name)?;
+    match value.trim() {
+        "0" => Ok((name, false)),
+        "1" => Ok((name, true)),
+        other => Err(format!("expected 0 or 1, found '{}'", other)),
+    }
+}
+
+// "a OP b -> c", "NOT a -> c" or "TRUE -> c"
+fn parse_gate(line: &str) -> Result<(String, Gate), String> {
+    let (gate, output) = line
+        .split_once(" -> ")
+        .ok_or_else(|| String::from("expected 'a OP b -> c'"))?;
+    let output = wire_name(output.trim())?;
+    let words: Vec<&str> = gate.split_whitespace().collect();
+    let is_binary = |op: &str| op.parse::<GateType>().is_ok_and(|op| op.arity() == 2);
+    let (op, inputs): (GateType, Vec<&str>) = match words[..] {
+        // "TRUE AND a" uses TRUE as a wire rather than as a gate with two inputs
+        [a, op, b] if is_binary(op) => (op.parse()?, vec![a, b]),
+        // prefix forms start with the operation
+        [op, ref inputs @ ..] if op.parse::<GateType>().is_ok() => (op.parse()?, inputs.to_vec()),
+        [a, op, b] => (op.parse()?, vec![a, b]),
+        [op, ..] => return Err(format!("unknown gate type '{}'", op)),
+        [] => return Err(String::from("expected 'a OP b -> c'")),
+    };
+    if inputs.len() != op.arity() {
+        return Err(format!("{} takes {} inputs", op, op.arity()));
+    }
+    let gate = Gate {
+        op,
+        inputs: inputs
+            .into_iter()
+            .map(wire_name)
+            .collect::<Result<_, _>>()?,
+    };
+    Ok((output, gate))
+}
+
+// The puzzle format: input values, a blank line, then one gate per line
+impl FromStr for Device {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut inputs = HashMap::new();
+        let mut gates = HashMap::new();
+        let mut in_gates = false;
+        for (index, line) in text.lines().enumerate() {
+            let error = |message| ParseError {
+                line: index + 1,
+                message,
+            };
+            if line.trim().is_empty() {
+                in_gates |= !inputs.is_empty();
+                continue;
+            }
+            // devices without inputs start with their gates
+            in_gates |= line.contains(" -> ");
+            if in_gates {
+                let (output, gate) = parse_gate(line.trim()).map_err(error)?;
+                if gates.insert(output.clone(), gate).is_some() {
+                    return Err(error(format!("{} is driven by two gates", output)));
+                }
+            } else {
+                let (name, value) = parse_input(line.trim()).map_err(error)?;
+                inputs.insert(name, value);
+            }
+        }
+        Ok(Device::new(inputs, gates))
+    }
+}
+
+// The puzzle format again, sorted by wire, so that parsing it gives back the same device
+impl Display for Device {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inputs = self
+            .inputs
+            .iter()
+            .sorted()
+            .map(|(wire, &value)| format!("{}: {}", wire, value as u8))
+            .join("\n");
+        let gates = self
+            .gates
+            .iter()
+            .sorted_by_key(|(output, _)| *output)
+            .map(|(output, gate)| format!("{} -> {}", gate, output))
+            .join("\n");
+        let sections = [inputs, gates];
+        write!(
+            f,
+            "{}",
+            sections.iter().filter(|s| !s.is_empty()).join("\n\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02
+";
+
+    #[test]
+    fn test_evaluate() {
+        let mut device: Device = EXAMPLE.parse().unwrap();
+        assert_eq!(device.gate_count(), 3);
+        assert_eq!(device.input_bits(), 3);
+        assert_eq!(device.output_bits(), 3);
+        assert_eq!((device.x(), device.y()), (7, 2));
+        assert_eq!(device.z(), Ok(4));
+        assert_eq!(
+            device.gate("z01"),
+            Some(&Gate::new("x01", GateType::Xor, "y01"))
+        );
+        assert_eq!(device.gate("x01"), None);
+
+        device.set_x_y(1, 1);
+        assert_eq!(device.z(), Ok(0b001));
+        device.set_input("y02", true);
+        assert_eq!(device.value("z02"), Ok(true));
+
+        device.swap_outputs("z00", "z01");
+        assert_eq!(device.z(), Ok(0b110));
+        assert_eq!(device.settled_values().len(), 9);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut device: Device = "x00: 1\n\nx00 AND y00 -> z00".parse().unwrap();
+        assert_eq!(device.z(), Err(CircuitError::Undriven(String::from("y00"))));
+
+        let mut device: Device = "x00: 1\n\nx00 AND b -> a\na OR x00 -> b\na XOR b -> z00"
+            .parse()
+            .unwrap();
+        let loops = vec![vec![String::from("a"), String::from("b")]];
+        assert_eq!(device.z(), Err(CircuitError::FeedbackLoops(loops)));
+        // only the input settles
+        assert_eq!(
+            device.settled_values(),
+            HashMap::from([(String::from("x00"), true)])
+        );
+
+        let error = |text: &str| text.parse::<Device>().unwrap_err().to_string();
+        assert_eq!(error("x00: 2"), "line 1: expected 0 or 1, found '2'");
+        assert_eq!(
+            error("x00: 1\n\nx00 IMPLIES y00 -> z00"),
+            "line 3: unknown gate type 'IMPLIES'"
+        );
+        assert_eq!(
+            error("x00: 1\n\nNOT x00 y00 -> z00"),
+            "line 3: NOT takes 1 inputs"
+        );
+        assert_eq!(
+            error("x00: 1\n\nx00 AND y00 z00"),
+            "line 3: expected 'a OP b -> c'"
+        );
+        assert_eq!(
+            error("x00: 1\n\nx00 AND y00 -> z00\nx00 OR y00 -> z00"),
+            "line 4: z00 is driven by two gates"
+        );
+
+        // operations are not wire names
+        assert_eq!(
+            error("x00: 1\n\nNOT TRUE -> z00"),
+            "line 3: 'TRUE' is a gate type, not a wire name"
+        );
+        assert_eq!(
+            error("x00: 1\n\nTRUE AND x00 -> z00"),
+            "line 3: 'TRUE' is a gate type, not a wire name"
+        );
+        assert_eq!(
+            error("x00: 1\n\nx00 OR NOT -> z00"),
+            "line 3: 'NOT' is a gate type, not a wire name"
+        );
+        assert_eq!(
+            error("x00: 1\n\nx00 XOR x00 -> AND"),
+            "line 3: 'AND' is a gate type, not a wire name"
+        );
+        assert_eq!(
+            error("FALSE: 1"),
+            "line 1: 'FALSE' is a gate type, not a wire name"
+        );
+    }
+
+    #[test]
+    fn test_find_swaps() {
+        // bitwise AND with the outputs of bits 0 and 2 crossed
+        let text = "x00: 0\nx01: 0\nx02: 0\ny00: 0\ny01: 0\ny02: 0\n\n\
+            x00 AND y00 -> z02\nx01 AND y01 -> z01\nx02 AND y02 -> z00";
+        let device: Device = text.parse().unwrap();
+        // the number of output bits that are wrong for some input
+        let wrong_bits = |device: &mut Device| {
+            let mut wrong = 0;
+            for (x, y) in (0..8).cartesian_product(0..8) {
+                device.set_x_y(x, y);
+                wrong |= device.z().map_or(!0, |z| z ^ (x & y));
+            }
+            wrong.count_ones() as usize
+        };
+        assert_eq!(
+            find_swaps(&device, 2, wrong_bits),
+            Some(vec![(String::from("z00"), String::from("z02"))])
+        );
+        assert_eq!(find_swaps(&device, 0, wrong_bits), None);
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let device: Device = "x00: 0\ny00: 0\n\nx00 XOR y00 -> z00\nx00 AND y00 -> carry"
+            .parse()
+            .unwrap();
+        let outputs = [wire('z', 0), String::from("carry")];
+        let table = truth_table(&device, &outputs, 2).unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                vec![false, false],
+                vec![true, false],
+                vec![true, false],
+                vec![false, true]
+            ]
+        );
+        assert_eq!(
+            table.to_string(),
+            "x00 y00 | z00 carry\n0   0   | 0   0\n1   0   | 1   0\n0   1   | 1   0\n1   1   | 0   1"
+        );
+        assert_eq!(
+            truth_table(&device, &outputs, 1),
+            Err(CircuitError::TooManyInputs(2))
+        );
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let xor: Device = "x00: 0\ny00: 0\n\nx00 XOR y00 -> z00".parse().unwrap();
+        // (x OR y) AND (x NAND y)
+        let text = "x00: 0\ny00: 0\n\nx00 OR y00 -> a\nx00 NAND y00 -> b\na AND b -> z00";
+        let mut device: Device = text.parse().unwrap();
+        assert!(equivalent(&xor, &device, 0));
+        device.swap_outputs("a", "b");
+        assert!(equivalent(&xor, &device, 0));
+        device.swap_outputs("a", "z00");
+        assert!(!equivalent(&xor, &device, 0));
+    }
+
+    #[test]
+    fn test_netlist() {
+        let device: Device = EXAMPLE.parse().unwrap();
+        assert_eq!(device.to_string(), EXAMPLE.trim_end());
+
+        let text = "NOT b -> a\nTRUE -> b";
+        let mut device: Device = text.parse().unwrap();
+        assert_eq!(device.to_string(), text);
+        assert_eq!(device.value("a"), Ok(false));
+    }
+
+    #[test]
+    fn test_diagrams() {
+        let device: Device = "x00: 1\ny00: 0\n\nx00 AND y00 -> end\nNOT end -> z00"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            to_mermaid(&device)
+                .subgraph("bit0", [wire('x', 0)])
+                .to_string(),
+            [
+                "flowchart TB",
+                "    END[\"end (AND)\"]",
+                "    x00([\"x00\"])",
+                "    y00([\"y00\"])",
+                "    z00[\"z00 (NOT)\"]",
+                "    subgraph bit0",
+                "        x00",
+                "    end",
+                "    END --> z00",
+                "    x00 --> END",
+                "    y00 --> END",
+            ]
+            .join("\n")
+        );
+        let dot = to_dot(&device);
+        assert!(dot.contains("[label=\"z00 (NOT)\"]"));
+        assert_eq!(dot.matches(" -> ").count(), 3);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // far deeper than a recursive evaluation could go
+        let length = 200_000;
+        let mut gates: HashMap<String, Gate> = (1..length)
+            .map(|idx| (format!("w{}", idx), Gate::not(&format!("w{}", idx - 1))))
+            .collect();
+        gates.insert(wire('z', 0), Gate::not(&format!("w{}", length - 1)));
+        let mut device = Device::new(HashMap::from([(String::from("w0"), true)]), gates);
+        // an even number of inversions
+        assert_eq!(device.z(), Ok(1));
+        device.set_input("w0", false);
+        assert_eq!(device.z(), Ok(0));
+        // inputs override gates
+        device.set_input("w1", true);
+        assert_eq!(device.z(), Ok(0));
+        device.set_input("w1", false);
+        assert_eq!(device.z(), Ok(1));
+    }
+
+    #[test]
+    fn test_extended_gates() {
+        let text = "\
+x00: 1
+y00: 0
+
+x00 NAND y00 -> z00
+x00 NOR y00 -> z01
+x00 XNOR y00 -> z02
+NOT y00 -> z03
+TRUE -> one
+one XNOR x00 -> z04
+FALSE -> z05
+";
+        let mut device: Device = text.parse().unwrap();
+        assert_eq!(device.z(), Ok(0b011001));
+        assert_eq!(device.gate("z03"), Some(&Gate::not("y00")));
+        assert_eq!(device.gate("one"), Some(&Gate::constant(true)));
+        assert_eq!(Gate::not("y00").to_string(), "NOT y00");
+        assert_eq!(Gate::constant(false).to_string(), "FALSE");
+        assert_eq!(device.settled_values().len(), 9);
+
+        device.set_x_y(0, 0);
+        assert_eq!(device.z(), Ok(0b001111));
+        assert!(GateType::ALL
+            .iter()
+            .all(|op| op.to_string().parse::<GateType>() == Ok(*op)));
+    }
+}
@@ -0,0 +1,470 @@
+use crate::utils::circuit::{wire, Device, Gate, GateType, Lcg};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+// The wires of one full adder in a ripple-carry adder, as far as they could be found
+#[derive(Clone, Debug)]
+pub struct Adder {
+    pub x_in: String,
+    pub y_in: String,
+    pub bit_xor: String,
+    pub bit_and: String,
+    pub pre_c_out: String,
+    pub c_out: String,
+    pub s_out: String,
+}
+
+// Stands in for a wire whose gate does not exist in the device
+pub const MISSING_NODE: &str = " _";
+
+fn gate_name(gate: &Gate, inverted_gate_map: &HashMap<Gate, String>) -> String {
+    inverted_gate_map
+        .get(gate)
+        .cloned()
+        .unwrap_or(MISSING_NODE.into())
+}
+
+// Finds each bit's full adder by looking up the gates it should consist of, starting from the
+// inputs. Where a gate is missing, because outputs were swapped, everything built on it is too.
+pub fn decompose(device: &Device) -> Vec<Adder> {
+    let input_bits = device.input_bits();
+    let output_bits = input_bits + 1;
+    let mut inverted_gate_map: HashMap<Gate, String> = HashMap::new();
+    for (name, gate) in device.gates() {
+        if let Some(old_name) = inverted_gate_map.insert(gate.clone(), name.clone()) {
+            panic!("Gate {name} was inserted as {old_name} before.");
+        }
+        if gate.mirror() != *gate {
+            inverted_gate_map.insert(gate.mirror(), name.clone());
+        }
+    }
+
+    // Reconstruct adding by hand, check where device deviates
+    // Half-adders
+    let mut bit_xor_gates: Vec<String> = vec![];
+    let mut bit_and_gates: Vec<String> = vec![];
+    for bit in 0..input_bits {
+        bit_xor_gates.push(gate_name(
+            &Gate::new(&wire('x', bit), GateType::Xor, &wire('y', bit)),
+            &inverted_gate_map,
+        ));
+        bit_and_gates.push(gate_name(
+            &Gate::new(&wire('x', bit), GateType::And, &wire('y', bit)),
+            &inverted_gate_map,
+        ));
+    }
+
+    // Full adders
+    // C_{i+1} = (x_i & y_i) | (C_i & (x_i ^ y_i))
+    // pre_carry_{i+1} := C_i & (x_i ^ y_i)
+    // carry_{i+1} := (x_i & y_i) | pre_carry_{i+1}
+    let mut pre_carry_gates: Vec<String> = vec![MISSING_NODE.into(), MISSING_NODE.into()];
+    let mut carry_gates: Vec<String> = vec![MISSING_NODE.into(), bit_and_gates[0].clone()];
+    for bit in 2..output_bits {
+        pre_carry_gates.push(gate_name(
+            &Gate::new(
+                &carry_gates[bit - 1],
+                GateType::And,
+                &bit_xor_gates[bit - 1],
+            ),
+            &inverted_gate_map,
+        ));
+        carry_gates.push(gate_name(
+            &Gate::new(&bit_and_gates[bit - 1], GateType::Or, &pre_carry_gates[bit]),
+            &inverted_gate_map,
+        ));
+    }
+
+    // outputs:
+    let mut out_gates: Vec<String> = vec![bit_xor_gates[0].clone()];
+    for bit in 1..input_bits {
+        out_gates.push(gate_name(
+            &Gate::new(&bit_xor_gates[bit], GateType::Xor, &carry_gates[bit]),
+            &inverted_gate_map,
+        ));
+    }
+    out_gates.push(carry_gates[output_bits - 1].clone());
+
+    let mut adders: Vec<Adder> = vec![];
+    for bit in 0..input_bits {
+        adders.push(Adder {
+            x_in: wire('x', bit),
+            y_in: wire('y', bit),
+            bit_xor: bit_xor_gates[bit].clone(),
+            bit_and: bit_and_gates[bit].clone(),
+            pre_c_out: pre_carry_gates[bit + 1].clone(),
+            c_out: carry_gates[bit + 1].clone(),
+            s_out: out_gates[bit].clone(),
+        })
+    }
+
+    adders
+}
+
+// A correct ripple-carry adder for numbers of the given width, with all inputs 0. Bit 0 is a
+// half adder; the carry out of the last bit is the top z wire.
+pub fn ripple_carry_adder(bits: usize) -> Device {
+    let mut inputs = HashMap::new();
+    let mut gates = HashMap::new();
+    for bit in 0..bits {
+        inputs.insert(wire('x', bit), false);
+        inputs.insert(wire('y', bit), false);
+    }
+    let mut carry = String::new();
+    for bit in 0..bits {
+        let (x, y) = (wire('x', bit), wire('y', bit));
+        let next_carry = if bit + 1 == bits {
+            wire('z', bits)
+        } else {
+            wire('c', bit)
+        };
+        if bit == 0 {
+            gates.insert(wire('z', 0), Gate::new(&x, GateType::Xor, &y));
+            gates.insert(next_carry.clone(), Gate::new(&x, GateType::And, &y));
+        } else {
+            let (sum, both, pre) = (wire('s', bit), wire('a', bit), wire('p', bit));
+            gates.insert(sum.clone(), Gate::new(&x, GateType::Xor, &y));
+            gates.insert(both.clone(), Gate::new(&x, GateType::And, &y));
+            gates.insert(wire('z', bit), Gate::new(&sum, GateType::Xor, &carry));
+            gates.insert(pre.clone(), Gate::new(&sum, GateType::And, &carry));
+            gates.insert(next_carry.clone(), Gate::new(&both, GateType::Or, &pre));
+        }
+        carry = next_carry;
+    }
+    Device::new(inputs, gates)
+}
+
+fn mask(bits: usize) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+// Input pairs that exercise every bit: each bit set alone, in x and y, with and without a
+// carry coming in from below, and a carry rippling up from bit 0. Then random additions.
+pub fn probes(bits: usize, random: usize) -> Vec<(u64, u64)> {
+    let mut probes = Vec::new();
+    for bit in 0..bits {
+        let one = 1 << bit;
+        probes.extend([(one, 0), (0, one), (one, one)]);
+        if bit > 0 {
+            let below = one >> 1;
+            probes.extend([
+                (one | below, below),
+                (below, one | below),
+                (one | below, one | below),
+                (mask(bit), 1),
+            ]);
+        }
+    }
+    let mut rng = Lcg(bits as u64);
+    probes.extend((0..random).map(|_| (rng.next() & mask(bits), rng.next() & mask(bits))));
+    probes
+}
+
+// An output bit that does not hold its share of x + y, with the first probe that showed it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultyBit {
+    pub bit: usize,
+    pub x: u64,
+    pub y: u64,
+    pub expected: bool,
+    // None if the bit has no value, being downstream of a feedback loop or undriven wire
+    pub found: Option<bool>,
+}
+
+impl Display for FaultyBit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let found = self
+            .found
+            .map_or(String::from("no value"), |found| (found as u8).to_string());
+        write!(
+            f,
+            "{}: {} + {} should give {}, found {}",
+            wire('z', self.bit),
+            self.x,
+            self.y,
+            self.expected as u8,
+            found
+        )
+    }
+}
+
+// The faulty output bits the probes find, lowest first. Stops probing as soon as a bit at or
+// below `stop_at` is found to be faulty.
+fn faults(device: &mut Device, probes: &[(u64, u64)], stop_at: Option<usize>) -> Vec<FaultyBit> {
+    let output_bits = device.input_bits() + 1;
+    let mut faults: Vec<FaultyBit> = Vec::new();
+    for &(x, y) in probes {
+        device.set_x_y(x, y);
+        let values = device.settled_values();
+        // with 64-bit inputs the carry out is bit 64 of the sum
+        let sum = x as u128 + y as u128;
+        for bit in 0..output_bits {
+            let expected = sum >> bit & 1 == 1;
+            let found = values.get(&wire('z', bit)).copied();
+            if found != Some(expected) && faults.iter().all(|fault| fault.bit != bit) {
+                faults.push(FaultyBit {
+                    bit,
+                    x,
+                    y,
+                    expected,
+                    found,
+                });
+            }
+        }
+        if stop_at.is_some_and(|stop_at| faults.iter().any(|fault| fault.bit <= stop_at)) {
+            break;
+        }
+    }
+    faults.sort_by_key(|fault| fault.bit);
+    faults
+}
+
+// Checks every output bit of a device meant to add x and y, using the targeted probes only,
+// so the result is the same on every run. An empty result means no probe found a fault.
+pub fn verify_adder(device: &Device) -> Vec<FaultyBit> {
+    let mut device = device.clone();
+    let probes = probes(device.input_bits(), 0);
+    faults(&mut device, &probes, None)
+}
+
+fn lowest_wrong_bit(device: &mut Device, probes: &[(u64, u64)], stop_at: usize) -> Option<usize> {
+    faults(device, probes, Some(stop_at))
+        .first()
+        .map(|fault| fault.bit)
+}
+
+// The gate outputs z at this bit and the next depend on, but z at the bit below does not:
+// where a fault that first shows at this bit most likely is
+fn suspects(device: &Device, bit: usize) -> Vec<String> {
+    let cone = |z: usize| -> HashSet<String> {
+        let mut cone = HashSet::new();
+        let mut stack = vec![wire('z', z)];
+        while let Some(wire) = stack.pop() {
+            if let Some(gate) = device.gate(&wire) {
+                if cone.insert(wire) {
+                    stack.extend(gate.inputs.iter().cloned());
+                }
+            }
+        }
+        cone
+    };
+    let below = if bit > 0 {
+        cone(bit - 1)
+    } else {
+        HashSet::new()
+    };
+    let mut suspects: Vec<String> = cone(bit)
+        .union(&cone(bit + 1))
+        .filter(|wire| !below.contains(*wire))
+        .cloned()
+        .collect();
+    suspects.sort();
+    suspects
+}
+
+// Swaps to try at the lowest wrong bit: first those the adder decomposition points at, where
+// a sum ended up on a gate other than its z wire, then pairs of suspects, then each suspect
+// with any other gate output
+fn candidates(device: &Device, bit: usize) -> Vec<(String, String)> {
+    let mut candidates = Vec::new();
+    let adders = decompose(device);
+    for (adder_bit, adder) in adders
+        .iter()
+        .enumerate()
+        .skip(bit.saturating_sub(1))
+        .take(3)
+    {
+        let z = wire('z', adder_bit);
+        if adder.s_out != MISSING_NODE && adder.s_out != z && device.gate(&z).is_some() {
+            candidates.push((adder.s_out.clone(), z));
+        }
+    }
+    let suspects = suspects(device, bit);
+    for (i, a) in suspects.iter().enumerate() {
+        for b in &suspects[i + 1..] {
+            candidates.push((a.clone(), b.clone()));
+        }
+    }
+    let mut others: Vec<&String> = device
+        .gates()
+        .map(|(wire, _)| wire)
+        .filter(|wire| !suspects.contains(wire))
+        .collect();
+    others.sort();
+    for a in &suspects {
+        for b in &others {
+            candidates.push((a.clone(), (*b).clone()));
+        }
+    }
+    candidates
+}
+
+fn repair(
+    device: &mut Device,
+    probes: &[(u64, u64)],
+    pairs_left: usize,
+    swaps: &mut Vec<(String, String)>,
+) -> bool {
+    let Some(bit) = lowest_wrong_bit(device, probes, 0) else {
+        return true;
+    };
+    if pairs_left == 0 {
+        return false;
+    }
+    for (a, b) in candidates(device, bit) {
+        device.swap_outputs(&a, &b);
+        if lowest_wrong_bit(device, probes, bit).is_none_or(|wrong| wrong > bit) {
+            swaps.push((a.clone(), b.clone()));
+            if repair(device, probes, pairs_left - 1, swaps) {
+                return true;
+            }
+            swaps.pop();
+        }
+        device.swap_outputs(&a, &b);
+    }
+    false
+}
+
+// Up to `max_pairs` swaps of gate outputs that turn the device into a working adder of x and y.
+// Works upwards from the lowest bit that adds wrongly, keeping a swap only if it moves the
+// lowest wrong bit up, and backtracks if the remaining pairs cannot finish the job.
+pub fn find_swapped_pairs(device: &Device, max_pairs: usize) -> Option<Vec<(String, String)>> {
+    let mut device = device.clone();
+    let probes = probes(device.input_bits(), 32);
+    let mut swaps = Vec::new();
+    repair(&mut device, &probes, max_pairs, &mut swaps).then_some(swaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::circuit::{equivalent, find_swaps};
+
+    fn sorted(pairs: Vec<(String, String)>) -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = pairs
+            .into_iter()
+            .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn test_ripple_carry_adder() {
+        let mut device = ripple_carry_adder(8);
+        assert_eq!(device.input_bits(), 8);
+        assert_eq!(device.output_bits(), 9);
+        for (x, y) in probes(8, 20) {
+            device.set_x_y(x, y);
+            assert_eq!(device.z(), Ok(x + y));
+        }
+        assert!(decompose(&device)
+            .iter()
+            .enumerate()
+            .all(|(bit, adder)| adder.s_out == wire('z', bit)));
+    }
+
+    #[test]
+    fn test_verify_adder() {
+        let mut device = ripple_carry_adder(10);
+        assert_eq!(verify_adder(&device), vec![]);
+
+        // the carry out of bit 6 is computed as a sum: bits 7 and up misbehave
+        device.swap_outputs("c06", "s06");
+        let faults = verify_adder(&device);
+        assert_eq!(faults[0].bit, 6);
+        assert!(faults.iter().all(|fault| fault.bit >= 6));
+        assert_eq!(faults[0].to_string().split(':').next(), Some("z06"));
+
+        // s02 now feeds itself, so z02 misses the carry and the carry into bit 3 has no value
+        let mut device = ripple_carry_adder(10);
+        device.swap_outputs("z02", "s02");
+        let faults = verify_adder(&device);
+        assert_eq!(faults[0].bit, 2);
+        assert!(faults[0].found.is_some());
+        assert_eq!((faults[1].bit, faults[1].found), (3, None));
+        assert!(faults[1].to_string().ends_with("found no value"));
+
+        // every bit the structural decomposition flags is found faulty too
+        let mut device = ripple_carry_adder(10);
+        device.swap_outputs("z04", "c04");
+        device.swap_outputs("z08", "p08");
+        let faulty: HashSet<usize> = verify_adder(&device)
+            .iter()
+            .map(|fault| fault.bit)
+            .collect();
+        let flagged: Vec<usize> = decompose(&device)
+            .iter()
+            .enumerate()
+            .filter(|(bit, adder)| adder.s_out != wire('z', *bit))
+            .map(|(bit, _)| bit)
+            .collect();
+        assert_eq!(flagged.first(), Some(&4));
+        assert!(flagged.iter().all(|bit| faulty.contains(bit)));
+    }
+
+    #[test]
+    fn test_verify_64_bit_adder() {
+        // the carry out of the top bit is z64, beyond what a u64 sum holds
+        let mut device = ripple_carry_adder(64);
+        assert_eq!(device.output_bits(), 65);
+        assert_eq!(verify_adder(&device), vec![]);
+
+        device.swap_outputs("c62", "s62");
+        let faults = verify_adder(&device);
+        assert_eq!(faults[0].bit, 62);
+        assert!(faults.iter().any(|fault| fault.bit == 64));
+    }
+
+    #[test]
+    fn test_equivalent() {
+        // too many inputs to try them all
+        let mut device = ripple_carry_adder(12);
+        assert!(equivalent(&device, &ripple_carry_adder(12), 100));
+        assert!(!equivalent(&device, &ripple_carry_adder(11), 100));
+        device.swap_outputs("z05", "c05");
+        assert!(!equivalent(&device, &ripple_carry_adder(12), 100));
+    }
+
+    #[test]
+    fn test_find_swaps() {
+        let mut device = ripple_carry_adder(4);
+        device.swap_outputs("c01", "z01");
+        device.swap_outputs("s03", "a03");
+        let swaps = find_swaps(&device, 2, |device| verify_adder(device).len()).unwrap();
+        assert_eq!(swaps.len(), 2);
+        for (a, b) in &swaps {
+            device.swap_outputs(a, b);
+        }
+        assert_eq!(verify_adder(&device), vec![]);
+    }
+
+    #[test]
+    fn test_find_swapped_pairs() {
+        let mut device = ripple_carry_adder(12);
+        assert_eq!(find_swapped_pairs(&device, 4), Some(vec![]));
+
+        let swaps = [
+            ("z03", "s03"),
+            ("s05", "a05"),
+            ("z08", "c08"),
+            ("p10", "z10"),
+        ];
+        for (a, b) in swaps {
+            device.swap_outputs(a, b);
+        }
+        let found = find_swapped_pairs(&device, 4).unwrap();
+        let expected = swaps.map(|(a, b)| (String::from(a), String::from(b)));
+        assert_eq!(sorted(found), sorted(expected.to_vec()));
+
+        // three swaps remain, but none are allowed
+        device.swap_outputs("z03", "s03");
+        assert_eq!(find_swapped_pairs(&device, 0), None);
+    }
+}
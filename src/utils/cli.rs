@@ -0,0 +1,113 @@
+use std::{collections::HashMap, env, fmt::Debug, str::FromStr};
+
+// A named puzzle parameter that can be overridden on the command line,
+// e.g. `cargo run --bin day14 -- --width 11 --height 7`
+#[derive(Debug, Clone, Copy)]
+pub struct Extension {
+    pub name: &'static str,
+    pub default: &'static str,
+}
+
+#[derive(Debug)]
+pub struct Extensions(HashMap<&'static str, String>);
+
+impl Extensions {
+    pub fn new(declared: &[Extension]) -> Self {
+        Extensions(
+            declared
+                .iter()
+                .map(|ext| (ext.name, String::from(ext.default)))
+                .collect(),
+        )
+    }
+
+    pub fn from_args(declared: &[Extension]) -> Self {
+        Self::with_args(declared, env::args().skip(1))
+    }
+
+    // Accepts both `--name value` and `--name=value`; unknown arguments are left alone
+    pub fn with_args(declared: &[Extension], args: impl IntoIterator<Item = String>) -> Self {
+        let mut extensions = Self::new(declared);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                continue;
+            };
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(String::from(value))),
+                None => (option, None),
+            };
+            if let Some(ext) = declared.iter().find(|ext| ext.name == name) {
+                let value = value
+                    .or_else(|| args.next())
+                    .unwrap_or_else(|| panic!("Missing value for --{name}."));
+                extensions.0.insert(ext.name, value);
+            }
+        }
+        extensions
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> T
+    where
+        T::Err: Debug,
+    {
+        let value = self
+            .0
+            .get(name)
+            .unwrap_or_else(|| panic!("Extension {name} was not declared."));
+        value
+            .parse()
+            .unwrap_or_else(|err| panic!("Could not parse --{name} {value}: {err:?}"))
+    }
+}
+
+// Whether a bare switch like `--dot` was passed, e.g. `cargo run --bin day23 -- --dot`
+pub fn flag(name: &str) -> bool {
+    has_flag(env::args().skip(1), name)
+}
+
+pub fn has_flag(args: impl IntoIterator<Item = String>, name: &str) -> bool {
+    args.into_iter()
+        .any(|arg| arg.strip_prefix("--") == Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTENSIONS: [Extension; 2] = [
+        Extension {
+            name: "width",
+            default: "101",
+        },
+        Extension {
+            name: "height",
+            default: "103",
+        },
+    ];
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let extensions = Extensions::new(&EXTENSIONS);
+        assert_eq!(extensions.get::<usize>("width"), 101);
+        assert_eq!(extensions.get::<usize>("height"), 103);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let extensions = Extensions::with_args(&EXTENSIONS, args("--width 11 --debug --height=7"));
+        assert_eq!(extensions.get::<usize>("width"), 11);
+        assert_eq!(extensions.get::<usize>("height"), 7);
+    }
+
+    #[test]
+    fn test_flags() {
+        assert!(has_flag(args("--width 11 --dot"), "dot"));
+        assert!(!has_flag(args("--width 11 --dots"), "dot"));
+        assert!(!has_flag(args("dot"), "dot"));
+    }
+}
@@ -0,0 +1,371 @@
+use itertools::Itertools;
+use std::{
+    cmp::{self, Reverse},
+    collections::{BTreeMap, BinaryHeap},
+};
+
+// The disk from day 9, read from its dense disk map: files and free space alternate, each a
+// run of blocks. Part 1 compacts it block by block, part 2 moves whole files.
+
+#[derive(Clone, Copy, Debug)]
+pub enum DataBlock {
+    File { id: usize, size: usize },
+    Free { size: usize },
+}
+
+impl DataBlock {
+    pub fn size(&self) -> usize {
+        match *self {
+            DataBlock::File { size, .. } | DataBlock::Free { size } => size,
+        }
+    }
+}
+
+pub fn partial_checksum(id: usize, start_position: usize, size: usize) -> u128 {
+    (id * (start_position..start_position + size).sum::<usize>()) as u128
+}
+
+// The blocks of a disk in order, each file or free space a run of blocks
+#[derive(Clone, Debug)]
+pub struct Disk {
+    blocks: Vec<DataBlock>,
+}
+
+impl Disk {
+    // e.g. "12345": a file of size 1, a gap of 2, a file of 3 and so on
+    pub fn from_map(disk_map: &str) -> Self {
+        let blocks = disk_map
+            .chars()
+            .filter_map(|character| character.to_digit(10))
+            .enumerate()
+            .map(|(idx, size)| -> DataBlock {
+                let size = size as usize;
+                if idx % 2 == 0 {
+                    DataBlock::File { id: idx / 2, size }
+                } else {
+                    DataBlock::Free { size }
+                }
+            })
+            .collect_vec();
+        Disk { blocks }
+    }
+
+    pub fn blocks(&self) -> &[DataBlock] {
+        &self.blocks
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(DataBlock::size).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn total_file_size(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| match *block {
+                DataBlock::File { size, .. } => size,
+                DataBlock::Free { .. } => 0,
+            })
+            .sum()
+    }
+
+    pub fn checksum(&self) -> u128 {
+        let mut checksum: u128 = 0;
+        let mut seeker: usize = 0;
+
+        for block in &self.blocks {
+            match block {
+                DataBlock::Free { size } => {
+                    seeker += *size;
+                }
+                DataBlock::File { id, size } => {
+                    checksum += partial_checksum(*id, seeker, *size);
+                    seeker += *size;
+                }
+            }
+        }
+
+        checksum
+    }
+
+    // As in the puzzle, e.g. "0..111....22222"; only the last digit of larger ids is shown
+    pub fn render(&self) -> String {
+        self.blocks
+            .iter()
+            .map(|block| match *block {
+                DataBlock::File { id, size } => {
+                    char::from(b'0' + (id % 10) as u8).to_string().repeat(size)
+                }
+                DataBlock::Free { size } => ".".repeat(size),
+            })
+            .collect()
+    }
+
+    // Part 1: blocks move one at a time from the end into the leftmost free block
+    pub fn compacted(&self) -> Disk {
+        // right uber_block only ever has one component in it
+        let mut left_block_idx = 0;
+        let mut right_block_idx = &self.blocks.len() - 1;
+        let mut compressed_blocks: Vec<DataBlock> = Vec::new();
+
+        let mut free_space_in_left_block: Option<usize> = None;
+        let mut files_remaining_in_right_block: Option<usize> = None;
+        while left_block_idx < right_block_idx {
+            let (left_block, right_block) =
+                (&self.blocks[left_block_idx], &self.blocks[right_block_idx]);
+
+            match (left_block, right_block) {
+                (_, DataBlock::Free { size: _ }) => right_block_idx -= 1,
+                (DataBlock::File { id, size }, _) => {
+                    compressed_blocks.push(DataBlock::File {
+                        id: *id,
+                        size: *size,
+                    });
+                    left_block_idx += 1;
+                }
+                (
+                    DataBlock::Free { size: free_size },
+                    DataBlock::File {
+                        id: file_id,
+                        size: file_size,
+                    },
+                ) => {
+                    let free_size = match free_space_in_left_block {
+                        Some(free_size_left) => free_size_left,
+                        None => *free_size,
+                    };
+                    let file_size = match files_remaining_in_right_block {
+                        Some(file_size_right) => file_size_right,
+                        None => *file_size,
+                    };
+
+                    let movable_files = cmp::min(free_size, file_size);
+                    let (new_free_size, new_file_size) =
+                        (free_size - movable_files, file_size - movable_files);
+
+                    compressed_blocks.push(DataBlock::File {
+                        id: *file_id,
+                        size: movable_files,
+                    });
+
+                    if new_free_size == 0 {
+                        left_block_idx += 1;
+                        free_space_in_left_block = None;
+                    } else {
+                        free_space_in_left_block = Some(new_free_size);
+                    }
+
+                    if new_file_size == 0 {
+                        right_block_idx -= 1;
+                        files_remaining_in_right_block = None;
+                    } else {
+                        files_remaining_in_right_block = Some(new_file_size);
+                    }
+                }
+            }
+        }
+
+        if let Some(size_left) = files_remaining_in_right_block {
+            if let DataBlock::File { id, size: _ } = &self.blocks[right_block_idx] {
+                compressed_blocks.push(DataBlock::File {
+                    id: *id,
+                    size: size_left,
+                })
+            }
+        } else if let DataBlock::File { id, size } = &self.blocks[left_block_idx] {
+            compressed_blocks.push(DataBlock::File {
+                id: *id,
+                size: *size,
+            });
+        }
+
+        let free_size = self.len() - compressed_blocks.iter().map(DataBlock::size).sum::<usize>();
+        compressed_blocks.push(DataBlock::Free { size: free_size });
+
+        let compacted = Disk {
+            blocks: compressed_blocks,
+        };
+        debug_assert_eq!(compacted.total_file_size(), self.total_file_size());
+        compacted
+    }
+
+    // Part 2: each file moves once, from the highest id down, to the leftmost gap it fits in.
+    // The gaps are kept by size, each size with its start positions in a min-heap, so finding
+    // that gap only looks at the smallest position of every size that is large enough.
+    pub fn defragmented(&self) -> Disk {
+        let mut files: Vec<(usize, usize, usize)> = Vec::new();
+        let mut gaps: BTreeMap<usize, BinaryHeap<Reverse<usize>>> = BTreeMap::new();
+        let mut position = 0;
+        for block in &self.blocks {
+            match *block {
+                DataBlock::File { id, size } => files.push((position, id, size)),
+                DataBlock::Free { size } if size > 0 => {
+                    gaps.entry(size).or_default().push(Reverse(position))
+                }
+                DataBlock::Free { .. } => (),
+            }
+            position += block.size();
+        }
+        let disk_size = position;
+
+        for (file_position, _, file_size) in files.iter_mut().rev() {
+            let leftmost_gap = gaps
+                .range(*file_size..)
+                .filter_map(|(&size, starts)| starts.peek().map(|&Reverse(start)| (start, size)))
+                .min();
+            let Some((gap_start, gap_size)) =
+                leftmost_gap.filter(|&(start, _)| start < *file_position)
+            else {
+                continue;
+            };
+
+            gaps.get_mut(&gap_size).and_then(BinaryHeap::pop);
+            if gap_size > *file_size {
+                gaps.entry(gap_size - *file_size)
+                    .or_default()
+                    .push(Reverse(gap_start + *file_size));
+            }
+            // the space left behind is right of every file still to move, so it is never used
+            *file_position = gap_start;
+        }
+
+        files.sort();
+        let mut blocks = Vec::new();
+        let mut position = 0;
+        for (file_position, id, size) in files {
+            if file_position > position {
+                blocks.push(DataBlock::Free {
+                    size: file_position - position,
+                });
+            }
+            blocks.push(DataBlock::File { id, size });
+            position = file_position + size;
+        }
+        if disk_size > position {
+            blocks.push(DataBlock::Free {
+                size: disk_size - position,
+            });
+        }
+
+        let defragmented = Disk { blocks };
+        debug_assert_eq!(defragmented.total_file_size(), self.total_file_size());
+        defragmented
+    }
+}
+
+// A disk map of random digits like the puzzle's, files never empty, but any length
+pub fn random_disk_map(len: usize) -> String {
+    let mut state: u64 = 0x2024;
+    (0..len)
+        .map(|index| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let digit = (state >> 33) as u8 % 10;
+            match index % 2 {
+                0 => char::from(b'1' + digit % 9),
+                _ => char::from(b'0' + digit),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_checksum() {
+        assert_eq!(partial_checksum(7, 10, 5), 7 * (10 + 11 + 12 + 13 + 14))
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(Disk::from_map("12345").render(), "0..111....22222");
+        assert_eq!(Disk::from_map("1").render(), "0");
+        assert!(Disk::from_map("").is_empty() && Disk::from_map("0").is_empty());
+    }
+
+    #[test]
+    fn test_tiny_disks() {
+        for (disk_map, compacted) in [
+            ("2", "00"),
+            ("232", "0011..."),
+            ("12345", "022111222......"),
+            ("3132", "000111..."),
+        ] {
+            let disk = Disk::from_map(disk_map);
+            assert_eq!(disk.compacted().render(), compacted);
+            assert_eq!(disk.compacted().total_file_size(), disk.total_file_size());
+        }
+        assert_eq!(Disk::from_map("232").compacted().checksum(), 5);
+        assert_eq!(Disk::from_map("3132").compacted().checksum(), 3 + 4 + 5);
+    }
+
+    #[test]
+    fn test_tiny_disks_part2() {
+        for (disk_map, defragmented) in [
+            ("2", "00"),
+            ("232", "0011..."),
+            ("12345", "0..111....22222"),
+            ("3132", "000.111.."),
+        ] {
+            let disk = Disk::from_map(disk_map);
+            assert_eq!(disk.defragmented().render(), defragmented);
+            assert_eq!(
+                disk.defragmented().total_file_size(),
+                disk.total_file_size()
+            );
+        }
+        assert_eq!(Disk::from_map("232").defragmented().checksum(), 5);
+        assert_eq!(Disk::from_map("3132").defragmented().checksum(), 4 + 5 + 6);
+    }
+
+    // Moves whole files block by block, as in the puzzle's description
+    fn defrag_by_blocks(disk: &Disk) -> u128 {
+        let mut blocks: Vec<Option<usize>> = disk
+            .blocks
+            .iter()
+            .flat_map(|block| match *block {
+                DataBlock::File { id, size } => vec![Some(id); size],
+                DataBlock::Free { size } => vec![None; size],
+            })
+            .collect();
+        let max_id = blocks.iter().flatten().max().copied().unwrap_or(0);
+        for id in (0..=max_id).rev() {
+            let start = blocks.iter().position(|&block| block == Some(id)).unwrap();
+            let size = blocks[start..]
+                .iter()
+                .take_while(|&&block| block == Some(id))
+                .count();
+            let gap = (0..start).find(|&gap| blocks[gap..gap + size].iter().all(Option::is_none));
+            if let Some(gap) = gap {
+                blocks[gap..gap + size].fill(Some(id));
+                blocks[start..start + size].fill(None);
+            }
+        }
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(position, block)| (position * block.unwrap_or(0)) as u128)
+            .sum()
+    }
+
+    #[test]
+    fn test_defrag_random_disks() {
+        for len in [1, 2, 7, 50, 301] {
+            let disk = Disk::from_map(&random_disk_map(len));
+            let defragmented = disk.defragmented();
+            assert_eq!(defragmented.len(), disk.len());
+            assert_eq!(
+                defragmented.checksum(),
+                defrag_by_blocks(&disk),
+                "{}",
+                disk.render()
+            );
+        }
+    }
+}
@@ -1,78 +1,851 @@
+use crate::utils::map2d::bitgrid::BitGrid;
 use crate::utils::map2d::grid::Bounds;
 use crate::utils::map2d::grid::Grid;
+use crate::utils::map2d::grid::ValidPosition;
+use crate::utils::parse::numbers::{numbers_in, NumberError};
 use std::{
-    fmt::Debug,
+    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
     fs::File,
-    io::{BufRead, BufReader, Lines},
+    io::{self, BufRead, BufReader},
+    path::Path,
     str::FromStr,
 };
 
 use itertools::Itertools;
 
-pub trait HasCharConverter {
-    fn convert(c: char) -> Self;
+// How a map tile is read from its character; Err explains why a character is not a valid tile
+pub trait HasCharConverter: Sized {
+    fn convert(c: char) -> Result<Self, String>;
 }
 
 impl HasCharConverter for u32 {
-    fn convert(c: char) -> Self {
-        c.to_digit(10).expect("Error converting digit.")
+    fn convert(c: char) -> Result<Self, String> {
+        c.to_digit(10).ok_or(String::from("expected a digit"))
+    }
+}
+
+// Occupancy maps: '#' is occupied, '.' is free
+impl HasCharConverter for bool {
+    fn convert(c: char) -> Result<Self, String> {
+        match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(String::from("expected '#' or '.'")),
+        }
     }
 }
 
 impl HasCharConverter for char {
-    fn convert(c: char) -> Self {
-        c
+    fn convert(c: char) -> Result<Self, String> {
+        Ok(c)
     }
 }
 
-impl<T: HasCharConverter> From<Vec<String>> for Grid<T> {
-    fn from(lines: Vec<String>) -> Self {
-        let data = lines
+// Position is 0-based, as in the grid
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidChar {
+    pub c: char,
+    pub pos: ValidPosition,
+    pub reason: String,
+}
+
+impl Display for InvalidChar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid character {:?} at row {}, column {}: {}",
+            self.c,
+            self.pos.1 + 1,
+            self.pos.0 + 1,
+            self.reason
+        )
+    }
+}
+
+// Why rows of text do not make a grid; rows are 0-based, as in the grid
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    Ragged {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    InvalidChar(InvalidChar),
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Ragged { row, len, expected } => write!(
+                f,
+                "row {} has {} characters, expected {} like the first row",
+                row + 1,
+                len,
+                expected
+            ),
+            GridError::InvalidChar(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl GridError {
+    pub fn row(&self) -> usize {
+        match self {
+            GridError::Ragged { row, .. } => *row,
+            GridError::InvalidChar(err) => err.pos.1,
+        }
+    }
+}
+
+impl Grid<char> {
+    pub fn try_parse<T: HasCharConverter>(&self) -> Result<Grid<T>, InvalidChar> {
+        let data = self
+            .data
             .iter()
-            .map(|line| -> Vec<T> { line.chars().map(|c| -> T { T::convert(c) }).collect_vec() })
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, &c)| {
+                        T::convert(c).map_err(|reason| InvalidChar {
+                            c,
+                            pos: ValidPosition(x, y),
+                            reason,
+                        })
+                    })
+                    .try_collect()
+            })
+            .try_collect()?;
+        Ok(Grid {
+            data,
+            bounds: self.bounds,
+        })
+    }
+}
+
+impl<T: HasCharConverter> Grid<T> {
+    // All rows must be as long as the first
+    pub fn try_from_rows<S: AsRef<str>>(
+        rows: impl IntoIterator<Item = S>,
+    ) -> Result<Self, GridError> {
+        let data = rows
+            .into_iter()
+            .map(|row| row.as_ref().chars().collect_vec())
             .collect_vec();
-        let bounds = Bounds(data[0].len(), data.len());
+        let bounds = Bounds(data.first().map_or(0, |row| row.len()), data.len());
+        if let Some((row, cells)) = data
+            .iter()
+            .enumerate()
+            .find(|(_, cells)| cells.len() != bounds.0)
+        {
+            return Err(GridError::Ragged {
+                row,
+                len: cells.len(),
+                expected: bounds.0,
+            });
+        }
         Grid { data, bounds }
+            .try_parse()
+            .map_err(GridError::InvalidChar)
+    }
+
+    // Panics on ragged rows or invalid characters; use try_from_rows or Input::grid to handle
+    // them
+    pub fn from_rows<S: AsRef<str>>(rows: impl IntoIterator<Item = S>) -> Self {
+        Self::try_from_rows(rows).unwrap_or_else(|err| panic!("{}", err))
     }
 }
 
-pub fn lines_from_file(path: &str) -> Lines<BufReader<File>> {
-    let file = File::open(path).expect("Failed to open file.");
-    BufReader::new(file).lines()
+impl<T: HasCharConverter> From<Vec<String>> for Grid<T> {
+    fn from(lines: Vec<String>) -> Self {
+        Grid::from_rows(lines)
+    }
 }
 
-pub fn strings_from_file(path: &str) -> impl Iterator<Item = String> {
-    lines_from_file(path).map(|line| line.unwrap())
+// Errors from reading puzzle input, located by path and (1-based) line number
+#[derive(Debug)]
+pub enum AocError {
+    Io {
+        path: String,
+        line: Option<usize>,
+        source: io::Error,
+    },
+    Parse {
+        path: String,
+        line: usize,
+        message: String,
+    },
 }
 
-pub fn two_columns_from_file<T: FromStr>(path: &str) -> (Vec<T>, Vec<T>)
+impl Display for AocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AocError::Io {
+                path,
+                line: None,
+                source,
+            } => write!(f, "{}: {}", path, source),
+            AocError::Io {
+                path,
+                line: Some(line),
+                source,
+            } => write!(f, "{}:{}: {}", path, line, source),
+            AocError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path, line, message),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Io { source, .. } => Some(source),
+            AocError::Parse { .. } => None,
+        }
+    }
+}
+
+fn io_error(path: &str, line: Option<usize>, source: io::Error) -> AocError {
+    AocError::Io {
+        path: path.to_string(),
+        line,
+        source,
+    }
+}
+
+fn parse_error(path: &str, index: usize, message: String) -> AocError {
+    AocError::Parse {
+        path: path.to_string(),
+        line: index + 1,
+        message,
+    }
+}
+
+fn parse_words<T: FromStr>(path: &str, index: usize, line: &str) -> Result<Vec<T>, AocError>
 where
     T::Err: Debug,
 {
-    lines_from_file(path)
-        .map(|line| -> (T, T) {
-            line.unwrap()
-                .split_whitespace()
-                .map(|word| word.parse().expect(&format!("Failed to parse: {}.", word)))
-                .collect_tuple()
-                .expect("Each line must contain exactly two elements.")
+    line.split_whitespace()
+        .map(|word| {
+            word.parse::<T>().map_err(|err| {
+                parse_error(
+                    path,
+                    index,
+                    format!("Failed to parse {:?}: {:?}", word, err),
+                )
+            })
         })
-        .unzip()
+        .collect()
+}
+
+// source names the reader in error messages, e.g. a path
+pub fn lines_from_reader<R: BufRead>(
+    reader: R,
+    source: &str,
+) -> impl Iterator<Item = Result<String, AocError>> {
+    let source = source.to_string();
+    reader
+        .lines()
+        .enumerate()
+        .map(move |(index, line)| line.map_err(|err| io_error(&source, Some(index + 1), err)))
+}
+
+#[cfg(feature = "compressed")]
+fn decompress(file: File, extension: &str) -> io::Result<Box<dyn BufRead>> {
+    match extension {
+        "gz" => Ok(Box::new(BufReader::new(flate2::read::GzDecoder::new(file)))),
+        "zst" => Ok(Box::new(BufReader::new(zstd::Decoder::new(file)?))),
+        _ => Ok(Box::new(BufReader::new(file))),
+    }
+}
+
+#[cfg(not(feature = "compressed"))]
+fn decompress(file: File, extension: &str) -> io::Result<Box<dyn BufRead>> {
+    match extension {
+        "gz" | "zst" => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "reading compressed input requires the `compressed` feature",
+        )),
+        _ => Ok(Box::new(BufReader::new(file))),
+    }
 }
 
-pub fn rows_from_file<T: FromStr>(path: &str) -> Vec<Vec<T>>
+// Every loader opens its file here, so errors name the path the same way.
+// Files ending in .gz or .zst are decompressed on the fly.
+fn open(path: &Path) -> Result<(Box<dyn BufRead>, String), AocError> {
+    let source = path.display().to_string();
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    File::open(path)
+        .and_then(|file| decompress(file, extension))
+        .map(|reader| (reader, source.clone()))
+        .map_err(|err| io_error(&source, None, err))
+}
+
+pub fn lines_from_file(
+    path: impl AsRef<Path>,
+) -> Result<impl Iterator<Item = Result<String, AocError>>, AocError> {
+    let (reader, source) = open(path.as_ref())?;
+    Ok(lines_from_reader(reader, &source))
+}
+
+// Consecutive groups of n lines, read lazily. The last chunk holds whatever is left
+// and is shorter than n if the line count is not a multiple of n; it is never empty.
+pub fn chunks_of_lines(
+    path: impl AsRef<Path>,
+    n: usize,
+) -> Result<impl Iterator<Item = Result<Vec<String>, AocError>>, AocError> {
+    assert!(n > 0, "Chunks must hold at least one line.");
+    let mut lines = lines_from_file(path)?;
+    Ok(std::iter::from_fn(move || {
+        let mut chunk = Vec::with_capacity(n);
+        for line in lines.by_ref() {
+            match line {
+                Ok(line) => chunk.push(line),
+                Err(err) => return Some(Err(err)),
+            }
+            if chunk.len() == n {
+                return Some(Ok(chunk));
+            }
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    }))
+}
+
+pub type MarkerPositions = HashMap<char, Vec<ValidPosition>>;
+
+// Puzzle input read up front, from a file or any other reader, so that tests can use literals
+pub struct Input {
+    source: String,
+    lines: Vec<String>,
+}
+
+impl Input {
+    pub fn from_reader<R: BufRead>(reader: R, source: &str) -> Result<Self, AocError> {
+        Ok(Input {
+            source: source.to_string(),
+            lines: lines_from_reader(reader, source).try_collect()?,
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AocError> {
+        let (reader, source) = open(path.as_ref())?;
+        Self::from_reader(reader, &source)
+    }
+
+    pub fn from_text(text: &str) -> Self {
+        Input {
+            source: String::from("<inline>"),
+            lines: text.lines().map(String::from).collect(),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn strings(self) -> impl Iterator<Item = String> {
+        self.lines.into_iter()
+    }
+
+    // The whole input, lines joined by '\n'
+    pub fn text(self) -> String {
+        self.lines.join("\n")
+    }
+
+    // Everything before and after the first blank line, e.g. a header and a body
+    pub fn two_sections(self) -> Result<(String, String), AocError> {
+        let Some(separator) = self.lines.iter().position(|line| line.is_empty()) else {
+            return Err(parse_error(
+                &self.source,
+                self.lines.len().saturating_sub(1),
+                String::from("Expected a blank line separating two sections."),
+            ));
+        };
+        Ok((
+            self.lines[..separator].join("\n"),
+            self.lines[separator + 1..].join("\n"),
+        ))
+    }
+
+    // Groups of consecutive non-empty lines, i.e. paragraphs separated by blank lines
+    pub fn blocks(self) -> impl Iterator<Item = Vec<String>> {
+        self.lines
+            .into_iter()
+            .chunk_by(|line| line.is_empty())
+            .into_iter()
+            .filter(|(is_empty, _)| !is_empty)
+            .map(|(_, block)| block.collect_vec())
+            .collect_vec()
+            .into_iter()
+    }
+
+    fn grid_error(&self, err: GridError) -> AocError {
+        parse_error(&self.source, err.row(), err.to_string())
+    }
+
+    pub fn grid<T: HasCharConverter>(self) -> Result<Grid<T>, AocError> {
+        Grid::try_from_rows(&self.lines).map_err(|err| self.grid_error(err))
+    }
+
+    pub fn bit_grid(self) -> Result<BitGrid, AocError> {
+        Ok(BitGrid::from(&self.grid::<bool>()?))
+    }
+
+    // The grid with the given markers (start, end, robot, ...) located and replaced by '.'
+    // before the remaining characters are converted
+    pub fn grid_with_markers<T: HasCharConverter>(
+        self,
+        markers: &[char],
+    ) -> Result<(Grid<T>, MarkerPositions), AocError> {
+        let mut grid: Grid<char> =
+            Grid::try_from_rows(&self.lines).map_err(|err| self.grid_error(err))?;
+        let positions = grid.take_markers(markers, '.');
+        let grid = grid
+            .try_parse()
+            .map_err(|err| self.grid_error(GridError::InvalidChar(err)))?;
+        Ok((grid, positions))
+    }
+
+    // One value per line; the first failure is reported with its line number
+    pub fn parse_lines_with<T, E: Debug>(
+        self,
+        mut parse: impl FnMut(&str) -> Result<T, E>,
+    ) -> Result<Vec<T>, AocError> {
+        let source = self.source;
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                parse(line).map_err(|err| {
+                    parse_error(
+                        &source,
+                        index,
+                        format!("Failed to parse {:?}: {:?}", line, err),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn parse_lines<T: FromStr>(self) -> Result<Vec<T>, AocError>
+    where
+        T::Err: Debug,
+    {
+        self.parse_lines_with(str::parse)
+    }
+
+    // Exactly N separated fields per line, e.g. "3,4" with separator ","
+    pub fn tuples<T: FromStr, const N: usize>(
+        self,
+        separator: &str,
+    ) -> Result<Vec<[T; N]>, AocError>
+    where
+        T::Err: Debug,
+    {
+        self.parse_lines_with(|line| -> Result<[T; N], String> {
+            let fields: Vec<T> = line
+                .split(separator)
+                .map(|field| field.trim().parse().map_err(|err| format!("{:?}", err)))
+                .try_collect()?;
+            let count = fields.len();
+            fields
+                .try_into()
+                .map_err(|_| format!("Expected {} fields, found {}.", N, count))
+        })
+    }
+
+    pub fn ints(self) -> Result<Vec<Vec<i64>>, AocError> {
+        self.parse_lines_with(numbers_in::<i64>)
+    }
+
+    // Whitespace-separated columns; every row must have exactly N fields
+    pub fn columns<T: FromStr, const N: usize>(self) -> Result<[Vec<T>; N], AocError>
+    where
+        T::Err: Debug,
+    {
+        let mut columns: [Vec<T>; N] =
+            std::array::from_fn(|_| Vec::with_capacity(self.lines.len()));
+        for (index, line) in self.lines.iter().enumerate() {
+            let fields = parse_words(&self.source, index, line)?;
+            if fields.len() != N {
+                return Err(parse_error(
+                    &self.source,
+                    index,
+                    format!("Expected {} columns, found {}.", N, fields.len()),
+                ));
+            }
+            for (column, field) in columns.iter_mut().zip(fields) {
+                column.push(field);
+            }
+        }
+        Ok(columns)
+    }
+
+    pub fn two_columns<T: FromStr>(self) -> Result<(Vec<T>, Vec<T>), AocError>
+    where
+        T::Err: Debug,
+    {
+        let [left, right] = self.columns()?;
+        Ok((left, right))
+    }
+
+    pub fn rows<T: FromStr>(self) -> Result<Vec<Vec<T>>, AocError>
+    where
+        T::Err: Debug,
+    {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| parse_words(&self.source, index, line))
+            .collect()
+    }
+}
+
+// Path-based shorthands for the Input readers
+
+pub fn strings_from_file(path: impl AsRef<Path>) -> Result<impl Iterator<Item = String>, AocError> {
+    Ok(Input::from_file(path)?.strings())
+}
+
+pub fn string_from_file(path: impl AsRef<Path>) -> Result<String, AocError> {
+    Ok(Input::from_file(path)?.text())
+}
+
+pub fn two_sections(path: impl AsRef<Path>) -> Result<(String, String), AocError> {
+    Input::from_file(path)?.two_sections()
+}
+
+pub fn blocks_from_file(
+    path: impl AsRef<Path>,
+) -> Result<impl Iterator<Item = Vec<String>>, AocError> {
+    Ok(Input::from_file(path)?.blocks())
+}
+
+pub fn grid_from_file<T: HasCharConverter>(path: impl AsRef<Path>) -> Result<Grid<T>, AocError> {
+    Input::from_file(path)?.grid()
+}
+
+pub fn bit_grid_from_file(path: impl AsRef<Path>) -> Result<BitGrid, AocError> {
+    Input::from_file(path)?.bit_grid()
+}
+
+pub fn grid_with_markers<T: HasCharConverter>(
+    path: impl AsRef<Path>,
+    markers: &[char],
+) -> Result<(Grid<T>, MarkerPositions), AocError> {
+    Input::from_file(path)?.grid_with_markers(markers)
+}
+
+pub fn parse_lines_with<T, E: Debug>(
+    path: impl AsRef<Path>,
+    parse: impl FnMut(&str) -> Result<T, E>,
+) -> Result<Vec<T>, AocError> {
+    Input::from_file(path)?.parse_lines_with(parse)
+}
+
+pub fn parse_lines<T: FromStr>(path: impl AsRef<Path>) -> Result<Vec<T>, AocError>
 where
     T::Err: Debug,
 {
-    lines_from_file(path)
-        .map(|line| -> Vec<T> {
-            line.unwrap()
-                .split_whitespace()
-                .map(|word: &str| {
-                    word.parse::<T>()
-                        .expect(&format!("Failed to parse: {}.", word))
-                })
-                .collect()
+    Input::from_file(path)?.parse_lines()
+}
+
+// All signed integers in a line of prose, e.g. "Button A: X+94, Y-34" -> [94, -34]; fails
+// on a run of digits too long for an i64
+pub fn ints_in_line(line: &str) -> Result<Vec<i64>, NumberError> {
+    numbers_in(line)
+}
+
+pub fn ints_from_file(path: impl AsRef<Path>) -> Result<Vec<Vec<i64>>, AocError> {
+    Input::from_file(path)?.ints()
+}
+
+pub fn tuples_from_file<T: FromStr, const N: usize>(
+    path: impl AsRef<Path>,
+    separator: &str,
+) -> Result<Vec<[T; N]>, AocError>
+where
+    T::Err: Debug,
+{
+    Input::from_file(path)?.tuples(separator)
+}
+
+pub fn columns_from_file<T: FromStr, const N: usize>(
+    path: impl AsRef<Path>,
+) -> Result<[Vec<T>; N], AocError>
+where
+    T::Err: Debug,
+{
+    Input::from_file(path)?.columns()
+}
+
+pub fn two_columns_from_file<T: FromStr>(
+    path: impl AsRef<Path>,
+) -> Result<(Vec<T>, Vec<T>), AocError>
+where
+    T::Err: Debug,
+{
+    Input::from_file(path)?.two_columns()
+}
+
+pub fn rows_from_file<T: FromStr>(path: impl AsRef<Path>) -> Result<Vec<Vec<T>>, AocError>
+where
+    T::Err: Debug,
+{
+    Input::from_file(path)?.rows()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_carry_location() {
+        let missing = strings_from_file("input/does-not-exist.txt").err().unwrap();
+        assert!(matches!(missing, AocError::Io { line: None, .. }));
+
+        // the day 1 input has two numeric columns, which do not parse as bools
+        let err = two_columns_from_file::<bool>("input/input01.txt.test1").unwrap_err();
+        assert!(matches!(err, AocError::Parse { line: 1, .. }));
+        assert!(err.to_string().starts_with("input/input01.txt.test1:1:"));
+    }
+
+    #[test]
+    fn test_path_buf() {
+        let path = Path::new("input").join("input01.txt.test1");
+        let from_path_buf = two_columns_from_file::<i32>(&path).unwrap();
+        assert_eq!(
+            from_path_buf,
+            two_columns_from_file::<i32>("input/input01.txt.test1").unwrap()
+        );
+
+        let missing = strings_from_file(path.with_extension("missing"))
+            .err()
+            .unwrap();
+        assert!(missing
+            .to_string()
+            .starts_with("input/input01.txt.missing: "));
+    }
+
+    #[cfg(feature = "compressed")]
+    #[test]
+    fn test_compressed() {
+        use std::io::Write;
+
+        let text = std::fs::read_to_string("input/input01.txt.test1").unwrap();
+        let dir = std::env::temp_dir();
+
+        let gz_path = dir.join("aoc_input01.txt.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&gz_path).unwrap(), Default::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+        assert_eq!(string_from_file(&gz_path).unwrap(), text.trim_end());
+
+        let zst_path = dir.join("aoc_input01.txt.zst");
+        zstd::stream::copy_encode(text.as_bytes(), File::create(&zst_path).unwrap(), 0).unwrap();
+        assert_eq!(string_from_file(&zst_path).unwrap(), text.trim_end());
+    }
+
+    #[cfg(not(feature = "compressed"))]
+    #[test]
+    fn test_compressed_needs_feature() {
+        let path = std::env::temp_dir().join("aoc_uncompressed.gz");
+        std::fs::write(&path, "1 2").unwrap();
+        let err = strings_from_file(&path).err().unwrap();
+        assert!(err.to_string().contains("`compressed` feature"));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let secrets = parse_lines::<u32>("input/input22.txt.test1").unwrap();
+        assert_eq!(secrets, vec![1, 10, 100, 2024]);
+
+        let err = parse_lines::<u32>("input/input01.txt.test1").unwrap_err();
+        assert!(matches!(err, AocError::Parse { line: 1, .. }));
+
+        let lengths = parse_lines_with("input/input01.txt.test1", |line| -> Result<usize, ()> {
+            Ok(line.len())
         })
-        .collect()
+        .unwrap();
+        assert_eq!(lengths, vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn test_inline_input() {
+        let input = Input::from_text("3   4\n4   3\n2   5");
+        assert_eq!(
+            input.two_columns::<u32>().unwrap(),
+            (vec![3, 4, 2], vec![4, 3, 5])
+        );
+
+        let blocks = Input::from_text("a\nb\n\n\nc").blocks().collect_vec();
+        assert_eq!(blocks, vec![vec!["a", "b"], vec!["c"]]);
+
+        let reader = io::Cursor::new("1\nx\n");
+        let err = Input::from_reader(reader, "cursor")
+            .unwrap()
+            .parse_lines::<u8>()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("cursor:2:"));
+    }
+
+    #[test]
+    fn test_sections() {
+        let (header, body) = Input::from_text("r, wr\n\nbrwrr\n\nbggr")
+            .two_sections()
+            .unwrap();
+        assert_eq!(header, "r, wr");
+        assert_eq!(body, "brwrr\n\nbggr");
+        assert!(Input::from_text("no\nblank").two_sections().is_err());
+        assert_eq!(Input::from_text("a\nb\n").text(), "a\nb");
+    }
+
+    #[test]
+    fn test_invalid_grid_chars() {
+        let grid: Grid<u32> = Input::from_text("012\n345").grid().unwrap();
+        assert_eq!(*grid.value(&ValidPosition(2, 1)), 5);
+
+        let err = Input::from_text("012\n3x5").grid::<u32>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "<inline>:2: invalid character 'x' at row 2, column 2: expected a digit"
+        );
+    }
+
+    #[test]
+    fn test_ragged_grid() {
+        assert_eq!(
+            Grid::<char>::try_from_rows(["abc", "de", "fgh"]).err(),
+            Some(GridError::Ragged {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        );
+        let err = Input::from_text(
+            "012
+345
+6789",
+        )
+        .grid::<u32>()
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "<inline>:3: row 3 has 4 characters, expected 3 like the first row"
+        );
+        assert!(Input::from_text("#.\n#")
+            .grid_with_markers::<bool>(&['S'])
+            .is_err());
+        assert!(Grid::<char>::try_from_rows(Vec::<&str>::new()).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "row 2 has 1 characters")]
+    fn test_ragged_grid_panics() {
+        Grid::<char>::from_rows(["ab", "c"]);
+    }
+
+    #[test]
+    fn test_bit_grid() {
+        let bits = Input::from_text("..#\n#..").bit_grid().unwrap();
+        assert_eq!(
+            bits.ones().collect_vec(),
+            vec![ValidPosition(2, 0), ValidPosition(0, 1)]
+        );
+        assert!(Input::from_text(".^#").bit_grid().is_err());
+    }
+
+    #[test]
+    fn test_tuples() {
+        let points = Input::from_text("5,4\n4, 2").tuples::<u32, 2>(",").unwrap();
+        assert_eq!(points, vec![[5, 4], [4, 2]]);
+        let err = Input::from_text("1,2\n1,2,3")
+            .tuples::<u32, 2>(",")
+            .unwrap_err();
+        assert!(matches!(err, AocError::Parse { line: 2, .. }));
+        assert_eq!(
+            tuples_from_file::<usize, 2>("input/input18.txt.test1", ",")
+                .unwrap()
+                .len(),
+            25
+        );
+    }
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints_in_line("Button A: X+94, Y-34"), Ok(vec![94, -34]));
+        assert_eq!(ints_in_line("no numbers - here"), Ok(Vec::new()));
+        assert_eq!(
+            ints_in_line("page 123456789012345678901 of 3"),
+            Err(NumberError::Overflow(String::from("123456789012345678901")))
+        );
+        let robots = ints_from_file("input/input14.txt.test1").unwrap();
+        assert!(robots.iter().all(|robot| robot.len() == 4));
+    }
+
+    #[test]
+    fn test_grid_with_markers() {
+        let (grid, markers): (Grid<char>, _) =
+            grid_with_markers("input/input16.txt.test1", &['S', 'E', '@']).unwrap();
+        assert_eq!(markers[&'S'].len(), 1);
+        assert_eq!(markers[&'E'].len(), 1);
+        assert!(markers[&'@'].is_empty());
+        assert_eq!(*grid.value(&markers[&'S'][0]), '.');
+        assert_eq!(grid.position_of(&'S'), None);
+    }
+
+    #[test]
+    fn test_chunks_of_lines() {
+        // four machines of three lines, separated by blank lines, without a trailing one
+        let chunks: Vec<Vec<String>> = chunks_of_lines("input/input13.txt.test1", 4)
+            .unwrap()
+            .try_collect()
+            .unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks[..3].iter().all(|chunk| chunk.len() == 4));
+        assert_eq!(chunks[3].len(), 3);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = blocks_from_file("input/input05.txt.test1")
+            .unwrap()
+            .collect_vec();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].iter().all(|line| line.contains('|')));
+        assert!(blocks[1].iter().all(|line| line.contains(',')));
+    }
+
+    #[test]
+    fn test_columns() {
+        let [a, b, c] = Input::from_text("1 2 3\n4 5 6").columns::<u8, 3>().unwrap();
+        assert_eq!((a, b, c), (vec![1, 4], vec![2, 5], vec![3, 6]));
+
+        let err = Input::from_text("1 2 3\n4 5\n7 8 9")
+            .columns::<u8, 3>()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "<inline>:2: Expected 3 columns, found 2.");
+    }
+
+    #[test]
+    fn test_columns_and_rows() {
+        let (left, right) = two_columns_from_file::<i32>("input/input01.txt.test1").unwrap();
+        assert_eq!(left.len(), right.len());
+        assert_eq!(
+            rows_from_file::<i32>("input/input01.txt.test1")
+                .unwrap()
+                .len(),
+            left.len()
+        );
+    }
 }
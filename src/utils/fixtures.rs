@@ -0,0 +1,59 @@
+// The path of a day's example input, e.g. test_input(12, 3) -> "input/input12.txt.test3"
+pub fn test_input(day: u32, variant: u32) -> String {
+    format!("input/input{:02}.txt.test{}", day, variant)
+}
+
+// Removes the first line break and the indentation common to all non-blank lines,
+// so sample inputs can be written indented inside a test
+pub fn dedent(text: &str) -> String {
+    let text = text.strip_prefix('\n').unwrap_or(text);
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A sample input embedded in a test, read from memory like Input::from_file reads a file:
+// inline!("
+//     3   4
+//     4   3
+// ").two_columns::<i32>()
+#[macro_export]
+macro_rules! inline {
+    ($text:expr) => {
+        $crate::utils::file_io::Input::from_text(&$crate::utils::fixtures::dedent($text))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_test_input() {
+        assert_eq!(test_input(1, 2), "input/input01.txt.test2");
+        assert_eq!(test_input(12, 3), "input/input12.txt.test3");
+    }
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(dedent("\n    a\n\n      b\n    "), "a\n\n  b\n");
+        let input = inline!(
+            "
+            3   4
+            4   3
+            "
+        );
+        assert_eq!(input.source(), "<inline>");
+        assert_eq!(
+            input.two_columns::<i32>().unwrap(),
+            (vec![3, 4], vec![4, 3])
+        );
+    }
+}
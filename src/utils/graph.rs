@@ -0,0 +1,281 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+pub mod cliques;
+pub mod components;
+pub mod dot;
+pub mod flow;
+pub mod matching;
+pub mod paths;
+pub mod scc;
+
+// The nodes reachable from a node in one step: neighbours, or successors for directed graphs
+pub trait Adjacency<N> {
+    fn nodes<'a>(&'a self) -> impl Iterator<Item = &'a N>
+    where
+        N: 'a;
+    fn adjacent<'a>(&'a self, node: &N) -> impl Iterator<Item = &'a N>
+    where
+        N: 'a;
+}
+
+// An undirected graph without edge weights, stored as adjacency sets
+#[derive(Debug, Clone)]
+pub struct Graph<N: Hash + Eq> {
+    adjacency: HashMap<N, HashSet<N>>,
+}
+
+impl<N: Hash + Eq> Default for Graph<N> {
+    fn default() -> Self {
+        Graph {
+            adjacency: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Hash + Eq + Clone> Graph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.adjacency.entry(node).or_default();
+    }
+
+    // Adds both endpoints if they are new; returns false if the edge was already there
+    pub fn add_edge(&mut self, a: N, b: N) -> bool {
+        self.adjacency
+            .entry(b.clone())
+            .or_default()
+            .insert(a.clone());
+        self.adjacency.entry(a).or_default().insert(b)
+    }
+
+    pub fn remove_edge(&mut self, a: &N, b: &N) -> bool {
+        let removed = self.adjacency.get_mut(a).is_some_and(|set| set.remove(b));
+        if let Some(set) = self.adjacency.get_mut(b) {
+            set.remove(a);
+        }
+        removed
+    }
+}
+
+impl<N: Hash + Eq> Graph<N> {
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.adjacency.contains_key(node)
+    }
+
+    pub fn contains_edge(&self, a: &N, b: &N) -> bool {
+        self.adjacency.get(a).is_some_and(|set| set.contains(b))
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.adjacency.keys()
+    }
+
+    // Empty for nodes that are not in the graph
+    pub fn neighbors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.adjacency.get(node).into_iter().flatten()
+    }
+
+    pub fn neighbor_set(&self, node: &N) -> Option<&HashSet<N>> {
+        self.adjacency.get(node)
+    }
+
+    pub fn degree(&self, node: &N) -> usize {
+        self.adjacency.get(node).map_or(0, HashSet::len)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        // every edge is stored at both ends, self-loops only once
+        let loops = self
+            .adjacency
+            .iter()
+            .filter(|(node, set)| set.contains(node))
+            .count();
+        (self.adjacency.values().map(HashSet::len).sum::<usize>() + loops) / 2
+    }
+}
+
+impl<N: Hash + Eq + Clone> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(edges: I) -> Self {
+        let mut graph = Graph::new();
+        for (a, b) in edges {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+}
+
+// A directed graph without edge weights; predecessors are stored as well as successors
+#[derive(Debug, Clone)]
+pub struct DiGraph<N: Hash + Eq> {
+    successors: HashMap<N, HashSet<N>>,
+    predecessors: HashMap<N, HashSet<N>>,
+}
+
+impl<N: Hash + Eq> Default for DiGraph<N> {
+    fn default() -> Self {
+        DiGraph {
+            successors: HashMap::new(),
+            predecessors: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Hash + Eq + Clone> DiGraph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.predecessors.entry(node.clone()).or_default();
+        self.successors.entry(node).or_default();
+    }
+
+    // Adds both endpoints if they are new; returns false if the edge was already there
+    pub fn add_edge(&mut self, from: N, to: N) -> bool {
+        self.add_node(from.clone());
+        self.add_node(to.clone());
+        self.predecessors
+            .get_mut(&to)
+            .expect("Node was just added.")
+            .insert(from.clone());
+        self.successors
+            .get_mut(&from)
+            .expect("Node was just added.")
+            .insert(to)
+    }
+}
+
+impl<N: Hash + Eq> DiGraph<N> {
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.successors.contains_key(node)
+    }
+
+    pub fn contains_edge(&self, from: &N, to: &N) -> bool {
+        self.successors
+            .get(from)
+            .is_some_and(|set| set.contains(to))
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.successors.keys()
+    }
+
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.successors.get(node).into_iter().flatten()
+    }
+
+    pub fn predecessors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.predecessors.get(node).into_iter().flatten()
+    }
+
+    pub fn out_degree(&self, node: &N) -> usize {
+        self.successors.get(node).map_or(0, HashSet::len)
+    }
+
+    pub fn in_degree(&self, node: &N) -> usize {
+        self.predecessors.get(node).map_or(0, HashSet::len)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.successors.values().map(HashSet::len).sum()
+    }
+}
+
+impl<N: Hash + Eq + Clone> FromIterator<(N, N)> for DiGraph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(edges: I) -> Self {
+        let mut graph = DiGraph::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+}
+
+impl<N: Hash + Eq> Adjacency<N> for Graph<N> {
+    fn nodes<'a>(&'a self) -> impl Iterator<Item = &'a N>
+    where
+        N: 'a,
+    {
+        Graph::nodes(self)
+    }
+
+    fn adjacent<'a>(&'a self, node: &N) -> impl Iterator<Item = &'a N>
+    where
+        N: 'a,
+    {
+        self.neighbors(node)
+    }
+}
+
+impl<N: Hash + Eq> Adjacency<N> for DiGraph<N> {
+    fn nodes<'a>(&'a self) -> impl Iterator<Item = &'a N>
+    where
+        N: 'a,
+    {
+        DiGraph::nodes(self)
+    }
+
+    fn adjacent<'a>(&'a self, node: &N) -> impl Iterator<Item = &'a N>
+    where
+        N: 'a,
+    {
+        self.successors(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let mut graph: Graph<&str> = [("kh", "tc"), ("qp", "kh"), ("de", "cg")]
+            .into_iter()
+            .collect();
+        assert!(!graph.add_edge("tc", "kh"));
+        graph.add_node("ub");
+
+        assert_eq!(graph.node_count(), 6);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.degree(&"kh"), 2);
+        assert_eq!(graph.degree(&"ub"), 0);
+        assert_eq!(graph.degree(&"xx"), 0);
+        assert!(graph.contains_edge(&"tc", &"kh"));
+        let mut neighbors: Vec<_> = graph.neighbors(&"kh").collect();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![&"qp", &"tc"]);
+
+        assert!(graph.remove_edge(&"kh", &"tc"));
+        assert!(!graph.contains_edge(&"tc", &"kh"));
+        assert_eq!(graph.edge_count(), 2);
+
+        graph.add_edge("ub", "ub");
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_digraph() {
+        let mut graph: DiGraph<u8> = [(1, 2), (2, 3), (1, 3)].into_iter().collect();
+        assert!(!graph.add_edge(1, 2));
+        assert!(graph.add_edge(2, 1));
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.out_degree(&1), 2);
+        assert_eq!(graph.in_degree(&3), 2);
+        assert!(graph.contains_edge(&2, &3) && !graph.contains_edge(&3, &2));
+        assert_eq!(graph.predecessors(&2).collect::<Vec<_>>(), vec![&1]);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::utils::graph::Graph;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+// The graph with nodes numbered 0..n, so the searches can work on plain index sets
+struct Indexed<'a, N> {
+    nodes: Vec<&'a N>,
+    adjacency: Vec<HashSet<usize>>,
+}
+
+impl<'a, N: Hash + Eq> Indexed<'a, N> {
+    fn new(graph: &'a Graph<N>) -> Self {
+        let nodes: Vec<&N> = graph.nodes().collect();
+        let index: HashMap<&N, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        let adjacency = nodes
+            .iter()
+            .map(|node| {
+                graph
+                    .neighbors(node)
+                    .map(|neighbor| index[neighbor])
+                    .collect()
+            })
+            .collect();
+        Indexed { nodes, adjacency }
+    }
+
+    // Repeatedly removes a node of minimum remaining degree. Following this order, every node
+    // has at most degeneracy-many later neighbours, which keeps the top-level candidate sets small.
+    fn degeneracy_order(&self) -> Vec<usize> {
+        let mut degree: Vec<usize> = self.adjacency.iter().map(HashSet::len).collect();
+        let mut removed = vec![false; self.nodes.len()];
+        let mut order = Vec::with_capacity(self.nodes.len());
+        for _ in 0..self.nodes.len() {
+            let next = (0..self.nodes.len())
+                .filter(|&v| !removed[v])
+                .min_by_key(|&v| degree[v])
+                .expect("A node is left while the order is incomplete.");
+            removed[next] = true;
+            order.push(next);
+            for &neighbor in &self.adjacency[next] {
+                degree[neighbor] -= 1;
+            }
+        }
+        order
+    }
+}
+
+struct MaxCliqueSearch<'a> {
+    adjacency: &'a [HashSet<usize>],
+    best: Vec<usize>,
+}
+
+impl MaxCliqueSearch<'_> {
+    // Bron–Kerbosch with Tomita pivoting, skipping branches that cannot beat the best clique
+    fn extend(
+        &mut self,
+        clique: &mut Vec<usize>,
+        candidates: HashSet<usize>,
+        mut excluded: HashSet<usize>,
+    ) {
+        if clique.len() + candidates.len() <= self.best.len() {
+            return;
+        }
+        if candidates.is_empty() {
+            // clique beats best, and it is maximal if nothing is excluded either;
+            // a non-maximal clique cannot be the largest, so it is fine to record it anyway
+            self.best = clique.clone();
+            return;
+        }
+
+        // the pivot's neighbours are covered by the branches that contain the pivot
+        let pivot = candidates
+            .iter()
+            .chain(&excluded)
+            .max_by_key(|&&u| candidates.intersection(&self.adjacency[u]).count())
+            .copied()
+            .expect("Candidates are not empty.");
+        let branches: Vec<usize> = candidates
+            .difference(&self.adjacency[pivot])
+            .copied()
+            .collect();
+
+        let mut candidates = candidates;
+        for v in branches {
+            let neighbors = &self.adjacency[v];
+            clique.push(v);
+            self.extend(
+                clique,
+                candidates.intersection(neighbors).copied().collect(),
+                excluded.intersection(neighbors).copied().collect(),
+            );
+            clique.pop();
+            candidates.remove(&v);
+            excluded.insert(v);
+        }
+    }
+}
+
+// A largest set of pairwise connected nodes; empty for an empty graph
+pub fn max_clique<N: Hash + Eq + Clone>(graph: &Graph<N>) -> HashSet<N> {
+    let indexed = Indexed::new(graph);
+    let order = indexed.degeneracy_order();
+    let mut position = vec![0; order.len()];
+    for (i, &v) in order.iter().enumerate() {
+        position[v] = i;
+    }
+
+    let mut search = MaxCliqueSearch {
+        adjacency: &indexed.adjacency,
+        best: Vec::new(),
+    };
+    for &v in &order {
+        let (later, earlier) = indexed.adjacency[v]
+            .iter()
+            .partition(|&&u| position[u] > position[v]);
+        search.extend(&mut vec![v], later, earlier);
+    }
+
+    search
+        .best
+        .into_iter()
+        .map(|v| indexed.nodes[v].clone())
+        .collect()
+}
+
+// Every clique of exactly k nodes that has at least one node the filter accepts,
+// e.g. computers whose name starts with 't'. Each clique is listed once.
+pub fn cliques_of_size<N: Hash + Eq + Clone>(
+    graph: &Graph<N>,
+    k: usize,
+    filter: impl Fn(&N) -> bool,
+) -> Vec<Vec<N>> {
+    let indexed = Indexed::new(graph);
+    let accepted: Vec<bool> = indexed.nodes.iter().map(|node| filter(node)).collect();
+
+    // grows cliques in increasing index order, so each one is found exactly once
+    fn extend(
+        adjacency: &[HashSet<usize>],
+        k: usize,
+        clique: &mut Vec<usize>,
+        candidates: &[usize],
+        found: &mut Vec<Vec<usize>>,
+    ) {
+        if clique.len() == k {
+            found.push(clique.clone());
+            return;
+        }
+        for (i, &v) in candidates.iter().enumerate() {
+            if clique.len() + candidates.len() - i < k {
+                break;
+            }
+            let next: Vec<usize> = candidates[i + 1..]
+                .iter()
+                .copied()
+                .filter(|u| adjacency[v].contains(u))
+                .collect();
+            clique.push(v);
+            extend(adjacency, k, clique, &next, found);
+            clique.pop();
+        }
+    }
+
+    let mut found = Vec::new();
+    if k > 0 {
+        let all: Vec<usize> = (0..indexed.nodes.len()).collect();
+        extend(&indexed.adjacency, k, &mut Vec::new(), &all, &mut found);
+    }
+    found
+        .into_iter()
+        .filter(|clique| clique.iter().any(|&v| accepted[v]))
+        .map(|clique| {
+            clique
+                .into_iter()
+                .map(|v| indexed.nodes[v].clone())
+                .collect()
+        })
+        .collect()
+}
+
+// Cliques of size 3 with at least one node the filter accepts, found edge by edge
+pub fn triangles<N: Hash + Eq + Clone>(
+    graph: &Graph<N>,
+    filter: impl Fn(&N) -> bool,
+) -> Vec<[N; 3]> {
+    let indexed = Indexed::new(graph);
+    let node = |v: usize| indexed.nodes[v].clone();
+    let mut found = Vec::new();
+    for (a, neighbors) in indexed.adjacency.iter().enumerate() {
+        for &b in neighbors.iter().filter(|&&b| b > a) {
+            for &c in neighbors.intersection(&indexed.adjacency[b]) {
+                if c > b && [a, b, c].iter().any(|&v| filter(indexed.nodes[v])) {
+                    found.push([node(a), node(b), node(c)]);
+                }
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A small linear congruential generator, so the random graphs are reproducible
+    fn random_graph(nodes: u32, density_percent: u64, seed: u64) -> Graph<u32> {
+        let mut state = seed;
+        let mut graph = Graph::new();
+        for a in 0..nodes {
+            graph.add_node(a);
+            for b in 0..a {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                if (state >> 33) % 100 < density_percent {
+                    graph.add_edge(a, b);
+                }
+            }
+        }
+        graph
+    }
+
+    fn is_clique(graph: &Graph<u32>, nodes: &HashSet<u32>) -> bool {
+        nodes
+            .iter()
+            .all(|a| nodes.iter().all(|b| a == b || graph.contains_edge(a, b)))
+    }
+
+    fn brute_force_clique_size(graph: &Graph<u32>, nodes: u32) -> usize {
+        (0u32..1 << nodes)
+            .map(|subset| -> HashSet<u32> { (0..nodes).filter(|v| subset >> v & 1 == 1).collect() })
+            .filter(|subset| is_clique(graph, subset))
+            .map(|subset| subset.len())
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_random_graphs() {
+        for seed in 0..30 {
+            let nodes = 4 + (seed as u32 % 9);
+            let graph = random_graph(nodes, 20 + seed % 4 * 20, seed);
+            let clique = max_clique(&graph);
+            assert!(is_clique(&graph, &clique), "seed {}", seed);
+            assert_eq!(
+                clique.len(),
+                brute_force_clique_size(&graph, nodes),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_cliques_of_size() {
+        for seed in 0..10 {
+            let graph = random_graph(10, 50, seed);
+            let mut triangles: Vec<Vec<u32>> = triangles(&graph, |_| true)
+                .into_iter()
+                .map(|triangle| triangle.to_vec())
+                .collect();
+            let mut cliques = cliques_of_size(&graph, 3, |_| true);
+            for clique in triangles.iter_mut().chain(cliques.iter_mut()) {
+                clique.sort();
+            }
+            triangles.sort();
+            cliques.sort();
+            assert_eq!(triangles, cliques, "seed {}", seed);
+
+            let largest = max_clique(&graph).len();
+            assert!(!cliques_of_size(&graph, largest, |_| true).is_empty());
+            assert!(cliques_of_size(&graph, largest + 1, |_| true).is_empty());
+        }
+
+        // K4: four triangles, one of them without node 0
+        let k4: Graph<u32> = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]
+            .into_iter()
+            .collect();
+        assert_eq!(cliques_of_size(&k4, 3, |_| true).len(), 4);
+        assert_eq!(triangles(&k4, |&v| v == 0).len(), 3);
+        assert_eq!(cliques_of_size(&k4, 2, |&v| v == 0).len(), 3);
+        assert_eq!(cliques_of_size(&k4, 4, |_| true).len(), 1);
+    }
+
+    #[test]
+    fn test_small_graphs() {
+        assert!(max_clique(&Graph::<u32>::new()).is_empty());
+
+        let mut single = Graph::new();
+        single.add_node('a');
+        assert_eq!(max_clique(&single), HashSet::from(['a']));
+
+        // two triangles sharing an edge, plus a tail
+        let graph: Graph<char> = [
+            ('a', 'b'),
+            ('b', 'c'),
+            ('a', 'c'),
+            ('b', 'd'),
+            ('c', 'd'),
+            ('d', 'e'),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(max_clique(&graph).len(), 3);
+    }
+}
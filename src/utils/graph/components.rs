@@ -0,0 +1,200 @@
+use crate::utils::graph::Graph;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use num::Zero;
+
+// Union-find over 0..n with path halving and union by size
+#[derive(Debug, Clone)]
+pub struct DisjointSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSets {
+    pub fn new(n: usize) -> Self {
+        DisjointSets {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    // Returns false if a and b were already in the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+}
+
+// The connected components, each as a set of nodes
+pub fn components<N: Hash + Eq + Clone>(graph: &Graph<N>) -> Vec<HashSet<N>> {
+    let mut seen: HashSet<&N> = HashSet::new();
+    let mut components = Vec::new();
+    for start in graph.nodes() {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = HashSet::from([start.clone()]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for next in graph.neighbors(node) {
+                if seen.insert(next) {
+                    component.insert(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+// True for the empty graph as well
+pub fn is_connected<N: Hash + Eq + Clone>(graph: &Graph<N>) -> bool {
+    components(graph).len() <= 1
+}
+
+// Kruskal's algorithm: the total weight and edges of a minimum spanning tree,
+// or of a spanning forest with one tree per component if the graph is not connected
+pub fn minimum_spanning_tree<N, W>(
+    graph: &Graph<N>,
+    weight: impl Fn(&N, &N) -> W,
+) -> (W, Vec<(N, N)>)
+where
+    N: Hash + Eq + Clone,
+    W: Ord + Copy + Zero + Add<Output = W>,
+{
+    let nodes: Vec<&N> = graph.nodes().collect();
+    let index: HashMap<&N, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let mut edges: Vec<(W, usize, usize)> = nodes
+        .iter()
+        .enumerate()
+        .flat_map(|(a, &node)| {
+            graph
+                .neighbors(node)
+                .map(|next| index[next])
+                .filter(move |&b| a < b)
+                .map(|b| (weight(nodes[a], nodes[b]), a, b))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    edges.sort_by_key(|&(w, _, _)| w);
+
+    let mut sets = DisjointSets::new(nodes.len());
+    let mut total = W::zero();
+    let mut tree = Vec::new();
+    for (w, a, b) in edges {
+        if sets.union(a, b) {
+            total = total + w;
+            tree.push((nodes[a].clone(), nodes[b].clone()));
+        }
+    }
+    (total, tree)
+}
+
+// Prim's algorithm: a minimum spanning tree of the component containing start,
+// grown from start. Gives the same total weight as Kruskal on a connected graph.
+pub fn prim<N, W>(graph: &Graph<N>, start: &N, weight: impl Fn(&N, &N) -> W) -> (W, Vec<(N, N)>)
+where
+    N: Hash + Eq + Clone,
+    W: Ord + Copy + Zero + Add<Output = W>,
+{
+    let mut total = W::zero();
+    let mut tree = Vec::new();
+    if !graph.contains_node(start) {
+        return (total, tree);
+    }
+    let nodes: Vec<&N> = graph.nodes().collect();
+    let index: HashMap<&N, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+    let mut in_tree = vec![false; nodes.len()];
+    let mut frontier: BinaryHeap<(Reverse<W>, usize, usize)> = BinaryHeap::new();
+    let add = |i: usize, in_tree: &mut Vec<bool>, frontier: &mut BinaryHeap<_>| {
+        in_tree[i] = true;
+        for next in graph.neighbors(nodes[i]) {
+            let j = index[next];
+            if !in_tree[j] {
+                frontier.push((Reverse(weight(nodes[i], next)), i, j));
+            }
+        }
+    };
+    add(index[start], &mut in_tree, &mut frontier);
+    while let Some((Reverse(w), i, j)) = frontier.pop() {
+        if in_tree[j] {
+            continue;
+        }
+        total = total + w;
+        tree.push((nodes[i].clone(), nodes[j].clone()));
+        add(j, &mut in_tree, &mut frontier);
+    }
+    (total, tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components() {
+        let mut graph: Graph<u32> = [(1, 2), (2, 3), (4, 5)].into_iter().collect();
+        graph.add_node(6);
+        let mut sizes: Vec<usize> = components(&graph).iter().map(HashSet::len).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 2, 3]);
+        assert!(!is_connected(&graph));
+
+        graph.add_edge(3, 4);
+        graph.add_edge(5, 6);
+        assert!(is_connected(&graph));
+        assert!(is_connected(&Graph::<u32>::new()));
+    }
+
+    #[test]
+    fn test_minimum_spanning_tree() {
+        // a square with one expensive side and an expensive diagonal
+        let weights = HashMap::from([
+            ((1, 2), 1),
+            ((2, 3), 2),
+            ((3, 4), 3),
+            ((1, 4), 10),
+            ((1, 3), 5),
+        ]);
+        let graph: Graph<u32> = weights.keys().copied().collect();
+        let weight = |a: &u32, b: &u32| weights[&(*a.min(b), *a.max(b))];
+        let (total, tree) = minimum_spanning_tree(&graph, weight);
+        assert_eq!(total, 6);
+        assert_eq!(tree.len(), 3);
+        let (prim_total, prim_tree) = prim(&graph, &3, weight);
+        assert_eq!((prim_total, prim_tree.len()), (6, 3));
+
+        // two components give a forest
+        let mut forest = graph.clone();
+        forest.add_edge(7, 8);
+        let (total, tree) =
+            minimum_spanning_tree(&forest, |a, b| if *a >= 7 { 4 } else { weight(a, b) });
+        assert_eq!(total, 10);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(prim(&forest, &7, |_, _| 4), (4, vec![(7, 8)]));
+        assert_eq!(prim(&forest, &9, |_, _| 4), (0, vec![]));
+    }
+}
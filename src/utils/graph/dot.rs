@@ -0,0 +1,100 @@
+use crate::utils::graph::{DiGraph, Graph};
+use itertools::Itertools;
+use std::{collections::HashMap, hash::Hash};
+
+// Node ids are assigned in label order, so the output only depends on the graph
+fn dot<'a, N: Hash + Eq + 'a>(
+    kind: &str,
+    connector: &str,
+    nodes: impl Iterator<Item = &'a N>,
+    edges: impl Fn(&N) -> Vec<&'a N>,
+    labels: impl Fn(&N) -> String,
+    keep_edge: impl Fn(usize, usize) -> bool,
+) -> String {
+    let nodes: Vec<(String, &N)> = nodes
+        .map(|node| (labels(node), node))
+        .sorted_by(|a, b| a.0.cmp(&b.0))
+        .collect();
+    let ids: HashMap<&N, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, node))| (node, i))
+        .collect();
+    let id = |node: &N| ids[node];
+
+    let mut lines = vec![format!("{} {{", kind)];
+    for (i, (label, _)) in nodes.iter().enumerate() {
+        lines.push(format!("    n{} [label={:?}];", i, label));
+    }
+    for (i, (_, node)) in nodes.iter().enumerate() {
+        for j in edges(node).into_iter().map(id).sorted() {
+            if keep_edge(i, j) {
+                lines.push(format!("    n{} {} n{};", i, connector, j));
+            }
+        }
+    }
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+// Graphviz DOT for an undirected graph, e.g. for `dot -Tsvg`; labels should be unique
+pub fn to_dot<N: Hash + Eq>(graph: &Graph<N>, labels: impl Fn(&N) -> String) -> String {
+    // each edge is stored at both ends, but drawn once
+    dot(
+        "graph",
+        "--",
+        graph.nodes(),
+        |node| graph.neighbors(node).collect(),
+        labels,
+        |i, j| i <= j,
+    )
+}
+
+pub fn digraph_to_dot<N: Hash + Eq>(graph: &DiGraph<N>, labels: impl Fn(&N) -> String) -> String {
+    dot(
+        "digraph",
+        "->",
+        graph.nodes(),
+        |node| graph.successors(node).collect(),
+        labels,
+        |_, _| true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_dot() {
+        let graph: Graph<&str> = [("kh", "tc"), ("qp", "kh")].into_iter().collect();
+        assert_eq!(
+            to_dot(&graph, |node| node.to_string()),
+            [
+                "graph {",
+                "    n0 [label=\"kh\"];",
+                "    n1 [label=\"qp\"];",
+                "    n2 [label=\"tc\"];",
+                "    n0 -- n1;",
+                "    n0 -- n2;",
+                "}",
+            ]
+            .join("\n")
+        );
+
+        let digraph: DiGraph<u8> = [(2, 1), (1, 3)].into_iter().collect();
+        assert_eq!(
+            digraph_to_dot(&digraph, |node| format!("node {}", node)),
+            [
+                "digraph {",
+                "    n0 [label=\"node 1\"];",
+                "    n1 [label=\"node 2\"];",
+                "    n2 [label=\"node 3\"];",
+                "    n0 -> n2;",
+                "    n1 -> n0;",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+}
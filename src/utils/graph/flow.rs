@@ -0,0 +1,284 @@
+use num::Zero;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::{Add, Sub},
+};
+
+// Capacities and flows; integers in practice
+pub trait Capacity: Ord + Copy + Zero + Add<Output = Self> + Sub<Output = Self> {}
+impl<W: Ord + Copy + Zero + Add<Output = W> + Sub<Output = W>> Capacity for W {}
+
+#[derive(Debug, Clone)]
+struct Edge<W> {
+    from: usize,
+    to: usize,
+    capacity: W,
+}
+
+// A network of capacitated edges. Every edge is stored next to its reverse,
+// so edge i ^ 1 is the one that flow along edge i can be pushed back through.
+#[derive(Debug, Clone)]
+pub struct FlowNetwork<N: Hash + Eq, W> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    edges: Vec<Edge<W>>,
+    adjacent: Vec<Vec<usize>>,
+}
+
+impl<N: Hash + Eq + Clone, W: Capacity> Default for FlowNetwork<N, W> {
+    fn default() -> Self {
+        FlowNetwork {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+            adjacent: Vec::new(),
+        }
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Capacity> FlowNetwork<N, W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn node(&mut self, node: N) -> usize {
+        if let Some(&i) = self.index.get(&node) {
+            return i;
+        }
+        self.nodes.push(node.clone());
+        self.adjacent.push(Vec::new());
+        self.index.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn push_pair(&mut self, from: N, to: N, capacity: W, back_capacity: W) {
+        let (from, to) = (self.node(from), self.node(to));
+        self.adjacent[from].push(self.edges.len());
+        self.edges.push(Edge { from, to, capacity });
+        self.adjacent[to].push(self.edges.len());
+        self.edges.push(Edge {
+            from: to,
+            to: from,
+            capacity: back_capacity,
+        });
+    }
+
+    // Parallel edges are allowed and add up
+    pub fn add_edge(&mut self, from: N, to: N, capacity: W) {
+        self.push_pair(from, to, capacity, W::zero());
+    }
+
+    // An edge that can carry the capacity either way, as for a cut in an undirected graph
+    pub fn add_undirected_edge(&mut self, a: N, b: N, capacity: W) {
+        self.push_pair(a, b, capacity, capacity);
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Edmonds-Karp: repeatedly augments along a shortest path in the residual network.
+    // None if the source or sink is not in the network, or both are the same node.
+    pub fn max_flow(&self, source: &N, sink: &N) -> Option<MaxFlow<'_, N, W>> {
+        let source = *self.index.get(source)?;
+        let sink = *self.index.get(sink)?;
+        if source == sink {
+            return None;
+        }
+
+        let mut residual: Vec<W> = self.edges.iter().map(|edge| edge.capacity).collect();
+        let mut value = W::zero();
+        loop {
+            let parent_edge = self.residual_bfs(&residual, source);
+            if parent_edge[sink].is_none() {
+                break;
+            }
+            let mut path = Vec::new();
+            let mut node = sink;
+            while let Some(e) = parent_edge[node] {
+                path.push(e);
+                node = self.edges[e].from;
+            }
+            let bottleneck = path
+                .iter()
+                .map(|&e| residual[e])
+                .min()
+                .expect("Source and sink differ, so the path has an edge.");
+            for e in path {
+                residual[e] = residual[e] - bottleneck;
+                residual[e ^ 1] = residual[e ^ 1] + bottleneck;
+            }
+            value = value + bottleneck;
+        }
+
+        Some(MaxFlow {
+            network: self,
+            source,
+            residual,
+            value,
+        })
+    }
+
+    // For each node reached from source through edges with spare capacity,
+    // the edge it was reached by (None for source and for unreached nodes)
+    fn residual_bfs(&self, residual: &[W], source: usize) -> Vec<Option<usize>> {
+        let mut parent_edge = vec![None; self.nodes.len()];
+        let mut reached = vec![false; self.nodes.len()];
+        reached[source] = true;
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for &e in &self.adjacent[node] {
+                let to = self.edges[e].to;
+                if !reached[to] && residual[e] > W::zero() {
+                    reached[to] = true;
+                    parent_edge[to] = Some(e);
+                    queue.push_back(to);
+                }
+            }
+        }
+        parent_edge
+    }
+}
+
+#[derive(Debug)]
+pub struct MaxFlow<'a, N: Hash + Eq, W> {
+    network: &'a FlowNetwork<N, W>,
+    source: usize,
+    residual: Vec<W>,
+    pub value: W,
+}
+
+// The cheapest set of edges separating source from sink; its capacity equals the max flow
+#[derive(Debug, Clone)]
+pub struct MinCut<N> {
+    pub source_side: HashSet<N>,
+    pub edges: Vec<(N, N)>,
+}
+
+impl<N: Hash + Eq + Clone, W: Capacity> MaxFlow<'_, N, W> {
+    // Net flow from one node to another, summed over parallel edges
+    pub fn flow(&self, from: &N, to: &N) -> W {
+        let network = self.network;
+        let (Some(&from), Some(&to)) = (network.index.get(from), network.index.get(to)) else {
+            return W::zero();
+        };
+        let mut sent = W::zero();
+        let mut returned = W::zero();
+        for (e, edge) in network.edges.iter().enumerate().step_by(2) {
+            // an edge pair carries flow forward if e has lost capacity, backward if it gained
+            let residual = self.residual[e];
+            let (forward, backward) = if residual <= edge.capacity {
+                (edge.capacity - residual, W::zero())
+            } else {
+                (W::zero(), residual - edge.capacity)
+            };
+            if (edge.from, edge.to) == (from, to) {
+                sent = sent + forward;
+                returned = returned + backward;
+            } else if (edge.from, edge.to) == (to, from) {
+                sent = sent + backward;
+                returned = returned + forward;
+            }
+        }
+        if sent >= returned {
+            sent - returned
+        } else {
+            W::zero()
+        }
+    }
+
+    pub fn min_cut(&self) -> MinCut<N> {
+        let network = self.network;
+        let parent_edge = network.residual_bfs(&self.residual, self.source);
+        let on_source_side = |node: usize| node == self.source || parent_edge[node].is_some();
+
+        let source_side = (0..network.nodes.len())
+            .filter(|&node| on_source_side(node))
+            .map(|node| network.nodes[node].clone())
+            .collect();
+        let edges = network
+            .edges
+            .iter()
+            .filter(|edge| {
+                edge.capacity > W::zero() && on_source_side(edge.from) && !on_source_side(edge.to)
+            })
+            .map(|edge| {
+                (
+                    network.nodes[edge.from].clone(),
+                    network.nodes[edge.to].clone(),
+                )
+            })
+            .collect();
+        MinCut { source_side, edges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_flow() {
+        // the textbook example with a maximum flow of 23
+        let mut network = FlowNetwork::new();
+        for (from, to, capacity) in [
+            ("s", "v1", 16),
+            ("s", "v2", 13),
+            ("v1", "v3", 12),
+            ("v2", "v1", 4),
+            ("v2", "v4", 14),
+            ("v3", "v2", 9),
+            ("v3", "t", 20),
+            ("v4", "v3", 7),
+            ("v4", "t", 4),
+        ] {
+            network.add_edge(from, to, capacity);
+        }
+        let flow = network.max_flow(&"s", &"t").unwrap();
+        assert_eq!(flow.value, 23);
+        assert_eq!(flow.flow(&"s", &"v1") + flow.flow(&"s", &"v2"), 23);
+        assert_eq!(flow.flow(&"v3", &"t") + flow.flow(&"v4", &"t"), 23);
+        assert_eq!(flow.flow(&"t", &"v3"), 0);
+
+        let cut = flow.min_cut();
+        assert_eq!(cut.source_side, HashSet::from(["s", "v1", "v2", "v4"]));
+        let mut edges = cut.edges.clone();
+        edges.sort();
+        assert_eq!(edges, vec![("v1", "v3"), ("v4", "t"), ("v4", "v3")]);
+
+        // sink unreachable
+        network.add_edge("t", "u", 5);
+        assert_eq!(network.max_flow(&"u", &"s").unwrap().value, 0);
+        // not in the network
+        assert!(network.max_flow(&"s", &"w").is_none());
+        assert!(network.max_flow(&"w", &"t").is_none());
+        assert!(network.max_flow(&"s", &"s").is_none());
+    }
+
+    #[test]
+    fn test_undirected_min_cut() {
+        // two 5-cliques joined by three bridges: cutting the bridges splits them
+        let mut network = FlowNetwork::new();
+        for group in [0, 10] {
+            for a in group..group + 5 {
+                for b in a + 1..group + 5 {
+                    network.add_undirected_edge(a, b, 1u32);
+                }
+            }
+        }
+        for (a, b) in [(0, 10), (1, 11), (2, 12)] {
+            network.add_undirected_edge(a, b, 1);
+        }
+        assert_eq!(network.node_count(), 10);
+
+        let flow = network.max_flow(&4, &14).unwrap();
+        assert_eq!(flow.value, 3);
+        assert_eq!(flow.flow(&0, &10) + flow.flow(&10, &0), 1);
+        let cut = flow.min_cut();
+        assert_eq!(cut.source_side, HashSet::from([0, 1, 2, 3, 4]));
+        let mut edges = cut.edges.clone();
+        edges.sort();
+        assert_eq!(edges, vec![(0, 10), (1, 11), (2, 12)]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, hash::Hash};
+
+struct HopcroftKarp {
+    adjacent: Vec<Vec<usize>>,
+    left_match: Vec<Option<usize>>,
+    right_match: Vec<Option<usize>>,
+    layer: Vec<usize>,
+}
+
+impl HopcroftKarp {
+    const UNREACHED: usize = usize::MAX;
+
+    // Layers the free left nodes and everything alternating paths reach from them;
+    // true if some path ends at a free right node
+    fn layer(&mut self) -> bool {
+        let mut queue = Vec::new();
+        for (u, matched) in self.left_match.iter().enumerate() {
+            if matched.is_none() {
+                self.layer[u] = 0;
+                queue.push(u);
+            } else {
+                self.layer[u] = Self::UNREACHED;
+            }
+        }
+        let mut found = false;
+        let mut head = 0;
+        while let Some(&u) = queue.get(head) {
+            head += 1;
+            for &v in &self.adjacent[u] {
+                match self.right_match[v] {
+                    None => found = true,
+                    Some(next) if self.layer[next] == Self::UNREACHED => {
+                        self.layer[next] = self.layer[u] + 1;
+                        queue.push(next);
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+        found
+    }
+
+    // Augments along a shortest alternating path from u, following the layers
+    fn augment(&mut self, u: usize) -> bool {
+        for i in 0..self.adjacent[u].len() {
+            let v = self.adjacent[u][i];
+            let extends = match self.right_match[v] {
+                None => true,
+                Some(next) => self.layer[next] == self.layer[u] + 1 && self.augment(next),
+            };
+            if extends {
+                self.left_match[u] = Some(v);
+                self.right_match[v] = Some(u);
+                return true;
+            }
+        }
+        self.layer[u] = Self::UNREACHED;
+        false
+    }
+}
+
+// Hopcroft-Karp: a largest set of (left, right) pairs such that every pair is adjacent
+// and no node is used twice. `adjacent` gives the right nodes a left node may pair with.
+pub fn max_bipartite_matching<L, R, I>(
+    left: impl IntoIterator<Item = L>,
+    adjacent: impl Fn(&L) -> I,
+) -> Vec<(L, R)>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+    I: IntoIterator<Item = R>,
+{
+    let mut left_nodes: Vec<L> = Vec::new();
+    let mut left_index: HashMap<L, usize> = HashMap::new();
+    for node in left {
+        left_index.entry(node.clone()).or_insert_with(|| {
+            left_nodes.push(node);
+            left_nodes.len() - 1
+        });
+    }
+
+    let mut right_nodes: Vec<R> = Vec::new();
+    let mut right_index: HashMap<R, usize> = HashMap::new();
+    let adjacent: Vec<Vec<usize>> = left_nodes
+        .iter()
+        .map(|node| {
+            adjacent(node)
+                .into_iter()
+                .map(|right| {
+                    *right_index.entry(right.clone()).or_insert_with(|| {
+                        right_nodes.push(right);
+                        right_nodes.len() - 1
+                    })
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut search = HopcroftKarp {
+        adjacent,
+        left_match: vec![None; left_nodes.len()],
+        right_match: vec![None; right_nodes.len()],
+        layer: vec![0; left_nodes.len()],
+    };
+    while search.layer() {
+        for u in 0..left_nodes.len() {
+            if search.left_match[u].is_none() {
+                search.augment(u);
+            }
+        }
+    }
+
+    search
+        .left_match
+        .iter()
+        .enumerate()
+        .filter_map(|(u, v)| v.map(|v| (left_nodes[u].clone(), right_nodes[v].clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use std::collections::HashSet;
+
+    fn is_matching<L: Hash + Eq, R: Hash + Eq>(pairs: &[(L, R)]) -> bool {
+        pairs.iter().map(|(l, _)| l).all_unique() && pairs.iter().map(|(_, r)| r).all_unique()
+    }
+
+    #[test]
+    fn test_matching() {
+        // greedy pairing 1-a first would leave 2 unmatched
+        let edges = HashMap::from([(1, vec!['a', 'b']), (2, vec!['a']), (3, vec!['b', 'c'])]);
+        let matching = max_bipartite_matching(1..=3, |l| edges[l].clone());
+        assert_eq!(matching.len(), 3);
+        assert!(is_matching(&matching));
+        assert!(matching.contains(&(2, 'a')));
+
+        let matching = max_bipartite_matching(0..4, |_| vec!["only"]);
+        assert_eq!(matching.len(), 1);
+
+        let matching: Vec<(u32, u32)> = max_bipartite_matching(0..4, |_| vec![]);
+        assert!(matching.is_empty());
+    }
+
+    #[test]
+    fn test_matching_brute_force() {
+        // small random graphs, checked against trying every subset of edges
+        let mut seed: u64 = 7;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed >> 33
+        };
+        for _ in 0..50 {
+            let edges: HashSet<(u64, u64)> = (0..8).map(|_| (next() % 5, next() % 5)).collect();
+            let edges: Vec<(u64, u64)> = edges.into_iter().collect();
+            let best = (0..1u32 << edges.len())
+                .map(|mask| {
+                    (0..edges.len())
+                        .filter(|i| mask & (1 << i) != 0)
+                        .map(|i| edges[i])
+                        .collect::<Vec<_>>()
+                })
+                .filter(|pairs| is_matching(pairs))
+                .map(|pairs| pairs.len())
+                .max()
+                .unwrap();
+
+            let matching = max_bipartite_matching(0..5, |&l| {
+                edges.iter().filter(move |(a, _)| *a == l).map(|&(_, r)| r)
+            });
+            assert!(is_matching(&matching));
+            assert!(matching.iter().all(|pair| edges.contains(pair)));
+            assert_eq!(matching.len(), best);
+        }
+    }
+}
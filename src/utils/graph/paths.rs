@@ -0,0 +1,324 @@
+use crate::utils::graph::Adjacency;
+use num::Zero;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+// Walks parent links back from goal and returns the path in forward order
+fn reconstruct<N: Hash + Eq + Clone>(parents: &HashMap<N, N>, goal: &N) -> Vec<N> {
+    let mut path = vec![goal.clone()];
+    while let Some(parent) = parents.get(path.last().expect("Path is never empty.")) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+// Breadth-first search from start, recording every reached node's distance and parent
+fn bfs_tree<N: Hash + Eq + Clone>(
+    graph: &impl Adjacency<N>,
+    start: &N,
+    goal: Option<&N>,
+) -> (HashMap<N, usize>, HashMap<N, N>) {
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(node) = queue.pop_front() {
+        if Some(&node) == goal {
+            break;
+        }
+        let distance = distances[&node];
+        for next in graph.adjacent(&node) {
+            if !distances.contains_key(next) {
+                distances.insert(next.clone(), distance + 1);
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next.clone());
+            }
+        }
+    }
+    (distances, parents)
+}
+
+// Number of edges to every node reachable from start
+pub fn bfs<N: Hash + Eq + Clone>(graph: &impl Adjacency<N>, start: &N) -> HashMap<N, usize> {
+    bfs_tree(graph, start, None).0
+}
+
+// A path with the fewest edges, including both ends
+pub fn shortest_path<N: Hash + Eq + Clone>(
+    graph: &impl Adjacency<N>,
+    start: &N,
+    goal: &N,
+) -> Option<Vec<N>> {
+    let (distances, parents) = bfs_tree(graph, start, Some(goal));
+    distances
+        .contains_key(goal)
+        .then(|| reconstruct(&parents, goal))
+}
+
+fn dijkstra_tree<N, W>(
+    graph: &impl Adjacency<N>,
+    start: &N,
+    goal: Option<&N>,
+    weight: impl Fn(&N, &N) -> W,
+) -> (HashMap<N, W>, HashMap<N, N>)
+where
+    N: Hash + Eq + Clone,
+    W: Ord + Copy + Zero + Add<Output = W>,
+{
+    // the heap holds indices into `seen`, so nodes need not be Ord
+    let mut seen: Vec<N> = vec![start.clone()];
+    let mut ids: HashMap<N, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut best: HashMap<N, W> = HashMap::from([(start.clone(), W::zero())]);
+    let mut parents = HashMap::new();
+    let mut done = vec![false];
+    let mut heap = BinaryHeap::from([Reverse((W::zero(), 0))]);
+
+    while let Some(Reverse((cost, id))) = heap.pop() {
+        if done[id] {
+            continue;
+        }
+        done[id] = true;
+        let node = seen[id].clone();
+        if Some(&node) == goal {
+            break;
+        }
+        for next in graph.adjacent(&node) {
+            let next_cost = cost + weight(&node, next);
+            if best.get(next).is_some_and(|&known| known <= next_cost) {
+                continue;
+            }
+            let next_id = *ids.entry(next.clone()).or_insert_with(|| {
+                seen.push(next.clone());
+                done.push(false);
+                seen.len() - 1
+            });
+            best.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), node.clone());
+            heap.push(Reverse((next_cost, next_id)));
+        }
+    }
+    (best, parents)
+}
+
+// Cheapest cost to every node reachable from start; weights must not be negative
+pub fn dijkstra<N, W>(
+    graph: &impl Adjacency<N>,
+    start: &N,
+    weight: impl Fn(&N, &N) -> W,
+) -> HashMap<N, W>
+where
+    N: Hash + Eq + Clone,
+    W: Ord + Copy + Zero + Add<Output = W>,
+{
+    dijkstra_tree(graph, start, None, weight).0
+}
+
+// The cost and nodes of a cheapest path from start to goal; weights must not be negative
+pub fn dijkstra_path<N, W>(
+    graph: &impl Adjacency<N>,
+    start: &N,
+    goal: &N,
+    weight: impl Fn(&N, &N) -> W,
+) -> Option<(W, Vec<N>)>
+where
+    N: Hash + Eq + Clone,
+    W: Ord + Copy + Zero + Add<Output = W>,
+{
+    let (best, parents) = dijkstra_tree(graph, start, Some(goal), weight);
+    best.get(goal)
+        .map(|&cost| (cost, reconstruct(&parents, goal)))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NegativeCycle;
+
+// Cheapest cost to every node reachable from start, allowing negative weights.
+// Fails if a negative cycle is reachable, as costs through it are unbounded.
+pub fn bellman_ford<N, W>(
+    graph: &impl Adjacency<N>,
+    start: &N,
+    weight: impl Fn(&N, &N) -> W,
+) -> Result<HashMap<N, W>, NegativeCycle>
+where
+    N: Hash + Eq + Clone,
+    W: Ord + Copy + Zero + Add<Output = W>,
+{
+    let node_count = graph.nodes().count();
+    let mut best: HashMap<N, W> = HashMap::from([(start.clone(), W::zero())]);
+    // one more round than needed: any improvement in it means a negative cycle
+    for _ in 0..node_count.max(1) {
+        let mut improved = false;
+        for node in graph.nodes() {
+            let Some(&cost) = best.get(node) else {
+                continue;
+            };
+            for next in graph.adjacent(node) {
+                let next_cost = cost + weight(node, next);
+                if best.get(next).is_none_or(|&known| next_cost < known) {
+                    best.insert(next.clone(), next_cost);
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            return Ok(best);
+        }
+    }
+    Err(NegativeCycle)
+}
+
+// Number of distinct paths from start to a goal in an implicit DAG, e.g. trails climbing
+// one height step at a time, or the ways to split a design into known towels.
+// Each node's count is computed once and reused by every path through it; paths stop at
+// the first goal they reach. Panics if a cycle is reachable, as the count would be infinite.
+pub fn count_paths_by<N, I>(
+    start: &N,
+    is_goal: impl Fn(&N) -> bool,
+    successors: impl Fn(&N) -> I,
+) -> usize
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut counts: HashMap<N, usize> = HashMap::new();
+    let mut in_progress: HashSet<N> = HashSet::new();
+    // (node, its successors, how many of them are counted)
+    let mut stack: Vec<(N, Vec<N>, usize)> = Vec::new();
+
+    let mut visit =
+        |node: &N, stack: &mut Vec<(N, Vec<N>, usize)>, counts: &mut HashMap<N, usize>| {
+            if is_goal(node) {
+                counts.insert(node.clone(), 1);
+            } else {
+                assert!(
+                    in_progress.insert(node.clone()),
+                    "Paths can only be counted in an acyclic graph."
+                );
+                stack.push((node.clone(), successors(node).into_iter().collect(), 0));
+            }
+        };
+    visit(start, &mut stack, &mut counts);
+
+    while let Some((node, next_nodes, counted)) = stack.last_mut() {
+        if let Some(next) = next_nodes.get(*counted) {
+            *counted += 1;
+            if !counts.contains_key(next) {
+                let next = next.clone();
+                visit(&next, &mut stack, &mut counts);
+            }
+            continue;
+        }
+        let total = next_nodes.iter().map(|next| counts[next]).sum();
+        counts.insert(node.clone(), total);
+        stack.pop();
+    }
+    counts[start]
+}
+
+// Number of distinct paths from `from` to `to`; the graph must be acyclic where reachable
+pub fn count_paths<N: Hash + Eq + Clone>(graph: &impl Adjacency<N>, from: &N, to: &N) -> usize {
+    count_paths_by(
+        from,
+        |node| node == to,
+        |node| graph.adjacent(node).cloned().collect::<Vec<_>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::graph::{DiGraph, Graph};
+
+    #[test]
+    fn test_bfs() {
+        // a square a-b-c-d-a with a tail d-e
+        let graph: Graph<char> = [('a', 'b'), ('b', 'c'), ('c', 'd'), ('d', 'a'), ('d', 'e')]
+            .into_iter()
+            .collect();
+        let distances = bfs(&graph, &'a');
+        assert_eq!(distances[&'c'], 2);
+        assert_eq!(distances[&'e'], 2);
+        assert_eq!(shortest_path(&graph, &'a', &'e'), Some(vec!['a', 'd', 'e']));
+        assert_eq!(shortest_path(&graph, &'a', &'z'), None);
+
+        let one_way: DiGraph<char> = [('a', 'b')].into_iter().collect();
+        assert_eq!(shortest_path(&one_way, &'b', &'a'), None);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let weights = HashMap::from([
+            (('s', 'a'), 4),
+            (('s', 'b'), 1),
+            (('b', 'a'), 2),
+            (('a', 't'), 1),
+            (('b', 't'), 5),
+        ]);
+        let graph: DiGraph<char> = weights.keys().copied().collect();
+        let weight = |a: &char, b: &char| weights[&(*a, *b)];
+
+        assert_eq!(dijkstra(&graph, &'s', weight)[&'t'], 4);
+        assert_eq!(
+            dijkstra_path(&graph, &'s', &'t', weight),
+            Some((4, vec!['s', 'b', 'a', 't']))
+        );
+        assert_eq!(bellman_ford(&graph, &'s', weight).unwrap()[&'t'], 4);
+
+        // a negative edge that Dijkstra would settle too early for
+        let negative = |a: &char, b: &char| {
+            if (*a, *b) == ('b', 't') {
+                -3
+            } else {
+                weight(a, b)
+            }
+        };
+        assert_eq!(bellman_ford(&graph, &'s', negative).unwrap()[&'t'], -2);
+
+        let mut cyclic = graph.clone();
+        cyclic.add_edge('t', 's');
+        let cycle_weight = |a: &char, b: &char| if *a == 't' { -10 } else { weight(a, b) };
+        assert_eq!(
+            bellman_ford(&cyclic, &'s', cycle_weight),
+            Err(NegativeCycle)
+        );
+    }
+
+    #[test]
+    fn test_count_paths() {
+        // a diamond chain: each of the 20 diamonds doubles the number of paths
+        let graph: DiGraph<u32> = (0..20)
+            .flat_map(|i| {
+                [
+                    (3 * i, 3 * i + 1),
+                    (3 * i, 3 * i + 2),
+                    (3 * i + 1, 3 * i + 3),
+                    (3 * i + 2, 3 * i + 3),
+                ]
+            })
+            .collect();
+        assert_eq!(count_paths(&graph, &0, &60), 1 << 20);
+        assert_eq!(count_paths(&graph, &60, &0), 0);
+        assert_eq!(count_paths(&graph, &5, &5), 1);
+
+        // compositions of 10 into parts 1 and 2: a Fibonacci number
+        assert_eq!(
+            count_paths_by(
+                &0,
+                |&n| n == 10,
+                |&n| [n + 1, n + 2].into_iter().filter(|&m| m <= 10)
+            ),
+            89
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "acyclic")]
+    fn test_count_paths_cycle() {
+        let graph: DiGraph<u32> = [(0, 1), (1, 0), (1, 2)].into_iter().collect();
+        count_paths(&graph, &0, &2);
+    }
+}
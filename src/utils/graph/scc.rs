@@ -0,0 +1,126 @@
+use crate::utils::graph::DiGraph;
+use std::{collections::HashMap, hash::Hash};
+
+// Tarjan's algorithm, with an explicit stack so long chains (e.g. ripple-carry adders)
+// cannot overflow the call stack. Components come out in reverse topological order:
+// nothing in a component points into a component listed after it.
+pub fn strongly_connected_components<N: Hash + Eq + Clone>(graph: &DiGraph<N>) -> Vec<Vec<N>> {
+    let nodes: Vec<&N> = graph.nodes().collect();
+    let index_of: HashMap<&N, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let successors: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|node| graph.successors(node).map(|next| index_of[next]).collect())
+        .collect();
+
+    let mut index: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut low_link = vec![0; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..nodes.len() {
+        if index[root].is_some() {
+            continue;
+        }
+        // (node, how many of its successors have been looked at)
+        let mut call_stack = vec![(root, 0)];
+        while let Some(&(v, next_child)) = call_stack.last() {
+            if next_child == 0 && index[v].is_none() {
+                index[v] = Some(next_index);
+                low_link[v] = next_index;
+                next_index += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+
+            if let Some(&w) = successors[v].get(next_child) {
+                call_stack.last_mut().expect("v is on the call stack.").1 += 1;
+                match index[w] {
+                    None => call_stack.push((w, 0)),
+                    Some(w_index) if on_stack[w] => low_link[v] = low_link[v].min(w_index),
+                    Some(_) => (),
+                }
+                continue;
+            }
+
+            // all successors done
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[v]);
+            }
+            if Some(low_link[v]) == index[v] {
+                let mut component = Vec::new();
+                loop {
+                    let w = stack.pop().expect("v is still on the stack.");
+                    on_stack[w] = false;
+                    component.push(nodes[w].clone());
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+// Components that contain a cycle: more than one node, or a single node with a self-loop
+pub fn cycles<N: Hash + Eq + Clone>(graph: &DiGraph<N>) -> Vec<Vec<N>> {
+    strongly_connected_components(graph)
+        .into_iter()
+        .filter(|component| match &component[..] {
+            [node] => graph.contains_edge(node, node),
+            _ => true,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut components: Vec<Vec<u32>>) -> Vec<Vec<u32>> {
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        components.sort();
+        components
+    }
+
+    #[test]
+    fn test_components() {
+        // 1 -> 2 -> 3 -> 1 is a cycle feeding into 4 <-> 5, then 6
+        let graph: DiGraph<u32> = [(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 4), (5, 6)]
+            .into_iter()
+            .collect();
+        let components = strongly_connected_components(&graph);
+        assert_eq!(
+            sorted(components.clone()),
+            vec![vec![1, 2, 3], vec![4, 5], vec![6]]
+        );
+
+        // reverse topological order
+        let position = |node: u32| {
+            components
+                .iter()
+                .position(|component| component.contains(&node))
+                .unwrap()
+        };
+        assert!(position(6) < position(4) && position(4) < position(1));
+
+        assert_eq!(sorted(cycles(&graph)), vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_acyclic_and_self_loops() {
+        let chain: DiGraph<u32> = (0..10_000).map(|i| (i, i + 1)).collect();
+        assert_eq!(strongly_connected_components(&chain).len(), 10_001);
+        assert!(cycles(&chain).is_empty());
+
+        let mut looped = chain;
+        looped.add_edge(7, 7);
+        assert_eq!(cycles(&looped), vec![vec![7]]);
+    }
+}
@@ -0,0 +1,419 @@
+use crate::utils::file_io::{HasCharConverter, Input};
+use crate::utils::parse::captures::captures_as;
+use itertools::Itertools;
+use std::{collections::HashMap, fmt::Display};
+
+// Something wrong with a puzzle input, found before any solving happens
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    // 1-based, None for problems with the input as a whole (e.g. a missing marker)
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Collects problems for a part of the input that starts at line index `offset`
+struct Linter<'a> {
+    lines: &'a [String],
+    offset: usize,
+    problems: &'a mut Vec<Problem>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, index: Option<usize>, message: String) {
+        self.problems.push(Problem {
+            line: index.map(|index| self.offset + index + 1),
+            message,
+        });
+    }
+
+    fn rectangular(&mut self) {
+        let Some(width) = self.lines.first().map(|line| line.chars().count()) else {
+            self.report(None, String::from("grid is empty"));
+            return;
+        };
+        for (index, line) in self.lines.iter().enumerate() {
+            let length = line.chars().count();
+            if length != width {
+                self.report(
+                    Some(index),
+                    format!("row has {} characters, expected {}", length, width),
+                );
+            }
+        }
+    }
+
+    fn chars(&mut self, check: impl Fn(char) -> Result<(), String>) {
+        for (index, line) in self.lines.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if let Err(reason) = check(c) {
+                    self.report(
+                        Some(index),
+                        format!(
+                            "invalid character {:?} at column {}: {}",
+                            c,
+                            column + 1,
+                            reason
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    // Exactly one of each marker, e.g. S and E
+    fn unique_markers(&mut self, markers: &[char]) {
+        for &marker in markers {
+            let found = self
+                .lines
+                .iter()
+                .enumerate()
+                .flat_map(|(index, line)| {
+                    line.chars()
+                        .filter(move |&c| c == marker)
+                        .map(move |_| index)
+                })
+                .collect_vec();
+            if found.len() != 1 {
+                self.report(
+                    found.get(1).copied(),
+                    format!("expected exactly one {:?}, found {}", marker, found.len()),
+                );
+            }
+        }
+    }
+
+    // Exactly one character out of the set, e.g. one guard facing any direction
+    fn unique_among(&mut self, markers: &str) {
+        let count = self
+            .lines
+            .iter()
+            .map(|line| line.chars().filter(|&c| markers.contains(c)).count())
+            .sum::<usize>();
+        if count != 1 {
+            self.report(
+                None,
+                format!("expected exactly one of {:?}, found {}", markers, count),
+            );
+        }
+    }
+}
+
+fn one_of(allowed: &'static str) -> impl Fn(char) -> Result<(), String> {
+    move |c| {
+        if allowed.contains(c) {
+            Ok(())
+        } else {
+            Err(format!("expected one of {:?}", allowed))
+        }
+    }
+}
+
+fn converts<T: HasCharConverter>(c: char) -> Result<(), String> {
+    T::convert(c).map(|_| ())
+}
+
+// A run of lines with the index of its first line
+type Section<'a> = (usize, &'a [String]);
+
+// The input split at blank lines
+fn sections(lines: &[String]) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            sections.push((start, &lines[start..index]));
+            start = index + 1;
+        }
+    }
+    sections.push((start, &lines[start..]));
+    sections
+}
+
+fn two_sections<'a>(
+    lines: &'a [String],
+    problems: &mut Vec<Problem>,
+) -> Option<(Section<'a>, Section<'a>)> {
+    let found = sections(lines);
+    if found.len() != 2 {
+        problems.push(Problem {
+            line: None,
+            message: format!(
+                "expected two sections separated by a blank line, found {}",
+                found.len()
+            ),
+        });
+        return None;
+    }
+    Some((found[0], found[1]))
+}
+
+fn grid(lines: &[String], problems: &mut Vec<Problem>) {
+    Linter {
+        lines,
+        offset: 0,
+        problems,
+    }
+    .rectangular();
+}
+
+fn guard_map(lines: &[String], problems: &mut Vec<Problem>) {
+    let mut linter = Linter {
+        lines,
+        offset: 0,
+        problems,
+    };
+    linter.rectangular();
+    linter.chars(one_of(".#^>v<"));
+    linter.unique_among("^>v<");
+}
+
+fn topographic_map(lines: &[String], problems: &mut Vec<Problem>) {
+    let mut linter = Linter {
+        lines,
+        offset: 0,
+        problems,
+    };
+    linter.rectangular();
+    linter.chars(converts::<u32>);
+}
+
+fn warehouse(lines: &[String], problems: &mut Vec<Problem>) {
+    let Some(((_, map), (offset, instructions))) = two_sections(lines, problems) else {
+        return;
+    };
+    let mut linter = Linter {
+        lines: map,
+        offset: 0,
+        problems,
+    };
+    linter.rectangular();
+    linter.chars(one_of(".#O@"));
+    linter.unique_markers(&['@']);
+    Linter {
+        lines: instructions,
+        offset,
+        problems,
+    }
+    .chars(one_of("^>v<"));
+}
+
+fn maze(lines: &[String], problems: &mut Vec<Problem>) {
+    let mut linter = Linter {
+        lines,
+        offset: 0,
+        problems,
+    };
+    linter.rectangular();
+    linter.chars(one_of(".#SE"));
+    linter.unique_markers(&['S', 'E']);
+}
+
+// Operands of adv, bst, out, bdv and cdv are combo operands, where 7 is reserved
+const COMBO_OPCODES: [u8; 5] = [0, 2, 5, 6, 7];
+
+fn program(lines: &[String], problems: &mut Vec<Problem>) {
+    let Some(((_, registers), (offset, program))) = two_sections(lines, problems) else {
+        return;
+    };
+    for name in ['A', 'B', 'C'] {
+        let pattern = format!(r"^Register {}: (\d+)$", name);
+        if !registers
+            .iter()
+            .any(|line| captures_as::<(u64,)>(line, &pattern).is_ok())
+        {
+            problems.push(Problem {
+                line: None,
+                message: format!("missing or malformed register {}", name),
+            });
+        }
+    }
+
+    let [line] = program else {
+        problems.push(Problem {
+            line: Some(offset + 1),
+            message: String::from("expected a single program line"),
+        });
+        return;
+    };
+    let mut report = |message: String| {
+        problems.push(Problem {
+            line: Some(offset + 1),
+            message,
+        })
+    };
+    let Ok((codes,)) = captures_as::<(String,)>(line, r"^Program: ([\d,]+)$") else {
+        report(String::from(
+            "expected 'Program: ' followed by comma-separated numbers",
+        ));
+        return;
+    };
+    let codes: Vec<u8> = match codes.split(',').map(str::parse).try_collect() {
+        Ok(codes) => codes,
+        Err(err) => return report(format!("invalid number in program: {}", err)),
+    };
+    for (position, &code) in codes.iter().enumerate() {
+        if code > 7 {
+            report(format!(
+                "value {} at position {} is not in 0..=7",
+                code, position
+            ));
+        }
+    }
+    if !codes.len().is_multiple_of(2) {
+        report(String::from("program has an opcode without an operand"));
+    }
+    for (position, pair) in codes.chunks_exact(2).enumerate() {
+        if COMBO_OPCODES.contains(&pair[0]) && pair[1] == 7 {
+            report(format!(
+                "instruction {} uses reserved combo operand 7",
+                position
+            ));
+        }
+    }
+}
+
+const WIRE: &str = "[a-z0-9]{3}";
+
+fn circuit(lines: &[String], problems: &mut Vec<Problem>) {
+    let Some(((_, values), (offset, gates))) = two_sections(lines, problems) else {
+        return;
+    };
+    let value_pattern = format!("^({}): ([01])$", WIRE);
+    for (index, line) in values.iter().enumerate() {
+        if captures_as::<(String, u8)>(line, &value_pattern).is_err() {
+            problems.push(Problem {
+                line: Some(index + 1),
+                message: format!("expected 'wire: 0/1', found {:?}", line),
+            });
+        }
+    }
+
+    let gate_pattern = format!("^({}) (AND|OR|XOR) ({}) -> ({})$", WIRE, WIRE, WIRE);
+    let mut drivers: HashMap<String, usize> = HashMap::new();
+    for (index, line) in gates.iter().enumerate() {
+        let line_number = offset + index + 1;
+        match captures_as::<(String, String, String, String)>(line, &gate_pattern) {
+            Ok((_, _, _, output)) => {
+                if let Some(first) = drivers.insert(output.clone(), line_number) {
+                    problems.push(Problem {
+                        line: Some(line_number),
+                        message: format!("wire {} is already driven by line {}", output, first),
+                    });
+                }
+            }
+            Err(_) => problems.push(Problem {
+                line: Some(line_number),
+                message: format!(
+                    "expected 'a OP b -> c' with OP in AND/OR/XOR, found {:?}",
+                    line
+                ),
+            }),
+        }
+    }
+}
+
+type Validator = fn(&[String], &mut Vec<Problem>);
+
+fn validator(day: u32) -> Option<Validator> {
+    match day {
+        4 | 8 | 12 => Some(grid),
+        6 => Some(guard_map),
+        10 => Some(topographic_map),
+        15 => Some(warehouse),
+        16 | 20 => Some(maze),
+        17 => Some(program),
+        24 => Some(circuit),
+        _ => None,
+    }
+}
+
+pub fn has_validator(day: u32) -> bool {
+    validator(day).is_some()
+}
+
+// All problems found in the input of the given day; empty if it looks fine or the day has no validator
+pub fn lint(day: u32, input: &Input) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if let Some(validate) = validator(day) {
+        validate(input.lines(), &mut problems);
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_text(day: u32, text: &str) -> Vec<String> {
+        lint(day, &Input::from_text(text))
+            .iter()
+            .map(Problem::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_example_inputs_pass() {
+        for (day, test) in [(6, 1), (10, 1), (15, 2), (16, 1), (17, 1), (20, 1), (24, 2)] {
+            let path = format!("input/input{:02}.txt.test{}", day, test);
+            let input = Input::from_file(&path).unwrap();
+            assert_eq!(lint(day, &input), vec![], "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_grids() {
+        assert_eq!(
+            lint_text(16, "#####\n#S.E#\n####"),
+            vec!["line 3: row has 4 characters, expected 5"]
+        );
+        assert_eq!(
+            lint_text(20, "#S.#\n#.x#\n#S.#"),
+            vec![
+                "line 2: invalid character 'x' at column 3: expected one of \".#SE\"",
+                "line 3: expected exactly one 'S', found 2",
+                "expected exactly one 'E', found 0",
+            ]
+        );
+        assert_eq!(
+            lint_text(10, "0123\n45a7"),
+            vec!["line 2: invalid character 'a' at column 3: expected a digit"]
+        );
+    }
+
+    #[test]
+    fn test_program() {
+        assert_eq!(
+            lint_text(17, "Register A: 1\nRegister B: 0\n\nProgram: 0,7,8,1,3"),
+            vec![
+                "missing or malformed register C",
+                "line 4: value 8 at position 2 is not in 0..=7",
+                "line 4: program has an opcode without an operand",
+                "line 4: instruction 0 uses reserved combo operand 7",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_circuit() {
+        assert_eq!(
+            lint_text(
+                24,
+                "x00: 1\ny00: 2\n\nx00 AND y00 -> z00\nx00 NAND y00 -> z01\nx00 OR y00 -> z00"
+            ),
+            vec![
+                "line 2: expected 'wire: 0/1', found \"y00: 2\"",
+                "line 5: expected 'a OP b -> c' with OP in AND/OR/XOR, found \"x00 NAND y00 -> z01\"",
+                "line 6: wire z00 is already driven by line 4",
+            ]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use num::Integer;
+use std::{collections::HashMap, fmt::Display, hash::Hash};
+
+// The two location lists from day 1, given column by column. They are read as pairs, so lists
+// of different lengths are an error.
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnequalLengths {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Display for UnequalLengths {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "lists of different lengths: {} on the left, {} on the right",
+            self.left, self.right
+        )
+    }
+}
+
+fn collect_pair<T>(
+    left: impl IntoIterator<Item = T>,
+    right: impl IntoIterator<Item = T>,
+) -> Result<(Vec<T>, Vec<T>), UnequalLengths> {
+    let (left, right): (Vec<T>, Vec<T>) = (left.into_iter().collect(), right.into_iter().collect());
+    if left.len() != right.len() {
+        return Err(UnequalLengths {
+            left: left.len(),
+            right: right.len(),
+        });
+    }
+    Ok((left, right))
+}
+
+// Pairs the smallest with the smallest and so on, and sums up how far apart each pair is
+pub fn total_distance<T: Integer + Copy>(
+    left: impl IntoIterator<Item = T>,
+    right: impl IntoIterator<Item = T>,
+) -> Result<T, UnequalLengths> {
+    let (mut left, mut right) = collect_pair(left, right)?;
+    left.sort();
+    right.sort();
+    Ok(left
+        .into_iter()
+        .zip(right)
+        // no abs() for unsigned types
+        .map(|(a, b)| if a > b { a - b } else { b - a })
+        .fold(T::zero(), |total, distance| total + distance))
+}
+
+// Each number on the left, times how often it appears on the right
+pub fn similarity_score<T: Integer + Copy + Hash>(
+    left: impl IntoIterator<Item = T>,
+    right: impl IntoIterator<Item = T>,
+) -> Result<T, UnequalLengths> {
+    let (left, right) = collect_pair(left, right)?;
+    let mut counts: HashMap<T, T> = HashMap::new();
+    for number in right {
+        let count = counts.entry(number).or_insert(T::zero());
+        *count = *count + T::one();
+    }
+    Ok(left
+        .into_iter()
+        .map(|number| number * counts.get(&number).copied().unwrap_or(T::zero()))
+        .fold(T::zero(), |total, score| total + score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from the puzzle
+    const LEFT: [i64; 6] = [3, 4, 2, 1, 3, 3];
+    const RIGHT: [i64; 6] = [4, 3, 5, 3, 9, 3];
+
+    #[test]
+    fn test_total_distance() {
+        assert_eq!(total_distance(LEFT, RIGHT), Ok(11));
+        assert_eq!(
+            total_distance(LEFT.map(|n| n as u64), RIGHT.map(|n| n as u64)),
+            Ok(11)
+        );
+        assert_eq!(total_distance([-5, 7], [5, -7]), Ok(4));
+        assert_eq!(total_distance(Vec::<u64>::new(), []), Ok(0));
+    }
+
+    #[test]
+    fn test_similarity_score() {
+        assert_eq!(similarity_score(LEFT, RIGHT), Ok(31));
+        assert_eq!(
+            similarity_score(LEFT.map(|n| n as u64), RIGHT.map(|n| n as u64)),
+            Ok(31)
+        );
+        assert_eq!(similarity_score([-2, 0], [-2, -2]), Ok(-4));
+    }
+
+    #[test]
+    fn test_unequal_lengths() {
+        let error = UnequalLengths { left: 6, right: 5 };
+        assert_eq!(total_distance(LEFT, RIGHT[..5].to_vec()), Err(error));
+        assert_eq!(
+            similarity_score([1u64], []),
+            Err(UnequalLengths { left: 1, right: 0 })
+        );
+        assert_eq!(
+            UnequalLengths { left: 1, right: 0 }.to_string(),
+            "lists of different lengths: 1 on the left, 0 on the right"
+        );
+    }
+}
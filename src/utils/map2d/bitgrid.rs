@@ -0,0 +1,312 @@
+use crate::utils::map2d::grid::{Bounds, Grid, ValidPosition};
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+const WORD: usize = u64::BITS as usize;
+
+// A packed row of bits; bit x is column x. Bits past the width are always zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitRow {
+    words: Vec<u64>,
+    width: usize,
+}
+
+impl BitRow {
+    pub fn new(width: usize) -> Self {
+        BitRow {
+            words: vec![0; width.div_ceil(WORD)],
+            width,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get(&self, x: usize) -> bool {
+        x < self.width && self.words[x / WORD] >> (x % WORD) & 1 == 1
+    }
+
+    pub fn set(&mut self, x: usize, value: bool) {
+        assert!(x < self.width, "Column {} out of bounds.", x);
+        let mask = 1 << (x % WORD);
+        if value {
+            self.words[x / WORD] |= mask;
+        } else {
+            self.words[x / WORD] &= !mask;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    index * WORD + bit
+                })
+            })
+        })
+    }
+
+    // The first set bit at or after x, e.g. the next obstacle when walking right
+    pub fn next_one(&self, x: usize) -> Option<usize> {
+        if x >= self.width {
+            return None;
+        }
+        let first = self.words[x / WORD] & (u64::MAX << (x % WORD));
+        if first != 0 {
+            return Some(x / WORD * WORD + first.trailing_zeros() as usize);
+        }
+        self.words[x / WORD + 1..]
+            .iter()
+            .position(|&word| word != 0)
+            .map(|offset| {
+                let index = x / WORD + 1 + offset;
+                index * WORD + self.words[index].trailing_zeros() as usize
+            })
+    }
+
+    // The last set bit at or before x, e.g. the next obstacle when walking left
+    pub fn prev_one(&self, x: usize) -> Option<usize> {
+        let x = x.min(self.width.checked_sub(1)?);
+        let last = self.words[x / WORD] & (u64::MAX >> (WORD - 1 - x % WORD));
+        if last != 0 {
+            return Some(x / WORD * WORD + WORD - 1 - last.leading_zeros() as usize);
+        }
+        self.words[..x / WORD]
+            .iter()
+            .rposition(|&word| word != 0)
+            .map(|index| index * WORD + WORD - 1 - self.words[index].leading_zeros() as usize)
+    }
+
+    fn clear_padding(&mut self) {
+        if !self.width.is_multiple_of(WORD) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= u64::MAX >> (WORD - self.width % WORD);
+            }
+        }
+    }
+
+    fn zip_with(&self, other: &BitRow, f: impl Fn(u64, u64) -> u64) -> BitRow {
+        assert_eq!(self.width, other.width, "Rows must have the same width.");
+        BitRow {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            width: self.width,
+        }
+    }
+}
+
+impl BitAnd for &BitRow {
+    type Output = BitRow;
+
+    fn bitand(self, other: &BitRow) -> BitRow {
+        self.zip_with(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitRow {
+    type Output = BitRow;
+
+    fn bitor(self, other: &BitRow) -> BitRow {
+        self.zip_with(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitRow {
+    type Output = BitRow;
+
+    fn bitxor(self, other: &BitRow) -> BitRow {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+}
+
+impl Not for &BitRow {
+    type Output = BitRow;
+
+    fn not(self) -> BitRow {
+        let mut row = BitRow {
+            words: self.words.iter().map(|word| !word).collect(),
+            width: self.width,
+        };
+        row.clear_padding();
+        row
+    }
+}
+
+// Moves every bit `shift` columns right (towards larger x); bits pushed past the width are lost
+impl Shl<usize> for &BitRow {
+    type Output = BitRow;
+
+    fn shl(self, shift: usize) -> BitRow {
+        let mut row = BitRow::new(self.width);
+        let (words, bits) = (shift / WORD, shift % WORD);
+        for index in (words..self.words.len()).rev() {
+            let source = index - words;
+            let mut word = self.words[source] << bits;
+            if bits > 0 && source > 0 {
+                word |= self.words[source - 1] >> (WORD - bits);
+            }
+            row.words[index] = word;
+        }
+        row.clear_padding();
+        row
+    }
+}
+
+// Moves every bit `shift` columns left (towards smaller x)
+impl Shr<usize> for &BitRow {
+    type Output = BitRow;
+
+    fn shr(self, shift: usize) -> BitRow {
+        let mut row = BitRow::new(self.width);
+        let (words, bits) = (shift / WORD, shift % WORD);
+        for index in 0..self.words.len().saturating_sub(words) {
+            let source = index + words;
+            let mut word = self.words[source] >> bits;
+            if bits > 0 && source + 1 < self.words.len() {
+                word |= self.words[source + 1] << (WORD - bits);
+            }
+            row.words[index] = word;
+        }
+        row
+    }
+}
+
+// A dense occupancy map, e.g. the obstacles of a '#'/'.' map, stored as packed rows
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    pub rows: Vec<BitRow>,
+    pub bounds: Bounds,
+}
+
+impl BitGrid {
+    pub fn new(bounds: Bounds) -> Self {
+        BitGrid {
+            rows: vec![BitRow::new(bounds.0); bounds.1],
+            bounds,
+        }
+    }
+
+    pub fn get(&self, pos: &ValidPosition) -> bool {
+        self.rows[pos.1].get(pos.0)
+    }
+
+    pub fn set(&mut self, pos: &ValidPosition, value: bool) {
+        self.rows[pos.1].set(pos.0, value);
+    }
+
+    pub fn row(&self, y: usize) -> &BitRow {
+        &self.rows[y]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.rows.iter().map(BitRow::count_ones).sum()
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = ValidPosition> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.ones().map(move |x| ValidPosition(x, y)))
+    }
+
+    // Rows become columns, so vertical scans can use the row operations
+    pub fn transposed(&self) -> Self {
+        let mut transposed = BitGrid::new(Bounds(self.bounds.1, self.bounds.0));
+        for ValidPosition(x, y) in self.ones() {
+            transposed.set(&ValidPosition(y, x), true);
+        }
+        transposed
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> Self {
+        let mut bits = BitGrid::new(grid.bounds);
+        for pos in grid.scanline_iter() {
+            if *grid.value(&pos) {
+                bits.set(&pos, true);
+            }
+        }
+        bits
+    }
+}
+
+impl From<&BitGrid> for Grid<bool> {
+    fn from(bits: &BitGrid) -> Self {
+        Grid::from_fn(bits.bounds, |pos| bits.get(&pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(width: usize, ones: &[usize]) -> BitRow {
+        let mut row = BitRow::new(width);
+        for &x in ones {
+            row.set(x, true);
+        }
+        row
+    }
+
+    #[test]
+    fn test_row_operations() {
+        let a = row(70, &[0, 3, 64, 69]);
+        let b = row(70, &[3, 5, 69]);
+        assert_eq!(&a & &b, row(70, &[3, 69]));
+        assert_eq!(&a | &b, row(70, &[0, 3, 5, 64, 69]));
+        assert_eq!(&a ^ &b, row(70, &[0, 5, 64]));
+        assert_eq!((!&a).count_ones(), 66);
+
+        assert_eq!(&a << 1, row(70, &[1, 4, 65]));
+        assert_eq!(&a << 64, row(70, &[64, 67]));
+        assert_eq!(&a >> 1, row(70, &[2, 63, 68]));
+        assert_eq!(&a >> 66, row(70, &[3]));
+    }
+
+    #[test]
+    fn test_row_search() {
+        let a = row(130, &[2, 64, 129]);
+        assert_eq!(a.ones().collect::<Vec<_>>(), vec![2, 64, 129]);
+        assert_eq!(a.next_one(0), Some(2));
+        assert_eq!(a.next_one(3), Some(64));
+        assert_eq!(a.next_one(65), Some(129));
+        assert_eq!(a.next_one(130), None);
+        assert_eq!(a.prev_one(200), Some(129));
+        assert_eq!(a.prev_one(128), Some(64));
+        assert_eq!(a.prev_one(63), Some(2));
+        assert_eq!(a.prev_one(1), None);
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let grid = Grid::from_fn(Bounds(5, 3), |ValidPosition(x, y)| (x + y) % 3 == 0);
+        let bits = BitGrid::from(&grid);
+        assert_eq!(bits.count_ones(), 5);
+        assert_eq!(Grid::<bool>::from(&bits).data, grid.data);
+
+        let transposed = bits.transposed();
+        assert_eq!(transposed.bounds, Bounds(3, 5));
+        assert!(bits
+            .ones()
+            .all(|ValidPosition(x, y)| transposed.get(&ValidPosition(y, x))));
+    }
+}
@@ -1,4 +1,9 @@
+use crate::utils::map2d::grid::ToChar;
 use crate::utils::map2d::position::Position;
+use crate::utils::mathnd::IntVec2D;
+use std::fmt::Display;
+use std::ops::Deref;
+use std::str::FromStr;
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum Direction {
@@ -48,6 +53,15 @@ impl Direction {
         *self = self.turned_around();
     }
 
+    pub fn offset(self) -> IntVec2D<i32> {
+        match self {
+            Self::UP => IntVec2D(0, -1),
+            Self::RIGHT => IntVec2D(1, 0),
+            Self::DOWN => IntVec2D(0, 1),
+            Self::LEFT => IntVec2D(-1, 0),
+        }
+    }
+
     pub fn iter_all() -> impl Iterator<Item = Direction> {
         [
             Direction::UP,
@@ -60,21 +74,26 @@ impl Direction {
     }
 }
 
-impl From<char> for Direction {
-    fn from(character: char) -> Self {
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidDirectionChar(pub char);
+
+impl TryFrom<char> for Direction {
+    type Error = InvalidDirectionChar;
+
+    fn try_from(character: char) -> Result<Self, Self::Error> {
         match character {
-            '^' => Direction::UP,
-            '>' => Direction::RIGHT,
-            'v' => Direction::DOWN,
-            '<' => Direction::LEFT,
-            _ => panic!("Invalid character {character} specified to create Direction."),
+            '^' => Ok(Direction::UP),
+            '>' => Ok(Direction::RIGHT),
+            'v' => Ok(Direction::DOWN),
+            '<' => Ok(Direction::LEFT),
+            _ => Err(InvalidDirectionChar(character)),
         }
     }
 }
 
-impl Into<char> for Direction {
-    fn into(self) -> char {
-        match self {
+impl From<Direction> for char {
+    fn from(direction: Direction) -> Self {
+        match direction {
             Direction::UP => '^',
             Direction::RIGHT => '>',
             Direction::DOWN => 'v',
@@ -83,15 +102,112 @@ impl Into<char> for Direction {
     }
 }
 
+impl ToChar for Direction {
+    fn to_char(&self) -> char {
+        (*self).into()
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// A parsable run of directions such as "^^>>vv"; whitespace is skipped
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DirectionSequence(pub Vec<Direction>);
+
+impl FromStr for DirectionSequence {
+    type Err = InvalidDirectionChar;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(Direction::try_from)
+            .collect::<Result<Vec<Direction>, _>>()
+            .map(DirectionSequence)
+    }
+}
+
+impl Display for DirectionSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|direction| write!(f, "{direction}"))
+    }
+}
+
+impl Deref for DirectionSequence {
+    type Target = Vec<Direction>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for DirectionSequence {
+    type Item = Direction;
+    type IntoIter = std::vec::IntoIter<Direction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl Position {
     pub fn step(&self, direction: &Direction) -> Position {
-        let Position(x, y) = self;
+        *self + direction.offset()
+    }
 
-        match direction {
-            Direction::UP => Position(*x, *y - 1),
-            Direction::RIGHT => Position(*x + 1, *y),
-            Direction::DOWN => Position(*x, *y + 1),
-            Direction::LEFT => Position(*x - 1, *y),
+    pub fn step_n(&self, direction: &Direction, n: i32) -> Position {
+        *self + direction.offset() * n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_round_trip() {
+        for direction in Direction::iter_all() {
+            assert_eq!(Direction::try_from(direction.to_char()), Ok(direction));
+        }
+        assert_eq!(Direction::try_from('x'), Err(InvalidDirectionChar('x')));
+    }
+
+    #[test]
+    fn test_offsets_and_steps() {
+        // y points down
+        assert_eq!(Direction::UP.offset(), IntVec2D(0, -1));
+        assert_eq!(Direction::RIGHT.offset(), IntVec2D(1, 0));
+        for direction in Direction::iter_all() {
+            assert_eq!(direction.turned_around().offset(), direction.offset() * -1);
+            assert_eq!(direction.offset().manhattan(), 1);
         }
+
+        let pos = Position(2, 1);
+        assert_eq!(pos.step_n(&Direction::LEFT, 5), Position(-3, 1));
+        assert_eq!(pos.step_n(&Direction::UP, 3), Position(2, -2));
+        assert_eq!(pos.step_n(&Direction::DOWN, 0), pos);
+        assert_eq!(pos.step_n(&Direction::DOWN, 1), pos.step(&Direction::DOWN));
+        // a negative count steps backwards
+        assert_eq!(
+            pos.step_n(&Direction::RIGHT, -2),
+            pos.step_n(&Direction::LEFT, 2)
+        );
+    }
+
+    #[test]
+    fn test_sequences() {
+        let sequence: DirectionSequence = "^^>\n>vv<".parse().unwrap();
+        assert_eq!(sequence.len(), 7);
+        assert_eq!(sequence[2], Direction::RIGHT);
+        assert_eq!(sequence.to_string(), "^^>>vv<");
+        assert_eq!(
+            "^^A".parse::<DirectionSequence>(),
+            Err(InvalidDirectionChar('A'))
+        );
     }
 }
@@ -1,12 +1,15 @@
 use crate::utils::map2d::direction::Direction;
 use crate::utils::map2d::position::Position;
 use itertools::Itertools;
-use std::collections::{HashSet, VecDeque};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Bounds(pub usize, pub usize);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Grid<T> {
     pub data: Vec<Vec<T>>,
     pub bounds: Bounds,
@@ -15,6 +18,47 @@ pub struct Grid<T> {
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ValidPosition(pub usize, pub usize);
 
+impl Bounds {
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.0 >= 0 && pos.1 >= 0 && pos.0 < self.0 as i32 && pos.1 < self.1 as i32
+    }
+
+    // Column-major, like Grid::position_iter
+    pub fn iter(&self) -> impl Iterator<Item = ValidPosition> {
+        self.column_major_iter()
+    }
+
+    // Row by row, left to right: (0, 0), (1, 0), ..., (0, 1), ...
+    pub fn scanline_iter(&self) -> impl Iterator<Item = ValidPosition> {
+        (0..self.1)
+            .cartesian_product(0..self.0)
+            .map(|(y, x)| ValidPosition(x, y))
+    }
+
+    // Column by column, top to bottom: (0, 0), (0, 1), ..., (1, 0), ...
+    pub fn column_major_iter(&self) -> impl Iterator<Item = ValidPosition> {
+        (0..self.0)
+            .cartesian_product(0..self.1)
+            .map(|(x, y)| ValidPosition(x, y))
+    }
+
+    pub fn area(&self) -> usize {
+        self.0 * self.1
+    }
+
+    pub fn intersection(&self, other: &Bounds) -> Bounds {
+        Bounds(self.0.min(other.0), self.1.min(other.1))
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds(self.0.max(other.0), self.1.max(other.1))
+    }
+
+    pub fn expanded(&self, dx: usize, dy: usize) -> Bounds {
+        Bounds(self.0 + dx, self.1 + dy)
+    }
+}
+
 impl Into<Position> for ValidPosition {
     fn into(self) -> Position {
         Position(self.0 as i32, self.1 as i32)
@@ -23,11 +67,9 @@ impl Into<Position> for ValidPosition {
 
 impl Position {
     pub fn in_bounds(&self, bounds: &Bounds) -> Option<ValidPosition> {
-        if self.0 >= 0 && self.1 >= 0 && self.0 < bounds.0 as i32 && self.1 < bounds.1 as i32 {
-            Some(ValidPosition(self.0 as usize, self.1 as usize))
-        } else {
-            None
-        }
+        bounds
+            .contains(self)
+            .then_some(ValidPosition(self.0 as usize, self.1 as usize))
     }
 
     pub fn valid_neighbours(&self, bounds: &Bounds) -> HashSet<ValidPosition> {
@@ -48,23 +90,68 @@ impl ValidPosition {
         let pos: Position = (*self).into();
         pos.step(direction).in_bounds(bounds)
     }
+
+    pub fn try_step_n(&self, direction: &Direction, n: usize, bounds: &Bounds) -> Option<Self> {
+        let pos: Position = (*self).into();
+        // steps beyond i32 leave any grid
+        pos.step_n(direction, i32::try_from(n).ok()?)
+            .in_bounds(bounds)
+    }
+
+    pub fn within_manhattan(&self, radius: usize, bounds: &Bounds) -> Vec<ValidPosition> {
+        let Position(x, y) = (*self).into();
+        let radius = radius as i32;
+        (-radius..=radius)
+            .flat_map(|dx| {
+                let max_dy = radius - dx.abs();
+                (-max_dy..=max_dy).map(move |dy| Position(x + dx, y + dy))
+            })
+            .filter_map(|pos| pos.in_bounds(bounds))
+            .collect()
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> usize {
+        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> usize {
+        self.0.abs_diff(other.0).max(self.1.abs_diff(other.1))
+    }
+
+    pub fn euclidean_sq(&self, other: &Self) -> usize {
+        let (dx, dy) = (self.0.abs_diff(other.0), self.1.abs_diff(other.1));
+        dx * dx + dy * dy
+    }
 }
 
-impl<T: Clone> Grid<T> {
-    pub fn new(bounds: Bounds, fill: T) -> Self {
+impl<T> Grid<T> {
+    pub fn from_fn(bounds: Bounds, mut f: impl FnMut(ValidPosition) -> T) -> Self {
         let data: Vec<Vec<T>> = (0..bounds.1)
-            .map(|_| -> Vec<T> { (0..bounds.0).map(|_| fill.clone()).collect() })
+            .map(|y| -> Vec<T> { (0..bounds.0).map(|x| f(ValidPosition(x, y))).collect() })
             .collect();
 
         Grid { data, bounds }
     }
 }
 
+impl<T: Clone> Grid<T> {
+    pub fn filled(bounds: Bounds, value: T) -> Self {
+        Self::from_fn(bounds, |_| value.clone())
+    }
+}
+
 impl<T> Grid<T> {
+    // Column-major; use scanline_iter when row-major order matters
     pub fn position_iter(&self) -> impl Iterator<Item = ValidPosition> {
-        (0..self.bounds.0)
-            .cartesian_product(0..self.bounds.1)
-            .map(|(x, y)| ValidPosition(x, y))
+        self.bounds.column_major_iter()
+    }
+
+    pub fn scanline_iter(&self) -> impl Iterator<Item = ValidPosition> {
+        self.bounds.scanline_iter()
+    }
+
+    pub fn column_major_iter(&self) -> impl Iterator<Item = ValidPosition> {
+        self.bounds.column_major_iter()
     }
 
     pub fn value(&self, pos: &ValidPosition) -> &T {
@@ -76,12 +163,272 @@ impl<T> Grid<T> {
     }
 }
 
+// A width x height view into a grid, addressed relative to its top-left anchor
+pub struct GridWindow<'a, T> {
+    grid: &'a Grid<T>,
+    pub anchor: ValidPosition,
+    pub bounds: Bounds,
+}
+
+impl<'a, T> GridWindow<'a, T> {
+    pub fn value(&self, pos: &ValidPosition) -> &'a T {
+        self.grid
+            .value(&ValidPosition(self.anchor.0 + pos.0, self.anchor.1 + pos.1))
+    }
+
+    pub fn position_iter(&self) -> impl Iterator<Item = ValidPosition> {
+        self.bounds.iter()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &'a T> + '_ {
+        self.position_iter().map(|pos| self.value(&pos))
+    }
+}
+
+impl<T> Grid<T> {
+    // All windows lying fully inside the grid, one per anchor position
+    pub fn windows(&self, width: usize, height: usize) -> impl Iterator<Item = GridWindow<'_, T>> {
+        let Bounds(grid_width, grid_height) = self.bounds;
+        let (anchors_x, anchors_y) = if width <= grid_width && height <= grid_height {
+            (grid_width - width + 1, grid_height - height + 1)
+        } else {
+            (0, 0)
+        };
+
+        (0..anchors_x)
+            .cartesian_product(0..anchors_y)
+            .map(move |(x, y)| GridWindow {
+                grid: self,
+                anchor: ValidPosition(x, y),
+                bounds: Bounds(width, height),
+            })
+    }
+
+    pub fn map<S>(&self, f: impl Fn(&T) -> S) -> Grid<S> {
+        Grid {
+            data: self
+                .data
+                .iter()
+                .map(|row| row.iter().map(&f).collect_vec())
+                .collect_vec(),
+            bounds: self.bounds,
+        }
+    }
+}
+
+// Without the `rayon` feature these run sequentially with the same signatures
+#[cfg(feature = "rayon")]
+impl<T: Sync> Grid<T> {
+    pub fn par_map<S: Send>(&self, f: impl Fn(ValidPosition, &T) -> S + Sync) -> Grid<S> {
+        use rayon::prelude::*;
+
+        let data: Vec<Vec<S>> = self
+            .data
+            .par_iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, value)| f(ValidPosition(x, y), value))
+                    .collect()
+            })
+            .collect();
+        Grid {
+            data,
+            bounds: self.bounds,
+        }
+    }
+
+    pub fn par_fold<A: Send>(
+        &self,
+        identity: impl Fn() -> A + Sync + Send,
+        fold: impl Fn(A, ValidPosition, &T) -> A + Sync + Send,
+        reduce: impl Fn(A, A) -> A + Sync + Send,
+    ) -> A {
+        use rayon::prelude::*;
+
+        self.data
+            .par_iter()
+            .enumerate()
+            .fold(&identity, |acc, (y, row)| {
+                row.iter()
+                    .enumerate()
+                    .fold(acc, |acc, (x, value)| fold(acc, ValidPosition(x, y), value))
+            })
+            .reduce(&identity, reduce)
+    }
+}
+
+#[cfg(not(feature = "rayon"))]
+impl<T> Grid<T> {
+    pub fn par_map<S>(&self, f: impl Fn(ValidPosition, &T) -> S) -> Grid<S> {
+        Grid::from_fn(self.bounds, |pos| f(pos, self.value(&pos)))
+    }
+
+    pub fn par_fold<A>(
+        &self,
+        identity: impl Fn() -> A,
+        fold: impl Fn(A, ValidPosition, &T) -> A,
+        _reduce: impl Fn(A, A) -> A,
+    ) -> A {
+        self.position_iter()
+            .fold(identity(), |acc, pos| fold(acc, pos, self.value(&pos)))
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn rotated_cw(&self) -> Self {
+        let Bounds(width, height) = self.bounds;
+        let data = (0..width)
+            .map(|y| {
+                (0..height)
+                    .map(|x| self.data[height - 1 - x][y].clone())
+                    .collect_vec()
+            })
+            .collect_vec();
+        Grid {
+            data,
+            bounds: Bounds(height, width),
+        }
+    }
+
+    pub fn mirrored(&self) -> Self {
+        let data = self
+            .data
+            .iter()
+            .map(|row| row.iter().rev().cloned().collect_vec())
+            .collect_vec();
+        Grid {
+            data,
+            bounds: self.bounds,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Grid<T> {
+    // The distinct grids among the 4 rotations and their mirror images
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut orientations: Vec<Self> = Vec::new();
+        let mut grid = self.clone();
+        for _ in 0..4 {
+            for candidate in [grid.mirrored(), grid.clone()] {
+                if !orientations
+                    .iter()
+                    .any(|other| other.data == candidate.data)
+                {
+                    orientations.push(candidate);
+                }
+            }
+            grid = grid.rotated_cw();
+        }
+        orientations
+    }
+}
+
 impl<T: PartialEq> Grid<T> {
-    pub fn find(&self, value: &T) -> HashSet<ValidPosition> {
+    // Top-left anchors at which the template matches; None in the template matches anything
+    pub fn find_pattern(&self, pattern: &Grid<Option<T>>) -> Vec<ValidPosition> {
+        self.windows(pattern.bounds.0, pattern.bounds.1)
+            .filter(|window| {
+                pattern.position_iter().all(|pos| {
+                    pattern
+                        .value(&pos)
+                        .as_ref()
+                        .is_none_or(|value| window.value(&pos) == value)
+                })
+            })
+            .map(|window| window.anchor)
+            .collect()
+    }
+}
+
+impl<T: Clone + PartialEq> Grid<T> {
+    pub fn find_pattern_any_orientation(&self, pattern: &Grid<Option<T>>) -> Vec<ValidPosition> {
+        pattern
+            .orientations()
+            .iter()
+            .flat_map(|oriented| self.find_pattern(oriented))
+            .collect()
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    // Cells where the grids differ, as (position, own value, other value)
+    pub fn diff<'a>(&'a self, other: &'a Grid<T>) -> Vec<(ValidPosition, &'a T, &'a T)> {
+        self.bounds
+            .intersection(&other.bounds)
+            .iter()
+            .map(|pos| (pos, self.value(&pos), other.value(&pos)))
+            .filter(|(_, value, other_value)| value != other_value)
+            .collect()
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn overlay<'a>(
+        &self,
+        positions: impl IntoIterator<Item = &'a ValidPosition>,
+        value: T,
+    ) -> Self {
+        let mut grid = self.clone();
+        for pos in positions {
+            *grid.value_mut(pos) = value.clone();
+        }
+        grid
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FindError {
+    NotFound,
+    Ambiguous(usize),
+}
+
+impl<T> Grid<T> {
+    pub fn find_where(&self, predicate: impl Fn(&T) -> bool) -> HashSet<ValidPosition> {
         self.position_iter()
-            .filter(|pos| -> bool { self.value(pos) == value })
+            .filter(|pos| -> bool { predicate(self.value(pos)) })
             .collect()
     }
+}
+
+impl<T: PartialEq> Grid<T> {
+    pub fn find(&self, value: &T) -> HashSet<ValidPosition> {
+        self.find_where(|other| other == value)
+    }
+
+    // First occurrence in scanline order
+    pub fn position_of(&self, value: &T) -> Option<ValidPosition> {
+        self.scanline_iter().find(|pos| self.value(pos) == value)
+    }
+
+    pub fn unique_position_of(&self, value: &T) -> Result<ValidPosition, FindError> {
+        let positions = self.find(value);
+        match positions.len() {
+            0 => Err(FindError::NotFound),
+            1 => Ok(*positions.iter().next().unwrap()),
+            n => Err(FindError::Ambiguous(n)),
+        }
+    }
+
+    // Replace every marker with the background, recording where each marker was in scanline order.
+    // Every requested marker gets an entry, possibly empty.
+    pub fn take_markers(&mut self, markers: &[T], background: T) -> HashMap<T, Vec<ValidPosition>>
+    where
+        T: Eq + Hash + Clone,
+    {
+        let mut found: HashMap<T, Vec<ValidPosition>> = markers
+            .iter()
+            .map(|marker| (marker.clone(), Vec::new()))
+            .collect();
+        for pos in self.bounds.scanline_iter() {
+            if let Some(positions) = found.get_mut(self.value(&pos)) {
+                positions.push(pos);
+                *self.value_mut(&pos) = background.clone();
+            }
+        }
+        found
+    }
 
     pub fn contiguous_region(&self, &pos: &ValidPosition) -> HashSet<ValidPosition> {
         let mut visited: HashSet<ValidPosition> = HashSet::new();
@@ -141,3 +488,221 @@ impl<S: Clone + Into<T>, T> Convert<Grid<T>> for Grid<S> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        Grid::from_rows(rows)
+    }
+
+    #[test]
+    fn test_constructors() {
+        let grid = Grid::from_fn(Bounds(3, 2), |ValidPosition(x, y)| 10 * y + x);
+        assert_eq!(grid.bounds, Bounds(3, 2));
+        assert_eq!(*grid.value(&ValidPosition(2, 0)), 2);
+        assert_eq!(*grid.value(&ValidPosition(1, 1)), 11);
+        assert_eq!(grid.position_iter().count(), 6);
+
+        // called once per cell, row by row
+        let mut calls = Vec::new();
+        Grid::from_fn(Bounds(2, 2), |pos| calls.push(pos));
+        assert_eq!(
+            calls,
+            vec![
+                ValidPosition(0, 0),
+                ValidPosition(1, 0),
+                ValidPosition(0, 1),
+                ValidPosition(1, 1)
+            ]
+        );
+
+        let filled = Grid::filled(Bounds(4, 1), 'x');
+        assert_eq!(filled.pretty_print_string(), "xxxx");
+        let empty = Grid::filled(Bounds(0, 0), 0);
+        assert_eq!(empty.position_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds = Bounds(3, 2);
+        assert!(bounds.contains(&Position(2, 1)));
+        assert!(!bounds.contains(&Position(3, 1)));
+        assert!(!bounds.contains(&Position(0, -1)));
+        assert_eq!(bounds.iter().count(), bounds.area());
+        assert_eq!(bounds.intersection(&Bounds(5, 1)), Bounds(3, 1));
+        assert_eq!(bounds.union(&Bounds(1, 4)), Bounds(3, 4));
+        assert_eq!(bounds.expanded(1, 2), Bounds(4, 4));
+    }
+
+    #[test]
+    fn test_try_step_n() {
+        let bounds = Bounds(5, 3);
+        let pos = ValidPosition(1, 2);
+        assert_eq!(
+            pos.try_step_n(&Direction::RIGHT, 3, &bounds),
+            Some(ValidPosition(4, 2))
+        );
+        assert_eq!(
+            pos.try_step_n(&Direction::UP, 2, &bounds),
+            Some(ValidPosition(1, 0))
+        );
+        assert_eq!(pos.try_step_n(&Direction::LEFT, 0, &bounds), Some(pos));
+        assert_eq!(
+            pos.try_step_n(&Direction::UP, 1, &bounds),
+            pos.try_step(&Direction::UP, &bounds)
+        );
+
+        // off every edge
+        assert_eq!(pos.try_step_n(&Direction::RIGHT, 4, &bounds), None);
+        assert_eq!(pos.try_step_n(&Direction::LEFT, 2, &bounds), None);
+        assert_eq!(pos.try_step_n(&Direction::UP, 3, &bounds), None);
+        assert_eq!(pos.try_step_n(&Direction::DOWN, 1, &bounds), None);
+        assert_eq!(pos.try_step_n(&Direction::RIGHT, usize::MAX, &bounds), None);
+    }
+
+    // '.' marks a cell of the template that may hold anything
+    fn template(rows: &[&str]) -> Grid<Option<char>> {
+        grid(rows).map(|&c| (c != '.').then_some(c))
+    }
+
+    #[test]
+    fn test_orientations() {
+        assert_eq!(template(&["XMAS"]).orientations().len(), 4);
+        assert_eq!(template(&["M.S", ".A.", "M.S"]).orientations().len(), 4);
+        assert_eq!(template(&["XM", "A."]).orientations().len(), 8);
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let puzzle = grid(&["XMASX", "SAMXM"]);
+        assert_eq!(
+            puzzle.find_pattern(&template(&["XMAS"])),
+            vec![ValidPosition(0, 0)]
+        );
+        assert_eq!(puzzle.find_pattern(&template(&["X", "M"])).len(), 1);
+        assert_eq!(puzzle.find_pattern(&template(&["A.X"])).len(), 2);
+        // larger than the grid, or all wildcards
+        assert!(puzzle.find_pattern(&template(&["XMASXM"])).is_empty());
+        assert_eq!(puzzle.find_pattern(&template(&["..", ".."])).len(), 4);
+        assert_eq!(
+            puzzle
+                .find_pattern_any_orientation(&template(&["XMAS"]))
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_within_manhattan() {
+        let bounds = Bounds(100, 100);
+        assert_eq!(ValidPosition(50, 50).within_manhattan(2, &bounds).len(), 13);
+        assert_eq!(
+            ValidPosition(50, 50).within_manhattan(20, &bounds).len(),
+            841
+        );
+        assert_eq!(
+            ValidPosition(50, 50).within_manhattan(0, &bounds),
+            vec![ValidPosition(50, 50)]
+        );
+        // clipped by the corner of the grid
+        assert_eq!(ValidPosition(0, 0).within_manhattan(2, &bounds).len(), 6);
+        let center = ValidPosition(3, 4);
+        assert!(center
+            .within_manhattan(3, &bounds)
+            .iter()
+            .all(|pos| pos.manhattan_distance(&center) <= 3));
+    }
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = (ValidPosition(1, 7), ValidPosition(4, 2));
+        assert_eq!(a.manhattan_distance(&b), 3 + 5);
+        assert_eq!(a.chebyshev_distance(&b), 5);
+        assert_eq!(a.euclidean_sq(&b), 9 + 25);
+        assert_eq!(b.manhattan_distance(&a), 8);
+        assert_eq!(a.manhattan_distance(&a), 0);
+
+        // the same as between the corresponding positions
+        let (pa, pb): (Position, Position) = (a.into(), b.into());
+        assert_eq!(
+            a.manhattan_distance(&b),
+            pa.manhattan_distance(&pb) as usize
+        );
+        assert_eq!(
+            a.chebyshev_distance(&b),
+            pa.chebyshev_distance(&pb) as usize
+        );
+        assert_eq!(a.euclidean_sq(&b), pa.euclidean_sq(&pb) as usize);
+    }
+
+    #[test]
+    fn test_par_map_and_fold() {
+        let grid: Grid<u32> = Grid::from_rows(["123", "456"]);
+        let doubled = grid.par_map(|_, value| value * 2);
+        assert_eq!(doubled.data, vec![vec![2, 4, 6], vec![8, 10, 12]]);
+
+        let weighted_sum = grid.par_fold(
+            || 0,
+            |acc, ValidPosition(x, _), value| acc + x as u32 * value,
+            |a, b| a + b,
+        );
+        assert_eq!(weighted_sum, 2 + 6 + 5 + 12);
+    }
+
+    #[test]
+    fn test_diff_and_overlay() {
+        let before = grid(&["ab", "cd"]);
+        let after = before.overlay(&[ValidPosition(1, 0), ValidPosition(0, 1)], 'x');
+        assert_eq!(after.pretty_print_string(), "ax\nxd");
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                (ValidPosition(0, 1), &'c', &'x'),
+                (ValidPosition(1, 0), &'b', &'x')
+            ]
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn test_iteration_order() {
+        let grid = grid(&["abc", "def"]);
+        let scanline: String = grid.scanline_iter().map(|pos| grid.value(&pos)).collect();
+        let column_major: String = grid
+            .column_major_iter()
+            .map(|pos| grid.value(&pos))
+            .collect();
+        assert_eq!(scanline, "abcdef");
+        assert_eq!(column_major, "adbecf");
+        assert!(grid.position_iter().eq(grid.column_major_iter()));
+    }
+
+    #[test]
+    fn test_find() {
+        let grid = grid(&["S.#", "#.S", "..E"]);
+        assert_eq!(grid.find_where(|&c| c == '#' || c == 'E').len(), 3);
+        assert_eq!(grid.position_of(&'S'), Some(ValidPosition(0, 0)));
+        assert_eq!(grid.position_of(&'x'), None);
+        assert_eq!(grid.unique_position_of(&'E'), Ok(ValidPosition(2, 2)));
+        assert_eq!(grid.unique_position_of(&'S'), Err(FindError::Ambiguous(2)));
+        assert_eq!(grid.unique_position_of(&'x'), Err(FindError::NotFound));
+    }
+
+    #[test]
+    fn test_windows() {
+        let grid = grid(&["abc", "def"]);
+        assert_eq!(grid.windows(2, 2).count(), 2);
+        assert_eq!(grid.windows(1, 1).count(), 6);
+        assert_eq!(grid.windows(4, 1).count(), 0);
+
+        let window = grid
+            .windows(2, 2)
+            .find(|window| window.anchor == ValidPosition(1, 0))
+            .unwrap();
+        assert_eq!(*window.value(&ValidPosition(0, 0)), 'b');
+        assert_eq!(*window.value(&ValidPosition(1, 1)), 'f');
+        assert_eq!(window.values().collect::<String>(), "becf");
+    }
+}
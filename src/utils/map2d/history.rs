@@ -0,0 +1,102 @@
+use crate::utils::map2d::grid::{Grid, ValidPosition};
+
+// Changed cells of one step, stored as (position, before, after)
+type Delta<T> = Vec<(ValidPosition, T, T)>;
+
+// Keeps a grid together with the cell-level changes of every step,
+// so a simulation can be stepped backward (undo) and forward again (replay)
+#[derive(Debug, Clone)]
+pub struct GridHistory<T> {
+    grid: Grid<T>,
+    done: Vec<Delta<T>>,
+    undone: Vec<Delta<T>>,
+}
+
+impl<T: Clone + PartialEq> GridHistory<T> {
+    pub fn new(grid: Grid<T>) -> Self {
+        GridHistory {
+            grid,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn steps(&self) -> usize {
+        self.done.len()
+    }
+
+    // Record the step leading from the current grid to `next`
+    pub fn record(&mut self, next: &Grid<T>) {
+        let delta = Self::delta(&self.grid, next);
+        for (pos, _, after) in &delta {
+            *self.grid.value_mut(pos) = after.clone();
+        }
+        self.done.push(delta);
+        self.undone.clear();
+    }
+
+    // Run a step on the current grid and record what it changed
+    pub fn apply<R>(&mut self, step: impl FnOnce(&mut Grid<T>) -> R) -> R {
+        let before = self.grid.clone();
+        let result = step(&mut self.grid);
+        self.done.push(Self::delta(&before, &self.grid));
+        self.undone.clear();
+        result
+    }
+
+    pub fn undo(&mut self) -> Option<&Grid<T>> {
+        let delta = self.done.pop()?;
+        for (pos, before, _) in &delta {
+            *self.grid.value_mut(pos) = before.clone();
+        }
+        self.undone.push(delta);
+        Some(&self.grid)
+    }
+
+    pub fn replay(&mut self) -> Option<&Grid<T>> {
+        let delta = self.undone.pop()?;
+        for (pos, _, after) in &delta {
+            *self.grid.value_mut(pos) = after.clone();
+        }
+        self.done.push(delta);
+        Some(&self.grid)
+    }
+
+    fn delta(before: &Grid<T>, after: &Grid<T>) -> Delta<T> {
+        before
+            .diff(after)
+            .into_iter()
+            .map(|(pos, before, after)| (pos, before.clone(), after.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_replay() {
+        let start: Grid<char> = Grid::from_rows(["ab", "cd"]);
+        let mut history = GridHistory::new(start.clone());
+
+        history.apply(|grid| *grid.value_mut(&ValidPosition(0, 0)) = 'x');
+        history.record(&Grid::from_rows(["xy", "cd"]));
+        assert_eq!(history.steps(), 2);
+        assert_eq!(history.current().pretty_print_string(), "xy\ncd");
+
+        assert_eq!(history.undo().unwrap().pretty_print_string(), "xb\ncd");
+        assert_eq!(history.undo().unwrap().pretty_print_string(), "ab\ncd");
+        assert!(history.undo().is_none());
+
+        assert_eq!(history.replay().unwrap().pretty_print_string(), "xb\ncd");
+        // a new step discards the steps that could still be replayed
+        history.apply(|grid| *grid.value_mut(&ValidPosition(1, 1)) = 'z');
+        assert!(history.replay().is_none());
+        assert_eq!(history.current().pretty_print_string(), "xb\ncz");
+    }
+}
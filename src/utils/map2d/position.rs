@@ -1,10 +1,17 @@
 use std::ops::{Add, Sub};
 
-use crate::utils::math2d::IntVec2D;
+use crate::utils::mathnd::IntVec2D;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct Position(pub i32, pub i32);
 
+// A mirror line: Vertical(c) is the line x = c, Horizontal(c) the line y = c
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Axis {
+    Vertical(i32),
+    Horizontal(i32),
+}
+
 impl Position {
     pub fn neighbours(&self) -> Vec<Position> {
         vec![
@@ -18,6 +25,42 @@ impl Position {
     pub fn mirrored_across(&self, other: &Self) -> Self {
         Position(2 * other.0 - self.0, 2 * other.1 - self.1)
     }
+
+    // Clockwise as drawn, i.e. with the y axis pointing down
+    pub fn rotated_cw_about(&self, origin: &Self) -> Self {
+        *origin + (*self - *origin).rotated_cw()
+    }
+
+    pub fn rotated_ccw_about(&self, origin: &Self) -> Self {
+        *origin + (*self - *origin).rotated_ccw()
+    }
+
+    pub fn rotated_180_about(&self, origin: &Self) -> Self {
+        self.mirrored_across(origin)
+    }
+
+    pub fn reflected(&self, axis: Axis) -> Self {
+        match axis {
+            Axis::Vertical(x) => Position(2 * x - self.0, self.1),
+            Axis::Horizontal(y) => Position(self.0, 2 * y - self.1),
+        }
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> u32 {
+        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> u32 {
+        self.0.abs_diff(other.0).max(self.1.abs_diff(other.1))
+    }
+
+    pub fn euclidean_sq(&self, other: &Self) -> u64 {
+        let (dx, dy) = (
+            self.0.abs_diff(other.0) as u64,
+            self.1.abs_diff(other.1) as u64,
+        );
+        dx * dx + dy * dy
+    }
 }
 
 impl Add<IntVec2D<i32>> for Position {
@@ -35,3 +78,26 @@ impl Sub<Position> for Position {
         IntVec2D(self.0 - rhs.0, self.1 - rhs.1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = (Position(1, -2), Position(-3, 5));
+        assert_eq!(a.manhattan_distance(&b), 4 + 7);
+        assert_eq!(a.chebyshev_distance(&b), 7);
+        assert_eq!(a.euclidean_sq(&b), 16 + 49);
+        // symmetric, and zero only to itself
+        assert_eq!(b.manhattan_distance(&a), 11);
+        assert_eq!(a.manhattan_distance(&a), 0);
+        assert_eq!(a.chebyshev_distance(&a), 0);
+        assert_eq!(a.euclidean_sq(&a), 0);
+
+        // no overflow at the extremes of i32
+        let (min, max) = (Position(i32::MIN, 0), Position(i32::MAX, 0));
+        assert_eq!(min.manhattan_distance(&max), u32::MAX);
+        assert_eq!(min.euclidean_sq(&max), (u32::MAX as u64).pow(2));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::utils::map2d::direction::Direction;
+use crate::utils::map2d::position::Position;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub positions: HashSet<Position>,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        self.positions.contains(pos)
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.positions
+            .iter()
+            .map(|pos| -> usize {
+                pos.neighbours()
+                    .iter()
+                    .filter(|neib| !self.contains(neib))
+                    .count()
+            })
+            .sum()
+    }
+
+    // The positions that have an edge of the region on their `direction` side
+    pub fn boundary(&self, direction: Direction) -> HashSet<Position> {
+        self.positions
+            .iter()
+            .copied()
+            .filter(|pos| !self.contains(&pos.step(&direction)))
+            .collect()
+    }
+
+    // Number of straight sides, found by walking along each boundary. corners() counts the same
+    // without the walk.
+    pub fn sides(&self) -> usize {
+        let mut sides = 0;
+        // easier to search as we only go straight, no flooding needed
+        for direction in Direction::iter_all() {
+            let boundary = self.boundary(direction);
+            let mut visited: HashSet<Position> = HashSet::new();
+            let search_dirs = [direction.turned_left(), direction.turned_right()];
+            for pos in &boundary {
+                if !visited.insert(*pos) {
+                    continue;
+                }
+
+                for search_dir in search_dirs {
+                    let mut search_pos = *pos;
+                    while boundary.contains(&search_pos) {
+                        visited.insert(search_pos);
+                        search_pos = search_pos.step(&search_dir);
+                    }
+                }
+
+                sides += 1;
+            }
+        }
+
+        sides
+    }
+
+    // A polygon has as many corners as it has sides. Each cell looks at its four corners: a
+    // corner is convex with both neighbours next to it outside, concave with both inside but
+    // the cell across outside.
+    pub fn corners(&self) -> usize {
+        self.positions
+            .iter()
+            .map(|pos| -> usize {
+                Direction::iter_all()
+                    .filter(|dir| {
+                        let side_dir = dir.turned_right();
+                        let (ahead, side) = (pos.step(dir), pos.step(&side_dir));
+                        let convex = !self.contains(&ahead) && !self.contains(&side);
+                        let concave = self.contains(&ahead)
+                            && self.contains(&side)
+                            && !self.contains(&ahead.step(&side_dir));
+                        convex || concave
+                    })
+                    .count()
+            })
+            .sum()
+    }
+}
+
+impl From<HashSet<Position>> for Region {
+    fn from(positions: HashSet<Position>) -> Self {
+        Region { positions }
+    }
+}
+
+impl FromIterator<Position> for Region {
+    fn from_iter<I: IntoIterator<Item = Position>>(iter: I) -> Self {
+        Region {
+            positions: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The '#' cells of a small picture
+    fn region(rows: &[&str]) -> Region {
+        rows.iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(x, _)| Position(x as i32, y as i32))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sides_and_corners() {
+        let shapes: [(&[&str], usize); 6] = [
+            (&["#"], 4),
+            (&["###", "#.#", "###"], 8),
+            // touching only at a corner
+            (&["#.", ".#"], 8),
+            (&["#####", "#....", "#####", "#....", "#####"], 12),
+            (
+                &["######", "###..#", "###..#", "#..###", "#..###", "######"],
+                12,
+            ),
+            (&["###", "#.#", "#.#", "#.."], 8),
+        ];
+        for (rows, sides) in shapes {
+            let region = region(rows);
+            assert_eq!(region.sides(), sides, "{:?}", rows);
+            assert_eq!(region.corners(), sides, "{:?}", rows);
+        }
+    }
+}
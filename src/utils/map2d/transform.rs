@@ -0,0 +1,140 @@
+use crate::utils::map2d::position::Position;
+use itertools::Itertools;
+
+// The eight symmetries of the square, acting about the origin (y axis pointing down)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Transform {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Transform {
+    pub fn all() -> [Transform; 8] {
+        [
+            Transform::Identity,
+            Transform::RotateCw,
+            Transform::Rotate180,
+            Transform::RotateCcw,
+            Transform::FlipHorizontal,
+            Transform::FlipVertical,
+            Transform::Transpose,
+            Transform::AntiTranspose,
+        ]
+    }
+
+    // (x, y) -> (a x + b y, c x + d y)
+    fn matrix(self) -> [i32; 4] {
+        match self {
+            Transform::Identity => [1, 0, 0, 1],
+            Transform::RotateCw => [0, -1, 1, 0],
+            Transform::Rotate180 => [-1, 0, 0, -1],
+            Transform::RotateCcw => [0, 1, -1, 0],
+            Transform::FlipHorizontal => [-1, 0, 0, 1],
+            Transform::FlipVertical => [1, 0, 0, -1],
+            Transform::Transpose => [0, 1, 1, 0],
+            Transform::AntiTranspose => [0, -1, -1, 0],
+        }
+    }
+
+    fn from_matrix(matrix: [i32; 4]) -> Self {
+        *Self::all()
+            .iter()
+            .find(|transform| transform.matrix() == matrix)
+            .expect("Products of square symmetries are square symmetries.")
+    }
+
+    pub fn apply(self, pos: &Position) -> Position {
+        let [a, b, c, d] = self.matrix();
+        Position(a * pos.0 + b * pos.1, c * pos.0 + d * pos.1)
+    }
+
+    // First apply self, then other
+    pub fn then(self, other: Transform) -> Transform {
+        let [a, b, c, d] = other.matrix();
+        let [e, f, g, h] = self.matrix();
+        Self::from_matrix([a * e + b * g, a * f + b * h, c * e + d * g, c * f + d * h])
+    }
+
+    pub fn inverse(self) -> Transform {
+        *Self::all()
+            .iter()
+            .find(|other| self.then(**other) == Transform::Identity)
+            .expect("Every square symmetry has an inverse.")
+    }
+}
+
+// Shift the positions so that the smallest coordinates are 0, and sort them
+pub fn normalized(positions: impl IntoIterator<Item = Position>) -> Vec<Position> {
+    let positions = positions.into_iter().collect_vec();
+    let min_x = positions.iter().map(|pos| pos.0).min().unwrap_or(0);
+    let min_y = positions.iter().map(|pos| pos.1).min().unwrap_or(0);
+    positions
+        .into_iter()
+        .map(|pos| Position(pos.0 - min_x, pos.1 - min_y))
+        .sorted()
+        .collect()
+}
+
+// The same representative for all shapes that agree up to translation, rotation and reflection
+pub fn canonical_shape(positions: &[Position]) -> Vec<Position> {
+    Transform::all()
+        .iter()
+        .map(|transform| normalized(positions.iter().map(|pos| transform.apply(pos))))
+        .min()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::map2d::position::Axis;
+
+    #[test]
+    fn test_position_transforms() {
+        let origin = Position(1, 1);
+        let pos = Position(3, 1);
+        assert_eq!(pos.rotated_cw_about(&origin), Position(1, 3));
+        assert_eq!(pos.rotated_ccw_about(&origin), Position(1, -1));
+        assert_eq!(pos.rotated_180_about(&origin), Position(-1, 1));
+        assert_eq!(pos.reflected(Axis::Vertical(0)), Position(-3, 1));
+        assert_eq!(pos.reflected(Axis::Horizontal(2)), Position(3, 3));
+        assert_eq!(
+            Transform::RotateCw.apply(&pos),
+            pos.rotated_cw_about(&Position(0, 0))
+        );
+    }
+
+    #[test]
+    fn test_composition() {
+        use Transform::*;
+        assert_eq!(RotateCw.then(RotateCw), Rotate180);
+        assert_eq!(RotateCw.then(RotateCcw), Identity);
+        assert_eq!(FlipHorizontal.then(FlipVertical), Rotate180);
+        for transform in Transform::all() {
+            assert_eq!(transform.then(transform.inverse()), Identity);
+            for other in Transform::all() {
+                let pos = Position(2, 5);
+                assert_eq!(
+                    transform.then(other).apply(&pos),
+                    other.apply(&transform.apply(&pos))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_shape() {
+        // an L-tromino in two different orientations and places
+        let l1 = [Position(0, 0), Position(0, 1), Position(1, 1)];
+        let l2 = [Position(5, 5), Position(6, 5), Position(5, 6)];
+        let line = [Position(0, 0), Position(1, 0), Position(2, 0)];
+        assert_eq!(canonical_shape(&l1), canonical_shape(&l2));
+        assert_ne!(canonical_shape(&l1), canonical_shape(&line));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::utils::map3d::position::Position3;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Bounds3(pub usize, pub usize, pub usize);
+
+#[derive(Debug, Clone)]
+pub struct Grid3<T> {
+    // indexed as data[z][y][x]
+    pub data: Vec<Vec<Vec<T>>>,
+    pub bounds: Bounds3,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ValidPosition3(pub usize, pub usize, pub usize);
+
+impl From<ValidPosition3> for Position3 {
+    fn from(pos: ValidPosition3) -> Self {
+        Position3(pos.0 as i32, pos.1 as i32, pos.2 as i32)
+    }
+}
+
+impl Bounds3 {
+    pub fn contains(&self, pos: &Position3) -> bool {
+        pos.0 >= 0
+            && pos.1 >= 0
+            && pos.2 >= 0
+            && pos.0 < self.0 as i32
+            && pos.1 < self.1 as i32
+            && pos.2 < self.2 as i32
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ValidPosition3> {
+        (0..self.0)
+            .cartesian_product(0..self.1)
+            .cartesian_product(0..self.2)
+            .map(|((x, y), z)| ValidPosition3(x, y, z))
+    }
+
+    pub fn volume(&self) -> usize {
+        self.0 * self.1 * self.2
+    }
+}
+
+impl Position3 {
+    pub fn in_bounds(&self, bounds: &Bounds3) -> Option<ValidPosition3> {
+        bounds.contains(self).then_some(ValidPosition3(
+            self.0 as usize,
+            self.1 as usize,
+            self.2 as usize,
+        ))
+    }
+
+    pub fn valid_neighbours(&self, bounds: &Bounds3) -> HashSet<ValidPosition3> {
+        self.neighbours()
+            .into_iter()
+            .filter_map(|neib| neib.in_bounds(bounds))
+            .collect()
+    }
+}
+
+impl ValidPosition3 {
+    pub fn valid_neighbours(&self, bounds: &Bounds3) -> HashSet<ValidPosition3> {
+        let pos: Position3 = (*self).into();
+        pos.valid_neighbours(bounds)
+    }
+}
+
+impl<T> Grid3<T> {
+    pub fn from_fn(bounds: Bounds3, mut f: impl FnMut(ValidPosition3) -> T) -> Self {
+        let data = (0..bounds.2)
+            .map(|z| {
+                (0..bounds.1)
+                    .map(|y| {
+                        (0..bounds.0)
+                            .map(|x| f(ValidPosition3(x, y, z)))
+                            .collect_vec()
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        Grid3 { data, bounds }
+    }
+
+    pub fn position_iter(&self) -> impl Iterator<Item = ValidPosition3> {
+        self.bounds.iter()
+    }
+
+    pub fn value(&self, pos: &ValidPosition3) -> &T {
+        &self.data[pos.2][pos.1][pos.0]
+    }
+
+    pub fn value_mut(&mut self, pos: &ValidPosition3) -> &mut T {
+        &mut self.data[pos.2][pos.1][pos.0]
+    }
+
+    // Breadth-first search over the 6-neighbourhood, only entering cells that are passable
+    pub fn distances_from(
+        &self,
+        start: ValidPosition3,
+        passable: impl Fn(&T) -> bool,
+    ) -> HashMap<ValidPosition3, usize> {
+        let mut distances: HashMap<ValidPosition3, usize> = HashMap::from([(start, 0)]);
+        let mut to_visit: VecDeque<ValidPosition3> = VecDeque::from([start]);
+
+        while let Some(pos) = to_visit.pop_front() {
+            let distance = distances[&pos];
+            for neib in pos.valid_neighbours(&self.bounds) {
+                if passable(self.value(&neib)) && !distances.contains_key(&neib) {
+                    distances.insert(neib, distance + 1);
+                    to_visit.push_back(neib);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+impl<T: Clone> Grid3<T> {
+    pub fn filled(bounds: Bounds3, value: T) -> Self {
+        Self::from_fn(bounds, |_| value.clone())
+    }
+}
+
+impl<T: PartialEq> Grid3<T> {
+    pub fn find(&self, value: &T) -> HashSet<ValidPosition3> {
+        self.position_iter()
+            .filter(|pos| self.value(pos) == value)
+            .collect()
+    }
+
+    pub fn contiguous_region(&self, &pos: &ValidPosition3) -> HashSet<ValidPosition3> {
+        let target_value = self.value(&pos);
+        self.distances_from(pos, |value| value == target_value)
+            .into_keys()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flood_fill() {
+        // a solid 3x3x3 cube with a hollow centre
+        let mut grid = Grid3::filled(Bounds3(3, 3, 3), true);
+        *grid.value_mut(&ValidPosition3(1, 1, 1)) = false;
+
+        assert_eq!(grid.find(&false).len(), 1);
+        assert_eq!(grid.contiguous_region(&ValidPosition3(0, 0, 0)).len(), 26);
+        assert_eq!(grid.contiguous_region(&ValidPosition3(1, 1, 1)).len(), 1);
+    }
+
+    #[test]
+    fn test_distances() {
+        let grid = Grid3::filled(Bounds3(4, 3, 2), ());
+        let distances = grid.distances_from(ValidPosition3(0, 0, 0), |_| true);
+        assert_eq!(distances.len(), grid.bounds.volume());
+        assert_eq!(distances[&ValidPosition3(3, 2, 1)], 6);
+        assert_eq!(
+            Position3(0, 0, 0).manhattan_distance(&Position3(3, -2, 1)),
+            6
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use crate::utils::mathnd::IntVec3D;
+use std::ops::{Add, Sub};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Position3(pub i32, pub i32, pub i32);
+
+impl Position3 {
+    pub fn neighbours(&self) -> Vec<Position3> {
+        vec![
+            Position3(self.0 + 1, self.1, self.2),
+            Position3(self.0 - 1, self.1, self.2),
+            Position3(self.0, self.1 + 1, self.2),
+            Position3(self.0, self.1 - 1, self.2),
+            Position3(self.0, self.1, self.2 + 1),
+            Position3(self.0, self.1, self.2 - 1),
+        ]
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> u32 {
+        self.0.abs_diff(other.0) + self.1.abs_diff(other.1) + self.2.abs_diff(other.2)
+    }
+}
+
+impl Add<Position3> for Position3 {
+    type Output = Position3;
+
+    fn add(self, rhs: Position3) -> Self::Output {
+        Position3(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+    }
+}
+
+impl Sub<Position3> for Position3 {
+    type Output = Position3;
+
+    fn sub(self, rhs: Position3) -> Self::Output {
+        Position3(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
+    }
+}
+
+impl Add<IntVec3D<i32>> for Position3 {
+    type Output = Position3;
+
+    fn add(self, rhs: IntVec3D<i32>) -> Self::Output {
+        Position3(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+    }
+}
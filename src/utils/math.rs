@@ -0,0 +1,110 @@
+use num::{integer::ExtendedGcd, Integer};
+
+pub mod diophantine;
+pub mod matrix;
+pub mod modint;
+pub mod primes;
+pub mod stats;
+
+// Always non-negative, and gcd(0, 0) = 0
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    a.gcd(&b)
+}
+
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    a.lcm(&b)
+}
+
+// (g, x, y) with a * x + b * y = g = gcd(a, b)
+pub fn extended_gcd<T: Integer + Clone>(a: T, b: T) -> (T, T, T) {
+    let ExtendedGcd { gcd, x, y, .. } = a.extended_gcd(&b);
+    (gcd, x, y)
+}
+
+// Merge x = r1 mod m1 and x = r2 mod m2 into a single congruence x = r mod lcm(m1, m2)
+fn merge_congruences((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let (gcd, x, _) = extended_gcd(m1, m2);
+    let difference = r2 - r1;
+    if difference % gcd != 0 {
+        return None;
+    }
+    // m1 * x = gcd mod m2, so r1 + m1 * x * difference / gcd solves both
+    let m2_reduced = m2 / gcd;
+    let t = (difference / gcd % m2_reduced * (x % m2_reduced)).mod_floor(&m2_reduced);
+    let lcm = m1 * m2_reduced;
+    Some(((r1 + m1 * t).mod_floor(&lcm), lcm))
+}
+
+fn crt_with(residues: &[u128], moduli: &[u128], allow_common_factors: bool) -> Option<u128> {
+    assert_eq!(
+        residues.len(),
+        moduli.len(),
+        "Need exactly one residue per modulus."
+    );
+    residues
+        .iter()
+        .zip(moduli)
+        .map(|(&residue, &modulus)| {
+            let modulus = i128::try_from(modulus).expect("Modulus too large.");
+            assert!(modulus > 0, "Moduli must be positive.");
+            (i128::try_from(residue % modulus as u128).unwrap(), modulus)
+        })
+        .try_fold((0, 1), |merged, congruence| {
+            if !allow_common_factors && gcd(merged.1, congruence.1) != 1 {
+                return None;
+            }
+            merge_congruences(merged, congruence)
+        })
+        .map(|(residue, _)| residue as u128)
+}
+
+// The smallest x >= 0 with x = residues[i] mod moduli[i] for all i, or None if there is none.
+// Moduli may share factors.
+pub fn crt(residues: &[u128], moduli: &[u128]) -> Option<u128> {
+    crt_with(residues, moduli, true)
+}
+
+// As crt, but returns None unless the moduli are pairwise coprime
+pub fn crt_coprime(residues: &[u128], moduli: &[u128]) -> Option<u128> {
+    crt_with(residues, moduli, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(20u64, 5), 5);
+        assert_eq!(gcd(5u64, 20), 5);
+        assert_eq!(gcd(0u64, 8), 8);
+        assert_eq!(gcd(3824i64, 218), 2);
+        assert_eq!(gcd(-91i128, 26), 13);
+        assert_eq!(lcm(4u64, 6), 12);
+        assert_eq!(lcm(-4i64, 6), 12);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240i64, 46), (-7, 3), (0, 5), (12, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[2, 3, 2], &[3, 5, 7]), Some(23));
+        assert_eq!(crt_coprime(&[2, 3, 2], &[3, 5, 7]), Some(23));
+        // robots realigning on a 101 x 103 torus
+        let t = crt(&[12, 34], &[101, 103]).unwrap();
+        assert_eq!((t % 101, t % 103), (12, 34));
+        assert!(t < 101 * 103);
+        // common factors
+        assert_eq!(crt(&[1, 3], &[4, 6]), Some(9));
+        assert_eq!(crt(&[1, 2], &[4, 6]), None);
+        assert_eq!(crt_coprime(&[1, 3], &[4, 6]), None);
+        assert_eq!(crt(&[], &[]), Some(0));
+    }
+}
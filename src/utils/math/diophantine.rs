@@ -0,0 +1,101 @@
+use crate::utils::math::extended_gcd;
+use num::{Integer, Signed};
+use std::cmp::{max, min, Ordering};
+
+// All integer solutions of a * x + b * y = c: (x0 + k * step_x, y0 + k * step_y) for integer k
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Solutions<T> {
+    pub x0: T,
+    pub y0: T,
+    pub step_x: T,
+    pub step_y: T,
+}
+
+// None if there is no solution - or infinitely many in both variables, i.e. a = b = 0
+pub fn solve<T: Integer + Signed + Copy>(a: T, b: T, c: T) -> Option<Solutions<T>> {
+    let (gcd, x, y) = extended_gcd(a, b);
+    if gcd.is_zero() || !(c % gcd).is_zero() {
+        return None;
+    }
+    let scale = c / gcd;
+    Some(Solutions {
+        x0: x * scale,
+        y0: y * scale,
+        step_x: b / gcd,
+        step_y: -a / gcd,
+    })
+}
+
+impl<T: Integer + Signed + Copy> Solutions<T> {
+    pub fn at(&self, k: T) -> (T, T) {
+        (self.x0 + k * self.step_x, self.y0 + k * self.step_y)
+    }
+
+    // The range of k (None meaning unbounded) for which x >= 0 and y >= 0,
+    // or None if there is no such k
+    pub fn non_negative_range(&self) -> Option<(Option<T>, Option<T>)> {
+        let mut lowest: Option<T> = None;
+        let mut highest: Option<T> = None;
+        for (start, step) in [(self.x0, self.step_x), (self.y0, self.step_y)] {
+            match step.cmp(&T::zero()) {
+                Ordering::Greater => {
+                    let bound = Integer::div_ceil(&-start, &step);
+                    lowest = Some(lowest.map_or(bound, |lowest| max(lowest, bound)));
+                }
+                Ordering::Less => {
+                    let bound = Integer::div_floor(&start, &-step);
+                    highest = Some(highest.map_or(bound, |highest| min(highest, bound)));
+                }
+                Ordering::Equal if start.is_negative() => return None,
+                Ordering::Equal => (),
+            }
+        }
+        match (lowest, highest) {
+            (Some(lowest), Some(highest)) if lowest > highest => None,
+            range => Some(range),
+        }
+    }
+
+    // The non-negative solution minimising cost_x * x + cost_y * y, if there is a minimum
+    pub fn minimize(&self, cost_x: T, cost_y: T) -> Option<(T, T)> {
+        let (lowest, highest) = self.non_negative_range()?;
+        let slope = cost_x * self.step_x + cost_y * self.step_y;
+        let k = match slope.cmp(&T::zero()) {
+            Ordering::Greater => lowest?,
+            Ordering::Less => highest?,
+            Ordering::Equal => lowest.or(highest).unwrap_or(T::zero()),
+        };
+        Some(self.at(k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let solutions = solve(6, 10, 8).unwrap();
+        for k in -5..5 {
+            let (x, y) = solutions.at(k);
+            assert_eq!(6 * x + 10 * y, 8);
+        }
+        assert_eq!(solve(6, 10, 7), None);
+        assert_eq!(solve(0, 0, 0), None);
+        assert_eq!(solve(0, 3, 6).unwrap().at(0).1, 2);
+    }
+
+    #[test]
+    fn test_minimize() {
+        // 3x + 2y = 7: only (1, 2) is non-negative
+        assert_eq!(solve(3, 2, 7).unwrap().minimize(3, 1), Some((1, 2)));
+        // x + 2y = 10
+        assert_eq!(solve(1, 2, 10).unwrap().minimize(3, 1), Some((0, 5)));
+        assert_eq!(solve(1, 2, 10).unwrap().minimize(1, 3), Some((10, 0)));
+        // 2x + 3y = 1 has no non-negative solutions
+        assert_eq!(solve(2, 3, 1).unwrap().non_negative_range(), None);
+        // x - y = 0 is unbounded, so there is a minimum only if the cost increases along it
+        assert_eq!(solve(1, -1, 0).unwrap().minimize(1, 1), Some((0, 0)));
+        assert_eq!(solve(1, -1, 0).unwrap().minimize(-1, 0), None);
+    }
+}
@@ -0,0 +1,136 @@
+use num::{One, Zero};
+use std::ops::{Add, Index, IndexMut, Mul};
+
+// Dense matrix over any semiring-like T, e.g. u64 or ModInt, stored row by row
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Copy + Zero + One + Add<Output = T> + Mul<Output = T>> Matrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![T::zero(); rows * cols],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zeros(size, size);
+        for i in 0..size {
+            matrix[(i, i)] = T::one();
+        }
+        matrix
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "All rows must have the same length."
+        );
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn mul_vec(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(self.cols, vector.len(), "Dimension mismatch.");
+        (0..self.rows)
+            .map(|row| {
+                (0..self.cols).fold(T::zero(), |acc, col| acc + self[(row, col)] * vector[col])
+            })
+            .collect()
+    }
+
+    // Square-and-multiply: O(n^3 log exponent)
+    pub fn pow(&self, mut exponent: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "Only square matrices have powers.");
+        let mut base = self.clone();
+        let mut result = Self::identity(self.rows);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            // squaring only when needed keeps plain integer powers from overflowing early
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl<T: Copy + Zero + One + Add<Output = T> + Mul<Output = T>> Mul<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: &Matrix<T>) -> Self::Output {
+        assert_eq!(self.cols, rhs.rows, "Dimension mismatch.");
+        let mut product = Matrix::zeros(self.rows, rhs.cols);
+        for row in 0..self.rows {
+            for k in 0..self.cols {
+                let factor = self[(row, k)];
+                for col in 0..rhs.cols {
+                    product[(row, col)] = product[(row, col)] + factor * rhs[(k, col)];
+                }
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::math::modint::ModInt;
+
+    #[test]
+    fn test_fibonacci() {
+        let step: Matrix<u64> = Matrix::from_rows(vec![vec![1, 1], vec![1, 0]]);
+        assert_eq!(step.pow(0), Matrix::identity(2));
+        assert_eq!(step.pow(10)[(0, 1)], 55);
+        assert_eq!(step.pow(90).mul_vec(&[1, 0])[1], 2880067194370816120);
+    }
+
+    #[test]
+    fn test_modular_recurrence() {
+        // a(n) = 2 a(n-1) + 3 a(n-2), a(0) = 0, a(1) = 1, so a(n) = (3^n - (-1)^n) / 4
+        type M = ModInt<1_000_000_007>;
+        let step = Matrix::from_rows(vec![vec![M::new(2), M::new(3)], vec![M::new(1), M::new(0)]]);
+        let a_n = step.pow(1_000_000).mul_vec(&[M::new(1), M::new(0)])[1];
+        let expected = (M::new(3).pow(1_000_000) - M::new(1)) * M::new(4).inverse().unwrap();
+        assert_eq!(a_n, expected);
+    }
+
+    #[test]
+    fn test_rectangular_product() {
+        let a: Matrix<u64> = Matrix::from_rows(vec![vec![1, 2, 3]]);
+        let b = Matrix::from_rows(vec![vec![4], vec![5], vec![6]]);
+        assert_eq!((&a * &b)[(0, 0)], 32);
+        assert_eq!((&b * &a).shape(), (3, 3));
+    }
+}
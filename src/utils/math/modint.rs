@@ -0,0 +1,156 @@
+use crate::utils::math::extended_gcd;
+use num::{Integer, One, Zero};
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+// An integer modulo M, always stored reduced to 0..M
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: u64) -> Self {
+        ModInt(value % M)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn pow(self, mut exponent: u64) -> Self {
+        let mut base = self;
+        let mut result = ModInt::new(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    // Exists iff the value is coprime to M
+    pub fn inverse(self) -> Option<Self> {
+        let (gcd, x, _) = extended_gcd(self.0 as i128, M as i128);
+        (gcd == 1).then(|| ModInt(x.mod_floor(&(M as i128)) as u64))
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        ModInt((value as i128).mod_floor(&(M as i128)) as u64)
+    }
+}
+
+impl<const M: u64> Zero for ModInt<M> {
+    fn zero() -> Self {
+        ModInt(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<const M: u64> One for ModInt<M> {
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const M: u64> Add<ModInt<M>> for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn add(self, rhs: ModInt<M>) -> Self::Output {
+        ModInt(((self.0 as u128 + rhs.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Sub<ModInt<M>> for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn sub(self, rhs: ModInt<M>) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl<const M: u64> Mul<ModInt<M>> for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn mul(self, rhs: ModInt<M>) -> Self::Output {
+        ModInt((self.0 as u128 * rhs.0 as u128 % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn neg(self) -> Self::Output {
+        ModInt((M - self.0) % M)
+    }
+}
+
+impl<const M: u64> AddAssign<ModInt<M>> for ModInt<M> {
+    fn add_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const M: u64> SubAssign<ModInt<M>> for ModInt<M> {
+    fn sub_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const M: u64> MulAssign<ModInt<M>> for ModInt<M> {
+    fn mul_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Mod7 = ModInt<7>;
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(Mod7::new(5) + Mod7::new(4), Mod7::new(2));
+        assert_eq!(Mod7::new(2) - Mod7::new(5), Mod7::new(4));
+        assert_eq!(Mod7::new(3) * Mod7::new(5), Mod7::new(1));
+        assert_eq!(-Mod7::new(0), Mod7::new(0));
+        assert_eq!(Mod7::from(-1i64), Mod7::new(6));
+        assert_eq!(Mod7::new(3).pow(6), Mod7::new(1));
+    }
+
+    #[test]
+    fn test_no_overflow() {
+        type Big = ModInt<{ u64::MAX - 58 }>;
+        let a = Big::new(u64::MAX - 60);
+        assert_eq!(a + a, Big::new(u64::MAX - 62));
+        assert_eq!(a * a, Big::new(4));
+    }
+
+    #[test]
+    fn test_inverse() {
+        assert_eq!(Mod7::new(3).inverse(), Some(Mod7::new(5)));
+        assert_eq!(ModInt::<16777216>::new(0).inverse(), None);
+        assert_eq!(ModInt::<10>::new(4).inverse(), None);
+        assert_eq!(ModInt::<10>::new(3).inverse(), Some(ModInt::new(7)));
+    }
+}
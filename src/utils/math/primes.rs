@@ -0,0 +1,162 @@
+use itertools::Itertools;
+
+// All primes <= limit, by the sieve of Eratosthenes
+pub fn sieve(limit: usize) -> Vec<usize> {
+    let mut is_prime = vec![true; limit + 1];
+    for n in (2..).take_while(|n| n * n <= limit) {
+        if is_prime[n] {
+            for multiple in (n * n..=limit).step_by(n) {
+                is_prime[multiple] = false;
+            }
+        }
+    }
+    (2..=limit).filter(|&n| is_prime[n]).collect()
+}
+
+// base^exponent mod modulus, multiplying in u128 so nothing overflows
+fn pow_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let (mut base, mut result) = (base as u128 % modulus as u128, 1u128);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus as u128;
+        }
+        base = base * base % modulus as u128;
+        exponent >>= 1;
+    }
+    result as u64
+}
+
+// Miller-Rabin with the first twelve primes as bases, which is exact for every u64
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&base) = BASES.iter().find(|&&base| n.is_multiple_of(base)) {
+        return n == base;
+    }
+    // n - 1 = d * 2^s with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod(base, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = (x as u128 * x as u128 % n as u128) as u64;
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+// Prime factors with multiplicities, in increasing order; empty for n <= 1. Trial division,
+// stopping as soon as what is left is prime. That only changes when a factor is divided out,
+// so only then is it tested again.
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    if is_prime(n) {
+        return vec![(n, 1)];
+    }
+    let mut divisor = 2;
+    while divisor <= n / divisor {
+        let mut multiplicity = 0;
+        while n.is_multiple_of(divisor) {
+            n /= divisor;
+            multiplicity += 1;
+        }
+        if multiplicity > 0 {
+            factors.push((divisor, multiplicity));
+            if is_prime(n) {
+                break;
+            }
+        }
+        divisor += if divisor == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+// All positive divisors, in increasing order
+pub fn divisors(n: u64) -> Vec<u64> {
+    if n == 0 {
+        return Vec::new();
+    }
+    factorize(n)
+        .into_iter()
+        .fold(vec![1], |divisors, (prime, multiplicity)| {
+            divisors
+                .iter()
+                .flat_map(|divisor| (0..=multiplicity).map(move |k| divisor * prime.pow(k)))
+                .collect()
+        })
+        .into_iter()
+        .sorted()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sieve() {
+        assert_eq!(sieve(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(sieve(1), Vec::<usize>::new());
+        assert_eq!(sieve(10_000).len(), 1229);
+        let primes = sieve(10_000);
+        assert!((0..=10_000).all(|n| is_prime(n as u64) == primes.contains(&n)));
+        assert!(!is_prime(1) && !is_prime(91) && is_prime(16777213));
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(101 * 103), vec![(101, 1), (103, 1)]);
+        assert_eq!(factorize(16777216), vec![(2, 24)]);
+
+        // two six-digit primes: half a million trial divisions without a factor
+        let (p, q) = (999_979, 999_983);
+        assert!(is_prime(p) && is_prime(q));
+        assert_eq!(factorize(p * q), vec![(p, 1), (q, 1)]);
+        assert_eq!(factorize(p * p), vec![(p, 2)]);
+    }
+
+    #[test]
+    fn test_near_u64_max() {
+        // the largest prime below 2^64
+        let prime = u64::MAX - 58;
+        assert!(is_prime(prime));
+        assert!(!is_prime(u64::MAX) && !is_prime(prime - 2));
+        assert_eq!(factorize(prime), vec![(prime, 1)]);
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6700417, 1)
+            ]
+        );
+        // 2^32 - 5 is prime; its square is just below 2^64
+        let square = (u32::MAX - 4) as u64 * (u32::MAX - 4) as u64;
+        assert!(!is_prime(square));
+    }
+
+    #[test]
+    fn test_divisors() {
+        assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(13), vec![1, 13]);
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(360).len(), 24);
+    }
+}
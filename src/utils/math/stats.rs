@@ -0,0 +1,75 @@
+use crate::utils::mathnd::IntVec2D;
+use num::{Integer, ToPrimitive};
+use std::{collections::HashMap, hash::Hash};
+
+// None for an empty input
+pub fn mean<T: ToPrimitive>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    let (count, sum) = values
+        .into_iter()
+        .fold((0usize, 0.0), |(count, sum), value| {
+            (
+                count + 1,
+                sum + value.to_f64().expect("Value not representable as f64."),
+            )
+        });
+    (count > 0).then(|| sum / count as f64)
+}
+
+// Population variance; None for an empty input
+pub fn variance<T: ToPrimitive>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    let values: Vec<f64> = values
+        .into_iter()
+        .map(|value| value.to_f64().expect("Value not representable as f64."))
+        .collect();
+    let mean = mean(values.iter().copied())?;
+    self::mean(values.iter().map(|value| (value - mean) * (value - mean)))
+}
+
+// Variance of the x and y coordinates separately
+pub fn variance_2d<T: Integer + ToPrimitive + Copy>(
+    positions: impl IntoIterator<Item = IntVec2D<T>>,
+) -> Option<(f64, f64)> {
+    let (xs, ys): (Vec<T>, Vec<T>) = positions.into_iter().map(|v| (v.0, v.1)).unzip();
+    Some((variance(xs)?, variance(ys)?))
+}
+
+// Shannon entropy in bits of the empirical distribution of the values
+pub fn entropy<T: Hash + Eq>(values: impl IntoIterator<Item = T>) -> f64 {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    let total: usize = counts.values().sum();
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_variance() {
+        assert_eq!(mean([1, 2, 3, 4]), Some(2.5));
+        assert_eq!(mean(Vec::<i32>::new()), None);
+        assert_eq!(variance([2, 4, 4, 4, 5, 5, 7, 9]), Some(4.0));
+        assert_eq!(variance([7u64]), Some(0.0));
+        assert_eq!(
+            variance_2d([IntVec2D(0, 5), IntVec2D(2, 5)]),
+            Some((1.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy(Vec::<u8>::new()), 0.0);
+        assert_eq!(entropy(['a', 'a', 'a']), 0.0);
+        assert_eq!(entropy(['a', 'b', 'a', 'b']), 1.0);
+        assert_eq!(entropy(0..8), 3.0);
+    }
+}
@@ -0,0 +1,578 @@
+use crate::utils::math::gcd;
+use num::{
+    rational::Ratio,
+    traits::{CheckedAdd, CheckedMul, CheckedSub, SaturatingAdd, SaturatingMul, SaturatingSub},
+    Integer, Signed,
+};
+use std::{
+    hash::Hash,
+    iter::Sum,
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct IntVec2D<T: Integer>(pub T, pub T);
+
+impl<T: Integer> Add<IntVec2D<T>> for IntVec2D<T> {
+    type Output = IntVec2D<T>;
+    fn add(self, rhs: IntVec2D<T>) -> Self::Output {
+        IntVec2D(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl<T: Integer> Sub<IntVec2D<T>> for IntVec2D<T> {
+    type Output = IntVec2D<T>;
+    fn sub(self, rhs: IntVec2D<T>) -> Self::Output {
+        IntVec2D(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl<T: Integer + Copy> Mul<T> for IntVec2D<T> {
+    type Output = IntVec2D<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        IntVec2D(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl<T: Integer + Copy> Div<T> for IntVec2D<T> {
+    type Output = IntVec2D<T>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        IntVec2D(self.0 / rhs, self.1 / rhs)
+    }
+}
+
+impl<T: Integer + Copy> Mul<IntVec2D<T>> for IntVec2D<T> {
+    type Output = IntVec2D<T>;
+
+    // componentwise
+    fn mul(self, rhs: IntVec2D<T>) -> Self::Output {
+        IntVec2D(self.0 * rhs.0, self.1 * rhs.1)
+    }
+}
+
+impl<T: Integer + Signed> Neg for IntVec2D<T> {
+    type Output = IntVec2D<T>;
+
+    fn neg(self) -> Self::Output {
+        IntVec2D(-self.0, -self.1)
+    }
+}
+
+impl<T: Integer + Copy> AddAssign<IntVec2D<T>> for IntVec2D<T> {
+    fn add_assign(&mut self, rhs: IntVec2D<T>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Integer + Copy> SubAssign<IntVec2D<T>> for IntVec2D<T> {
+    fn sub_assign(&mut self, rhs: IntVec2D<T>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Integer + Copy> MulAssign<T> for IntVec2D<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T: Integer> Sum for IntVec2D<T> {
+    fn sum<I: Iterator<Item = IntVec2D<T>>>(iter: I) -> Self {
+        iter.fold(IntVec2D(T::zero(), T::zero()), |acc, v| acc + v)
+    }
+}
+
+// Overflow-aware variants, mainly for i128/u128 coordinates
+impl<T: Integer + Copy + CheckedAdd + CheckedSub + CheckedMul> IntVec2D<T> {
+    pub fn checked_add(self, rhs: IntVec2D<T>) -> Option<IntVec2D<T>> {
+        Some(IntVec2D(
+            self.0.checked_add(&rhs.0)?,
+            self.1.checked_add(&rhs.1)?,
+        ))
+    }
+
+    pub fn checked_sub(self, rhs: IntVec2D<T>) -> Option<IntVec2D<T>> {
+        Some(IntVec2D(
+            self.0.checked_sub(&rhs.0)?,
+            self.1.checked_sub(&rhs.1)?,
+        ))
+    }
+
+    pub fn checked_mul(self, rhs: T) -> Option<IntVec2D<T>> {
+        Some(IntVec2D(
+            self.0.checked_mul(&rhs)?,
+            self.1.checked_mul(&rhs)?,
+        ))
+    }
+
+    pub fn checked_dot(self, rhs: IntVec2D<T>) -> Option<T> {
+        self.0
+            .checked_mul(&rhs.0)?
+            .checked_add(&self.1.checked_mul(&rhs.1)?)
+    }
+
+    pub fn checked_l2_sq(self) -> Option<T> {
+        self.checked_dot(self)
+    }
+}
+
+impl<T: Integer + Copy + SaturatingAdd + SaturatingSub + SaturatingMul> IntVec2D<T> {
+    pub fn saturating_add(self, rhs: IntVec2D<T>) -> IntVec2D<T> {
+        IntVec2D(self.0.saturating_add(&rhs.0), self.1.saturating_add(&rhs.1))
+    }
+
+    pub fn saturating_sub(self, rhs: IntVec2D<T>) -> IntVec2D<T> {
+        IntVec2D(self.0.saturating_sub(&rhs.0), self.1.saturating_sub(&rhs.1))
+    }
+
+    pub fn saturating_mul(self, rhs: T) -> IntVec2D<T> {
+        IntVec2D(self.0.saturating_mul(&rhs), self.1.saturating_mul(&rhs))
+    }
+}
+
+// scalar * vector; the orphan rule forces one impl per primitive
+macro_rules! impl_scalar_mul {
+    ($($t:ty),*) => {
+        $(
+            impl Mul<IntVec2D<$t>> for $t {
+                type Output = IntVec2D<$t>;
+
+                fn mul(self, rhs: IntVec2D<$t>) -> Self::Output {
+                    rhs * self
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_mul!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<T: Integer + Copy> IntVec2D<T> {
+    pub fn dot(self, rhs: IntVec2D<T>) -> T {
+        self.0 * rhs.0 + self.1 * rhs.1
+    }
+
+    pub fn l2_sq(self) -> T {
+        self.0 * self.0 + self.1 * self.1
+    }
+
+    #[deprecated(note = "renamed to l2_sq")]
+    pub fn norm_sq(self) -> T {
+        self.l2_sq()
+    }
+
+    // Rotations are clockwise as drawn with the y axis pointing down, as in map2d
+    pub fn rotated_cw(self) -> IntVec2D<T> {
+        IntVec2D(T::zero() - self.1, self.0)
+    }
+
+    pub fn rotated_ccw(self) -> IntVec2D<T> {
+        IntVec2D(self.1, T::zero() - self.0)
+    }
+
+    // Satisfies v.perp().dot(w) == determinant of the columns v, w
+    pub fn perp(self) -> IntVec2D<T> {
+        IntVec2D(T::zero() - self.1, self.0)
+    }
+}
+
+impl<T: Integer + Signed + Copy> IntVec2D<T> {
+    // componentwise, so e.g. a unit step towards a target along each axis
+    pub fn signum(self) -> IntVec2D<T> {
+        IntVec2D(self.0.signum(), self.1.signum())
+    }
+
+    // The shortest lattice vector in the same direction; zero stays zero
+    pub fn primitive(self) -> IntVec2D<T> {
+        match gcd(self.0, self.1) {
+            divisor if divisor.is_zero() => self,
+            divisor => self / divisor,
+        }
+    }
+
+    pub fn manhattan(self) -> T {
+        self.0.abs() + self.1.abs()
+    }
+
+    pub fn chebyshev(self) -> T {
+        std::cmp::max(self.0.abs(), self.1.abs())
+    }
+}
+
+impl<T: Integer> From<(T, T)> for IntVec2D<T> {
+    fn from((x, y): (T, T)) -> Self {
+        IntVec2D(x, y)
+    }
+}
+
+// 2x2 integer matrix, stored as its two columns
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Mat2<T: Integer>(pub IntVec2D<T>, pub IntVec2D<T>);
+
+impl<T: Integer + Copy> Mul<IntVec2D<T>> for Mat2<T> {
+    type Output = IntVec2D<T>;
+
+    fn mul(self, rhs: IntVec2D<T>) -> Self::Output {
+        self.0 * rhs.0 + self.1 * rhs.1
+    }
+}
+
+impl<T: Integer + Copy> Mul<Mat2<T>> for Mat2<T> {
+    type Output = Mat2<T>;
+
+    fn mul(self, rhs: Mat2<T>) -> Self::Output {
+        Mat2(self * rhs.0, self * rhs.1)
+    }
+}
+
+impl<T: Integer + Copy> Mat2<T> {
+    pub fn identity() -> Self {
+        Mat2(IntVec2D(T::one(), T::zero()), IntVec2D(T::zero(), T::one()))
+    }
+
+    pub fn determinant(&self) -> T {
+        self.0 .0 * self.1 .1 - self.1 .0 * self.0 .1
+    }
+
+    // determinant * inverse - exists for singular matrices, too
+    pub fn adjugate(&self) -> Self {
+        let zero = T::zero();
+        Mat2(
+            IntVec2D(self.1 .1, zero - self.0 .1),
+            IntVec2D(zero - self.1 .0, self.0 .0),
+        )
+    }
+
+    // Only integer matrices with determinant +-1 have an integer inverse
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        (determinant == T::one() || determinant == T::zero() - T::one()).then(|| {
+            let adjugate = self.adjugate();
+            Mat2(adjugate.0 * determinant, adjugate.1 * determinant)
+        })
+    }
+
+    // The unique solution x of self * x = b, if the matrix is invertible
+    pub fn solve_rational(&self, b: IntVec2D<T>) -> Option<(Ratio<T>, Ratio<T>)> {
+        let determinant = self.determinant();
+        if determinant == T::zero() {
+            return None;
+        }
+        let IntVec2D(x, y) = self.adjugate() * b;
+        Some((Ratio::new(x, determinant), Ratio::new(y, determinant)))
+    }
+
+    // The unique solution x of self * x = b, if the matrix is invertible and x is integral
+    pub fn solve(&self, b: IntVec2D<T>) -> Option<IntVec2D<T>> {
+        let (x, y) = self.solve_rational(b)?;
+        (x.is_integer() && y.is_integer()).then(|| IntVec2D(x.to_integer(), y.to_integer()))
+    }
+}
+
+// All lattice points on the segment from..=to, in order
+pub fn line_points<T: Integer + Signed + Copy>(
+    from: IntVec2D<T>,
+    to: IntVec2D<T>,
+) -> impl Iterator<Item = IntVec2D<T>> {
+    let step = (to - from).primitive();
+    std::iter::successors(Some(from), move |&point| {
+        (point != to).then(|| point + step)
+    })
+}
+
+// Exact 2D vector with rational coordinates, e.g. for intersection points
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct RatVec2D<T: Integer + Clone>(pub Ratio<T>, pub Ratio<T>);
+
+impl<T: Integer + Copy> From<IntVec2D<T>> for RatVec2D<T> {
+    fn from(IntVec2D(x, y): IntVec2D<T>) -> Self {
+        RatVec2D(Ratio::from(x), Ratio::from(y))
+    }
+}
+
+impl<T: Integer + Copy> Add<RatVec2D<T>> for RatVec2D<T> {
+    type Output = RatVec2D<T>;
+    fn add(self, rhs: RatVec2D<T>) -> Self::Output {
+        RatVec2D(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl<T: Integer + Copy> Sub<RatVec2D<T>> for RatVec2D<T> {
+    type Output = RatVec2D<T>;
+    fn sub(self, rhs: RatVec2D<T>) -> Self::Output {
+        RatVec2D(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl<T: Integer + Copy> Mul<Ratio<T>> for RatVec2D<T> {
+    type Output = RatVec2D<T>;
+
+    fn mul(self, rhs: Ratio<T>) -> Self::Output {
+        RatVec2D(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl<T: Integer + Copy> RatVec2D<T> {
+    // Some if both coordinates are whole numbers
+    pub fn to_integer(self) -> Option<IntVec2D<T>> {
+        (self.0.is_integer() && self.1.is_integer())
+            .then(|| IntVec2D(self.0.to_integer(), self.1.to_integer()))
+    }
+}
+
+// The points point + s * direction for rational s
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Line<T: Integer> {
+    pub point: IntVec2D<T>,
+    pub direction: IntVec2D<T>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Intersection<T: Integer + Clone> {
+    // the point and the parameters s, t along both lines at which it is reached
+    Point(RatVec2D<T>, Ratio<T>, Ratio<T>),
+    Parallel,
+    Coincident,
+}
+
+impl<T: Integer + Copy> Line<T> {
+    pub fn through(from: IntVec2D<T>, to: IntVec2D<T>) -> Self {
+        Line {
+            point: from,
+            direction: to - from,
+        }
+    }
+
+    pub fn at(&self, s: Ratio<T>) -> RatVec2D<T> {
+        RatVec2D::from(self.point) + RatVec2D::from(self.direction) * s
+    }
+
+    pub fn contains(&self, point: IntVec2D<T>) -> bool {
+        Mat2(self.direction, point - self.point).determinant() == T::zero()
+    }
+
+    pub fn intersection(&self, other: &Line<T>) -> Intersection<T> {
+        // point + s * direction = other.point + t * other.direction
+        let zero = IntVec2D(T::zero(), T::zero());
+        match Mat2(self.direction, zero - other.direction).solve_rational(other.point - self.point)
+        {
+            Some((s, t)) => Intersection::Point(self.at(s), s, t),
+            None if self.contains(other.point) => Intersection::Coincident,
+            None => Intersection::Parallel,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct IntVec3D<T: Integer>(pub T, pub T, pub T);
+
+impl<T: Integer> Add<IntVec3D<T>> for IntVec3D<T> {
+    type Output = IntVec3D<T>;
+    fn add(self, rhs: IntVec3D<T>) -> Self::Output {
+        IntVec3D(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+    }
+}
+
+impl<T: Integer> Sub<IntVec3D<T>> for IntVec3D<T> {
+    type Output = IntVec3D<T>;
+    fn sub(self, rhs: IntVec3D<T>) -> Self::Output {
+        IntVec3D(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
+    }
+}
+
+impl<T: Integer + Copy> Mul<T> for IntVec3D<T> {
+    type Output = IntVec3D<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        IntVec3D(self.0 * rhs, self.1 * rhs, self.2 * rhs)
+    }
+}
+
+impl<T: Integer + Copy> Div<T> for IntVec3D<T> {
+    type Output = IntVec3D<T>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        IntVec3D(self.0 / rhs, self.1 / rhs, self.2 / rhs)
+    }
+}
+
+impl<T: Integer + Copy> IntVec3D<T> {
+    pub fn dot(self, rhs: IntVec3D<T>) -> T {
+        self.0 * rhs.0 + self.1 * rhs.1 + self.2 * rhs.2
+    }
+
+    pub fn cross(self, rhs: IntVec3D<T>) -> IntVec3D<T> {
+        IntVec3D(
+            self.1 * rhs.2 - self.2 * rhs.1,
+            self.2 * rhs.0 - self.0 * rhs.2,
+            self.0 * rhs.1 - self.1 * rhs.0,
+        )
+    }
+
+    pub fn l2_sq(self) -> T {
+        self.0 * self.0 + self.1 * self.1 + self.2 * self.2
+    }
+
+    #[deprecated(note = "renamed to l2_sq")]
+    pub fn norm_sq(self) -> T {
+        self.l2_sq()
+    }
+}
+
+impl<T: Integer> From<(T, T, T)> for IntVec3D<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        IntVec3D(x, y, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operators() {
+        let mut v = IntVec2D(3, -2);
+        assert_eq!(-v, IntVec2D(-3, 2));
+        assert_eq!(2 * v, v * 2);
+        assert_eq!(v * IntVec2D(4, 5), IntVec2D(12, -10));
+        v += IntVec2D(1, 1);
+        assert_eq!(v, IntVec2D(4, -1));
+        v -= IntVec2D(2, 2);
+        assert_eq!(v, IntVec2D(2, -3));
+        v *= 3;
+        assert_eq!(v, IntVec2D(6, -9));
+        let total: IntVec2D<u8> = [IntVec2D(1, 2), IntVec2D(3, 4)].into_iter().sum();
+        assert_eq!(total, IntVec2D(4, 6));
+    }
+
+    #[test]
+    fn test_overflow_variants() {
+        let big = IntVec2D(i128::MAX / 2, 1);
+        assert_eq!(big.checked_add(big), Some(IntVec2D(i128::MAX - 1, 2)));
+        assert_eq!(big.checked_mul(3), None);
+        assert_eq!(big.checked_dot(big), None);
+        assert_eq!(IntVec2D(3i128, 4).checked_l2_sq(), Some(25));
+        assert_eq!(big.saturating_mul(3), IntVec2D(i128::MAX, 3));
+
+        let unsigned = IntVec2D(1u128, u128::MAX);
+        assert_eq!(unsigned.checked_sub(IntVec2D(2, 0)), None);
+        assert_eq!(unsigned.checked_add(IntVec2D(0, 1)), None);
+        assert_eq!(
+            unsigned.saturating_sub(IntVec2D(2, 0)),
+            IntVec2D(0, u128::MAX)
+        );
+        assert_eq!(unsigned.saturating_add(unsigned), IntVec2D(2, u128::MAX));
+        assert_eq!(2u128 * IntVec2D(1u128, 5), IntVec2D(2, 10));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let v = IntVec2D(3, -2);
+        assert_eq!(IntVec2D(1, 0).rotated_cw(), IntVec2D(0, 1));
+        assert_eq!(v.rotated_cw().rotated_ccw(), v);
+        assert_eq!(v.rotated_cw().rotated_cw(), IntVec2D(-3, 2));
+        assert_eq!(v.perp().dot(v), 0);
+        let w = IntVec2D(5, 7);
+        assert_eq!(v.perp().dot(w), Mat2(v, w).determinant());
+        assert_eq!(v.signum(), IntVec2D(1, -1));
+        assert_eq!(IntVec2D(0, 9).signum(), IntVec2D(0, 1));
+    }
+
+    #[test]
+    fn test_line_points() {
+        assert_eq!(IntVec2D(6, -4).primitive(), IntVec2D(3, -2));
+        assert_eq!(IntVec2D(0, -4).primitive(), IntVec2D(0, -1));
+        assert_eq!(
+            line_points(IntVec2D(1, 1), IntVec2D(7, -3)).collect::<Vec<_>>(),
+            vec![IntVec2D(1, 1), IntVec2D(4, -1), IntVec2D(7, -3)]
+        );
+        assert_eq!(line_points(IntVec2D(0, 0), IntVec2D(0, 3)).count(), 4);
+        assert_eq!(
+            line_points(IntVec2D(2, 5), IntVec2D(2, 5)).collect::<Vec<_>>(),
+            vec![IntVec2D(2, 5)]
+        );
+    }
+
+    #[test]
+    fn test_norms() {
+        let v = IntVec2D(3, -7);
+        assert_eq!(v.manhattan(), 10);
+        assert_eq!(v.chebyshev(), 7);
+        assert_eq!(v.l2_sq(), 58);
+        assert_eq!(IntVec2D(0, 0).manhattan(), 0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_norm_sq_alias() {
+        assert_eq!(IntVec2D(3, -7).norm_sq(), 58);
+        assert_eq!(IntVec3D(2, 4, 6).norm_sq(), 56);
+    }
+
+    #[test]
+    fn test_line_intersection() {
+        let diagonal = Line::through(IntVec2D(0, 0), IntVec2D(2, 2));
+        let anti_diagonal = Line::through(IntVec2D(0, 1), IntVec2D(1, 0));
+        let half = Ratio::new(1, 2);
+        assert_eq!(
+            diagonal.intersection(&anti_diagonal),
+            Intersection::Point(RatVec2D(half, half), Ratio::new(1, 4), half)
+        );
+        assert_eq!(RatVec2D(half, half).to_integer(), None);
+
+        let shifted = Line {
+            point: IntVec2D(1, 0),
+            direction: IntVec2D(5, 5),
+        };
+        assert_eq!(diagonal.intersection(&shifted), Intersection::Parallel);
+        let same = Line::through(IntVec2D(-3, -3), IntVec2D(7, 7));
+        assert_eq!(diagonal.intersection(&same), Intersection::Coincident);
+    }
+
+    #[test]
+    fn test_mat2() {
+        let m = Mat2(IntVec2D(94, 34), IntVec2D(22, 67));
+        assert_eq!(m.determinant(), 94 * 67 - 22 * 34);
+        assert_eq!(m * IntVec2D(80, 40), IntVec2D(8400, 5400));
+        assert_eq!(m.solve(IntVec2D(8400, 5400)), Some(IntVec2D(80, 40)));
+        assert_eq!(m.solve(IntVec2D(8401, 5400)), None);
+        assert_eq!(
+            m.solve_rational(IntVec2D(94, 34)),
+            Some((Ratio::from(1), Ratio::from(0)))
+        );
+        assert_eq!(m.inverse(), None);
+
+        let singular = Mat2(IntVec2D(1, 2), IntVec2D(2, 4));
+        assert_eq!(singular.solve(IntVec2D(3, 6)), None);
+
+        let shear = Mat2(IntVec2D(1, 0), IntVec2D(3, 1));
+        let inverse = shear.inverse().unwrap();
+        assert_eq!(shear * inverse, Mat2::identity());
+        let det = m.determinant();
+        assert_eq!(m.adjugate() * m, Mat2(IntVec2D(det, 0), IntVec2D(0, det)));
+    }
+
+    #[test]
+    fn test_cross() {
+        let x = IntVec3D(1, 0, 0);
+        let y = IntVec3D(0, 1, 0);
+        assert_eq!(x.cross(y), IntVec3D(0, 0, 1));
+        assert_eq!(y.cross(x), IntVec3D(0, 0, -1));
+
+        let a = IntVec3D(2, -3, 5);
+        let b = IntVec3D(-1, 4, 7);
+        assert_eq!(a.cross(b).dot(a), 0);
+        assert_eq!(a.cross(b).dot(b), 0);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = IntVec3D(2, 4, 6);
+        assert_eq!(a + IntVec3D(1, 1, 1) - IntVec3D(3, 5, 7), IntVec3D(0, 0, 0));
+        assert_eq!(a * 3 / 2, IntVec3D(3, 6, 9));
+        assert_eq!(a.l2_sq(), 56);
+        assert_eq!(IntVec3D::from((1, 2, 3)), IntVec3D(1, 2, 3));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::utils::graph::{paths::bfs, scc, DiGraph};
+use itertools::Itertools;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    error::Error,
+    fmt::{self, Debug, Display},
+    hash::Hash,
+};
+
+// Rules of the form "a comes before b", as in day 5's page ordering. Only the rules themselves
+// are checked, not what follows from them: day 5's rules form cycles as a whole and are only
+// consistent on the pages of a single update, so the transitive closure is usually taken of
+// the rules restricted to a sequence.
+#[derive(Debug, Clone)]
+pub struct PartialOrderRules<T: Hash + Eq> {
+    graph: DiGraph<T>,
+}
+
+// Items of a sequence that the rules put in a cycle, so no order satisfies them all
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContradictoryRules<T>(pub Vec<Vec<T>>);
+
+impl<T: Display> Display for ContradictoryRules<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cycles = self
+            .0
+            .iter()
+            .map(|items| items.iter().join(", "))
+            .join("; ");
+        write!(f, "contradictory rules among {}", cycles)
+    }
+}
+
+impl<T: Debug + Display> Error for ContradictoryRules<T> {}
+
+impl<T: Hash + Eq> Default for PartialOrderRules<T> {
+    fn default() -> Self {
+        PartialOrderRules {
+            graph: DiGraph::default(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> PartialOrderRules<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns false if the rule was already there
+    pub fn add_rule(&mut self, before: T, after: T) -> bool {
+        self.graph.add_edge(before, after)
+    }
+
+    // False only if a rule puts `after` first
+    pub fn allows(&self, before: &T, after: &T) -> bool {
+        !self.graph.contains_edge(after, before)
+    }
+
+    pub fn is_sorted(&self, sequence: &[T]) -> bool {
+        sequence
+            .iter()
+            .tuple_combinations()
+            .all(|(before, after)| self.allows(before, after))
+    }
+
+    // Every item named by a rule
+    pub fn items(&self) -> impl Iterator<Item = &T> {
+        self.graph.nodes()
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = (&T, &T)> {
+        self.graph.nodes().flat_map(|before| {
+            self.graph
+                .successors(before)
+                .map(move |after| (before, after))
+        })
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    // The rules between the given items only
+    pub fn restricted_to(&self, items: &[T]) -> Self {
+        let mut restricted = Self::new();
+        for before in items {
+            for after in self.graph.successors(before) {
+                if items.contains(after) {
+                    restricted.add_rule(before.clone(), after.clone());
+                }
+            }
+        }
+        restricted
+    }
+
+    // A rule for every pair of items connected by a chain of rules; an item in a cycle gets a
+    // rule with itself
+    pub fn transitive_closure(&self) -> Self {
+        let mut closure = Self::new();
+        for before in self.graph.nodes() {
+            for next in self.graph.successors(before) {
+                for after in bfs(&self.graph, next).into_keys() {
+                    closure.add_rule(before.clone(), after);
+                }
+            }
+        }
+        closure
+    }
+}
+
+impl<T: Hash + Eq + Clone + Ord> PartialOrderRules<T> {
+    // Sorts topologically, using only the rules between items of the sequence. Of the items
+    // free to go next, the one that came first goes first, so a sorted sequence stays as it
+    // is; repeated items end up next to each other.
+    pub fn sort(&self, sequence: &mut Vec<T>) -> Result<(), ContradictoryRules<T>> {
+        let graph = self.restricted_to(sequence).graph;
+        let items = sequence.iter().unique().cloned().collect_vec();
+        let index_of: HashMap<&T, usize> = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item, i))
+            .collect();
+
+        let mut rules_left = items.iter().map(|item| graph.in_degree(item)).collect_vec();
+        let mut free: BinaryHeap<Reverse<usize>> = (0..items.len())
+            .filter(|&i| rules_left[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(items.len());
+        while let Some(Reverse(i)) = free.pop() {
+            order.push(i);
+            for after in graph.successors(&items[i]) {
+                let j = index_of[after];
+                rules_left[j] -= 1;
+                if rules_left[j] == 0 {
+                    free.push(Reverse(j));
+                }
+            }
+        }
+
+        if order.len() < items.len() {
+            let cycles = scc::cycles(&graph)
+                .into_iter()
+                .map(|component| component.into_iter().sorted().collect_vec())
+                .sorted()
+                .collect_vec();
+            return Err(ContradictoryRules(cycles));
+        }
+
+        let counts = sequence.iter().cloned().counts();
+        *sequence = order
+            .into_iter()
+            .flat_map(|i| itertools::repeat_n(items[i].clone(), counts[&items[i]]))
+            .collect();
+        Ok(())
+    }
+}
+
+impl<T: Hash + Eq + Clone> FromIterator<(T, T)> for PartialOrderRules<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(rules: I) -> Self {
+        PartialOrderRules {
+            graph: rules.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A pseudo-random number below `bound`
+    fn next(state: &mut u64, bound: usize) -> usize {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (*state >> 33) as usize % bound
+    }
+
+    // Rules between the numbers below n, all agreeing with one hidden order, and a shuffled
+    // selection of those numbers
+    fn random_case(state: &mut u64, n: usize) -> (PartialOrderRules<usize>, Vec<usize>) {
+        let mut hidden: Vec<usize> = (0..n).collect();
+        for i in (1..n).rev() {
+            hidden.swap(i, next(state, i + 1));
+        }
+        let rules = hidden
+            .iter()
+            .tuple_combinations()
+            .filter(|_| next(state, 3) == 0)
+            .map(|(&before, &after)| (before, after))
+            .collect();
+        let sequence = (0..next(state, n + 1)).map(|_| next(state, n)).collect();
+        (rules, sequence)
+    }
+
+    #[test]
+    fn test_sort_properties() {
+        let mut state = 5;
+        for _ in 0..200 {
+            let (rules, sequence) = random_case(&mut state, 12);
+            let mut sorted = sequence.clone();
+            assert_eq!(rules.sort(&mut sorted), Ok(()));
+
+            // respects all rules, and keeps the same items
+            assert!(rules.is_sorted(&sorted), "{:?}", sorted);
+            assert!(rules
+                .rules()
+                .filter(|(before, after)| sorted.contains(before) && sorted.contains(after))
+                .all(|(before, after)| {
+                    sorted.iter().rposition(|item| item == before)
+                        < sorted.iter().position(|item| item == after)
+                }));
+            assert_eq!(
+                sorted.iter().sorted().collect_vec(),
+                sequence.iter().sorted().collect_vec()
+            );
+
+            // idempotent
+            let mut again = sorted.clone();
+            assert_eq!(rules.sort(&mut again), Ok(()));
+            assert_eq!(again, sorted);
+        }
+    }
+
+    #[test]
+    fn test_transitive_closure() {
+        let rules: PartialOrderRules<u32> = [(1, 2), (2, 3), (3, 4)].into_iter().collect();
+        assert!(rules.allows(&3, &1));
+        let closure = rules.transitive_closure();
+        assert_eq!(closure.rule_count(), 6);
+        assert!(!closure.allows(&3, &1) && closure.allows(&1, &4));
+        assert!(closure.is_sorted(&[1, 3, 4]));
+        assert!(!closure.is_sorted(&[4, 1]));
+
+        let cycle: PartialOrderRules<u32> = [(1, 2), (2, 1), (2, 3)].into_iter().collect();
+        let closure = cycle.transitive_closure();
+        assert!(!closure.allows(&1, &1) && !closure.allows(&2, &2));
+        assert!(closure.allows(&3, &3));
+        assert_eq!(closure.rule_count(), 6);
+    }
+
+    #[test]
+    fn test_contradictory_rules() {
+        let rules: PartialOrderRules<u32> = [(1, 2), (2, 3), (3, 1), (4, 4), (5, 1)]
+            .into_iter()
+            .collect();
+
+        let error = rules.sort(&mut vec![5, 3, 2, 1]).unwrap_err();
+        assert_eq!(error, ContradictoryRules(vec![vec![1, 2, 3]]));
+        assert_eq!(error.to_string(), "contradictory rules among 1, 2, 3");
+        let boxed: Box<dyn Error> = Box::new(error);
+        assert_eq!(boxed.to_string(), "contradictory rules among 1, 2, 3");
+        assert_eq!(
+            rules.sort(&mut vec![4, 5]),
+            Err(ContradictoryRules(vec![vec![4]]))
+        );
+
+        // the cycle only matters with all of its items in the sequence
+        let mut sequence = vec![1, 3, 5];
+        assert_eq!(rules.sort(&mut sequence), Ok(()));
+        assert_eq!(sequence, vec![3, 5, 1]);
+        assert_eq!(rules.restricted_to(&sequence).rule_count(), 2);
+    }
+}
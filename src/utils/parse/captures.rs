@@ -0,0 +1,127 @@
+use regex::{Captures, Regex};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CaptureError {
+    InvalidPattern(String),
+    NoMatch,
+    WrongGroupCount { expected: usize, found: usize },
+    Parse { group: usize, text: String },
+}
+
+// Types that can be built from the capture groups of a match, e.g. tuples of FromStr fields
+pub trait FromCaptures: Sized {
+    fn from_captures(captures: &Captures) -> Result<Self, CaptureError>;
+}
+
+fn group<T: FromStr>(captures: &Captures, group: usize) -> Result<T, CaptureError> {
+    let text = captures.get(group).map_or("", |capture| capture.as_str());
+    text.parse().map_err(|_| CaptureError::Parse {
+        group,
+        text: text.into(),
+    })
+}
+
+macro_rules! impl_from_captures {
+    ($count:literal: $($t:ident $group:literal),*) => {
+        impl<$($t: FromStr),*> FromCaptures for ($($t,)*) {
+            fn from_captures(captures: &Captures) -> Result<Self, CaptureError> {
+                // group 0 is the whole match
+                if captures.len() != $count + 1 {
+                    return Err(CaptureError::WrongGroupCount {
+                        expected: $count,
+                        found: captures.len() - 1,
+                    });
+                }
+                Ok(($(group::<$t>(captures, $group)?,)*))
+            }
+        }
+    };
+}
+
+impl_from_captures!(1: A 1);
+impl_from_captures!(2: A 1, B 2);
+impl_from_captures!(3: A 1, B 2, C 3);
+impl_from_captures!(4: A 1, B 2, C 3, D 4);
+impl_from_captures!(5: A 1, B 2, C 3, D 4, E 5);
+impl_from_captures!(6: A 1, B 2, C 3, D 4, E 5, F 6);
+
+// Compiled patterns are cached, as days typically apply the same pattern to every line
+fn compiled(pattern: &str) -> Result<Regex, CaptureError> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Regex>>> = OnceLock::new();
+    let mut cache = CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Regex cache poisoned.");
+    if let Some(regex) = cache.get(pattern) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(pattern).map_err(|err| CaptureError::InvalidPattern(err.to_string()))?;
+    cache.insert(pattern.into(), regex.clone());
+    Ok(regex)
+}
+
+// The groups of the first match of pattern in haystack
+pub fn captures_as<T: FromCaptures>(haystack: &str, pattern: &str) -> Result<T, CaptureError> {
+    let regex = compiled(pattern)?;
+    let captures = regex.captures(haystack).ok_or(CaptureError::NoMatch)?;
+    T::from_captures(&captures)
+}
+
+// The groups of every non-overlapping match, in order
+pub fn all_captures_as<T: FromCaptures>(
+    haystack: &str,
+    pattern: &str,
+) -> Result<Vec<T>, CaptureError> {
+    let regex = compiled(pattern)?;
+    regex
+        .captures_iter(haystack)
+        .map(|captures| T::from_captures(&captures))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captures_as() {
+        let (name, a, b): (String, u32, i64) =
+            captures_as("Button A: X+94, Y-34", r"Button (\w): X\+(\d+), Y(-?\d+)").unwrap();
+        assert_eq!((name.as_str(), a, b), ("A", 94, -34));
+
+        assert_eq!(
+            captures_as::<(u8,)>("Register A: 729", r"Register A: (\d+)"),
+            Err(CaptureError::Parse {
+                group: 1,
+                text: "729".into()
+            })
+        );
+        assert_eq!(
+            captures_as::<(u32,)>("nothing", r"Register A: (\d+)"),
+            Err(CaptureError::NoMatch)
+        );
+        assert_eq!(
+            captures_as::<(u32, u32)>("Register A: 729", r"Register A: (\d+)"),
+            Err(CaptureError::WrongGroupCount {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert!(matches!(
+            captures_as::<(u32,)>("", r"("),
+            Err(CaptureError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_all_captures_as() {
+        let products: Vec<(u32, u32)> =
+            all_captures_as("mul(2,4)%&mul[3,7]!mul(5,5)", r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
+        assert_eq!(products, vec![(2, 4), (5, 5)]);
+    }
+}
@@ -0,0 +1,193 @@
+use std::{fmt::Display, str::FromStr};
+
+// What was expected, and how much input was left at that point
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub expected: String,
+    pub remaining: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} ({} bytes before the end)",
+            self.expected, self.remaining
+        )
+    }
+}
+
+// The parsed value and the input that is left over
+pub type ParseResult<'a, T> = Result<(T, &'a str), ParseError>;
+
+pub trait Parser<T> {
+    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, T>;
+}
+
+impl<T, F> Parser<T> for F
+where
+    F: for<'a> Fn(&'a str) -> ParseResult<'a, T>,
+{
+    fn parse<'a>(&self, input: &'a str) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+// Pins closures to the higher-ranked signature, which inference does not find on its own
+fn parser<T, F>(f: F) -> F
+where
+    F: for<'a> Fn(&'a str) -> ParseResult<'a, T>,
+{
+    f
+}
+
+fn expected(what: impl Into<String>, input: &str) -> ParseError {
+    ParseError {
+        expected: what.into(),
+        remaining: input.len(),
+    }
+}
+
+pub fn tag(literal: &'static str) -> impl Parser<&'static str> {
+    parser(move |input| match input.strip_prefix(literal) {
+        Some(rest) => Ok((literal, rest)),
+        None => Err(expected(format!("{:?}", literal), input)),
+    })
+}
+
+// An optionally negative decimal integer, e.g. "-3" in "v=3,-3"
+pub fn number<T: FromStr>() -> impl Parser<T> {
+    parser(|input: &str| {
+        let sign = usize::from(input.starts_with('-'));
+        let digits = input[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len() - sign);
+        if digits == 0 {
+            return Err(expected("a number", input));
+        }
+        let (token, rest) = input.split_at(sign + digits);
+        match token.parse() {
+            Ok(value) => Ok((value, rest)),
+            Err(_) => Err(expected(
+                format!("a number fitting the type, not {}", token),
+                input,
+            )),
+        }
+    })
+}
+
+pub fn map<A, B>(inner: impl Parser<A>, f: impl Fn(A) -> B) -> impl Parser<B> {
+    parser(move |input| {
+        let (value, rest) = inner.parse(input)?;
+        Ok((f(value), rest))
+    })
+}
+
+pub fn pair<A, B>(first: impl Parser<A>, second: impl Parser<B>) -> impl Parser<(A, B)> {
+    parser(move |input| {
+        let (a, rest) = first.parse(input)?;
+        let (b, rest) = second.parse(rest)?;
+        Ok(((a, b), rest))
+    })
+}
+
+// inner, after a prefix that is thrown away
+pub fn preceded<P, T>(prefix: impl Parser<P>, inner: impl Parser<T>) -> impl Parser<T> {
+    parser(move |input| {
+        let (_, rest) = prefix.parse(input)?;
+        inner.parse(rest)
+    })
+}
+
+// inner, between an opening and closing parser that are thrown away, e.g. mul( ... )
+pub fn delimited<O, T, C>(
+    open: impl Parser<O>,
+    inner: impl Parser<T>,
+    close: impl Parser<C>,
+) -> impl Parser<T> {
+    parser(move |input| {
+        let (_, rest) = open.parse(input)?;
+        let (value, rest) = inner.parse(rest)?;
+        let (_, rest) = close.parse(rest)?;
+        Ok((value, rest))
+    })
+}
+
+// One or more items with a separator between them, e.g. "75,47,61"
+pub fn separated_list<T, S>(
+    item: impl Parser<T>,
+    separator: impl Parser<S>,
+) -> impl Parser<Vec<T>> {
+    parser(move |input| {
+        let (first, mut rest) = item.parse(input)?;
+        let mut items = vec![first];
+        // a separator without an item behind it is left unconsumed
+        while let Ok((_, after_separator)) = separator.parse(rest) {
+            let Ok((next, after_item)) = item.parse(after_separator) else {
+                break;
+            };
+            items.push(next);
+            rest = after_item;
+        }
+        Ok((items, rest))
+    })
+}
+
+// Runs the parser on the whole input; anything left over is an error
+pub fn parse_all<T>(parser: &impl Parser<T>, input: &str) -> Result<T, ParseError> {
+    match parser.parse(input)? {
+        (value, "") => Ok(value),
+        (_, rest) => Err(expected("end of input", rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(tag("p=").parse("p=0,4"), Ok(("p=", "0,4")));
+        assert_eq!(number::<i32>().parse("-3 v"), Ok((-3, " v")));
+        assert_eq!(
+            number::<u8>().parse("300"),
+            Err(ParseError {
+                expected: String::from("a number fitting the type, not 300"),
+                remaining: 3
+            })
+        );
+        assert!(number::<i32>().parse("-x").is_err());
+        assert!(tag("v=").parse("p=").is_err());
+    }
+
+    #[test]
+    fn test_robot_line() {
+        let vector = || pair(number::<i32>(), preceded(tag(","), number::<i32>()));
+        let robot = pair(
+            preceded(tag("p="), vector()),
+            preceded(tag(" v="), vector()),
+        );
+        assert_eq!(parse_all(&robot, "p=0,4 v=3,-3"), Ok(((0, 4), (3, -3))));
+    }
+
+    #[test]
+    fn test_lists() {
+        let update = separated_list(number::<u32>(), tag(","));
+        assert_eq!(parse_all(&update, "75,47,61"), Ok(vec![75, 47, 61]));
+        assert_eq!(update.parse("1,2,"), Ok((vec![1, 2], ",")));
+
+        let mul = delimited(
+            tag("mul("),
+            pair(number::<i32>(), preceded(tag(","), number::<i32>())),
+            tag(")"),
+        );
+        assert_eq!(mul.parse("mul(2,4)do()"), Ok(((2, 4), "do()")));
+        assert_eq!(
+            parse_all(&mul, "mul(2,4]"),
+            Err(ParseError {
+                expected: String::from("\")\""),
+                remaining: 1
+            })
+        );
+    }
+}
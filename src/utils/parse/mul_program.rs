@@ -0,0 +1,134 @@
+use crate::utils::parse::combinators::{
+    delimited, map, pair, preceded, tag, ParseError, ParseResult, Parser,
+};
+
+// The corrupted memory from day 3: instructions scattered between garbage. Scanning picks out
+// the instructions, and a Machine runs them, keeping its state from one line to the next.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
+
+// One to three digits, no sign
+fn operand(input: &str) -> ParseResult<'_, i64> {
+    let digits = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    if !(1..=3).contains(&digits) {
+        return Err(ParseError {
+            expected: String::from("one to three digits"),
+            remaining: input.len(),
+        });
+    }
+    let (token, rest) = input.split_at(digits);
+    Ok((token.parse().expect("Digits should parse."), rest))
+}
+
+// An instruction right at the start of the input
+fn instruction(input: &str) -> ParseResult<'_, Instruction> {
+    let mul = map(
+        delimited(
+            tag("mul("),
+            pair(operand, preceded(tag(","), operand)),
+            tag(")"),
+        ),
+        |(a, b)| Instruction::Mul(a, b),
+    );
+    mul.parse(input)
+        .or_else(|_| map(tag("don't()"), |_| Instruction::Dont).parse(input))
+        .or_else(|_| map(tag("do()"), |_| Instruction::Do).parse(input))
+}
+
+// Every instruction in order, skipping whatever is not one
+pub fn instructions(memory: &str) -> impl Iterator<Item = Instruction> + '_ {
+    let mut rest = memory;
+    std::iter::from_fn(move || {
+        while let Some(next_char) = rest.chars().next() {
+            match instruction(rest) {
+                Ok((instruction, after)) => {
+                    rest = after;
+                    return Some(instruction);
+                }
+                Err(_) => rest = &rest[next_char.len_utf8()..],
+            }
+        }
+        None
+    })
+}
+
+// Adds up the products of the muls it runs. With conditionals, don't() switches the muls off
+// until the next do(); without, both are ignored.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    conditionals: bool,
+    enabled: bool,
+    total: i64,
+}
+
+impl Machine {
+    pub fn new(conditionals: bool) -> Self {
+        Machine {
+            conditionals,
+            enabled: true,
+            total: 0,
+        }
+    }
+
+    pub fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Mul(a, b) if self.enabled => self.total += a * b,
+            Instruction::Mul(..) => (),
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = !self.conditionals,
+        }
+    }
+
+    pub fn run(&mut self, instructions: impl IntoIterator<Item = Instruction>) {
+        instructions
+            .into_iter()
+            .for_each(|instruction| self.execute(instruction));
+    }
+
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Instruction::*;
+
+    #[test]
+    fn test_instructions() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        assert_eq!(
+            instructions(memory).collect::<Vec<_>>(),
+            vec![Mul(2, 4), Dont, Mul(5, 5), Mul(11, 8), Do, Mul(8, 5)]
+        );
+        assert_eq!(
+            instructions("mul(1234,5)mul(12,345)mul(-1,2)mul( 1,2)").collect::<Vec<_>>(),
+            vec![Mul(12, 345)]
+        );
+        assert_eq!(
+            instructions("mmul(1,2)dodon't()ümul(3,4)").collect::<Vec<_>>(),
+            vec![Mul(1, 2), Dont, Mul(3, 4)]
+        );
+    }
+
+    #[test]
+    fn test_machine() {
+        let mut machine = Machine::new(true);
+        machine.run(instructions("mul(2,3)don't()"));
+        // still switched off on the next line
+        machine.run(instructions("mul(5,5)do()mul(1,4)"));
+        assert_eq!(machine.total(), 6 + 4);
+
+        let mut machine = Machine::new(false);
+        machine.run([Dont, Mul(5, 5), Do, Mul(1, 4)]);
+        assert_eq!(machine.total(), 25 + 4);
+    }
+}
@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::num::{IntErrorKind, ParseIntError};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NumberError {
+    Missing,
+    Invalid(String),
+    Negative(String),
+    Overflow(String),
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberError::Missing => write!(f, "number not found"),
+            NumberError::Invalid(word) => write!(f, "'{}' is not a number", word),
+            NumberError::Negative(word) => write!(f, "'{}' is negative", word),
+            NumberError::Overflow(word) => write!(f, "'{}' is out of range", word),
+        }
+    }
+}
+
+impl Error for NumberError {}
+
+pub fn parse_number<T: FromStr<Err = ParseIntError>>(word: &str) -> Result<T, NumberError> {
+    let word = word.trim();
+    if word.is_empty() {
+        return Err(NumberError::Missing);
+    }
+
+    word.parse().map_err(|err: ParseIntError| match err.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => NumberError::Overflow(word.into()),
+        // unsigned types reject the sign as an invalid digit
+        _ if word.starts_with('-') && word[1..].chars().all(|c| c.is_ascii_digit()) => {
+            NumberError::Negative(word.into())
+        }
+        _ => NumberError::Invalid(word.into()),
+    })
+}
+
+// Signed integers in order of appearance: a '-' only counts if a digit follows it
+pub fn number_tokens(haystack: &str) -> impl Iterator<Item = &str> {
+    let bytes = haystack.as_bytes();
+    let mut idx = 0;
+    std::iter::from_fn(move || {
+        while idx < bytes.len() {
+            let start = idx;
+            let is_sign = bytes[idx] == b'-' && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit);
+            if is_sign || bytes[idx].is_ascii_digit() {
+                idx += 1;
+                while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+                    idx += 1;
+                }
+                return Some(&haystack[start..idx]);
+            }
+            idx += 1;
+        }
+        None
+    })
+}
+
+pub fn numbers_in<T: FromStr<Err = ParseIntError>>(haystack: &str) -> Result<Vec<T>, NumberError> {
+    number_tokens(haystack).map(parse_number).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_tokens() {
+        let tokens: Vec<&str> = number_tokens("p=0,4 v=3,-3").collect();
+        assert_eq!(tokens, vec!["0", "4", "3", "-3"]);
+        let tokens: Vec<&str> = number_tokens("a-b--12x7-").collect();
+        assert_eq!(tokens, vec!["-12", "7"]);
+        assert_eq!(number_tokens("no numbers here").count(), 0);
+    }
+
+    #[test]
+    fn test_numbers_in() {
+        assert_eq!(numbers_in::<i32>("p=0,4 v=3,-3"), Ok(vec![0, 4, 3, -3]));
+        assert_eq!(numbers_in::<u8>("0,1,5,4,3,0"), Ok(vec![0, 1, 5, 4, 3, 0]));
+        assert_eq!(
+            numbers_in::<usize>("3,-1"),
+            Err(NumberError::Negative("-1".into()))
+        );
+        assert_eq!(
+            numbers_in::<u8>("256"),
+            Err(NumberError::Overflow("256".into()))
+        );
+        assert_eq!(
+            numbers_in::<i8>("-129"),
+            Err(NumberError::Overflow("-129".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number::<u64>(" 729 "), Ok(729));
+        assert_eq!(parse_number::<u64>(""), Err(NumberError::Missing));
+        assert_eq!(
+            parse_number::<u64>("7a"),
+            Err(NumberError::Invalid("7a".into()))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(NumberError::Missing.to_string(), "number not found");
+        assert_eq!(
+            NumberError::Invalid("7a".into()).to_string(),
+            "'7a' is not a number"
+        );
+        assert_eq!(
+            NumberError::Negative("-1".into()).to_string(),
+            "'-1' is negative"
+        );
+        let error: Box<dyn Error> = Box::new(NumberError::Overflow("256".into()));
+        assert_eq!(error.to_string(), "'256' is out of range");
+    }
+}
@@ -0,0 +1,154 @@
+use crate::utils::{
+    file_io::{self, AocError},
+    map2d::grid::{Bounds, ValidPosition},
+    mathnd::IntVec2D,
+    parse::combinators::{map, number, pair, parse_all, preceded, tag, Parser},
+};
+use num::Integer;
+
+// The robots from day 14: each moves in a straight line at its own speed, wrapping around the
+// edges of a torus. After a period of the width and height's lcm, all of them are back where
+// they started.
+
+pub type Number = i32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Robot {
+    pub pos: IntVec2D<Number>,
+    pub vel: IntVec2D<Number>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RobotSwarm {
+    robots: Vec<Robot>,
+    torus: IntVec2D<Number>,
+    time: Number,
+}
+
+// e.g. "0,4"
+fn vector() -> impl Parser<IntVec2D<Number>> {
+    map(pair(number(), preceded(tag(","), number())), IntVec2D::from)
+}
+
+impl RobotSwarm {
+    pub fn new(robots: Vec<Robot>, torus: Bounds) -> Self {
+        RobotSwarm {
+            robots,
+            torus: IntVec2D(torus.0 as Number, torus.1 as Number),
+            time: 0,
+        }
+    }
+
+    // One robot per line, e.g. "p=0,4 v=3,-3"
+    pub fn from_file(path: &str, torus: Bounds) -> Result<Self, AocError> {
+        let robot = map(
+            pair(
+                preceded(tag("p="), vector()),
+                preceded(tag(" v="), vector()),
+            ),
+            |(pos, vel)| Robot { pos, vel },
+        );
+        let robots = file_io::parse_lines_with(path, |line| parse_all(&robot, line))?;
+        Ok(RobotSwarm::new(robots, torus))
+    }
+
+    pub fn torus(&self) -> Bounds {
+        Bounds(self.torus.0 as usize, self.torus.1 as usize)
+    }
+
+    pub fn robots(&self) -> &[Robot] {
+        &self.robots
+    }
+
+    pub fn period(&self) -> Number {
+        self.torus.0.lcm(&self.torus.1)
+    }
+
+    // Seconds since the start, up to the period
+    pub fn time(&self) -> Number {
+        self.time
+    }
+
+    // Backwards for negative seconds
+    pub fn advance(&mut self, seconds: Number) {
+        let seconds = seconds.mod_floor(&self.period());
+        for robot in &mut self.robots {
+            robot.pos = Self::wrap(self.torus, robot.pos + robot.vel * seconds);
+        }
+        self.time = (self.time + seconds) % self.period();
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = ValidPosition> + '_ {
+        self.positions_after(0)
+    }
+
+    // Where the robots will be, leaving them where they are
+    pub fn positions_after(&self, seconds: Number) -> impl Iterator<Item = ValidPosition> + '_ {
+        let seconds = seconds.mod_floor(&self.period());
+        self.robots.iter().map(move |robot| {
+            let IntVec2D(x, y) = Self::wrap(self.torus, robot.pos + robot.vel * seconds);
+            ValidPosition(x as usize, y as usize)
+        })
+    }
+
+    // The product of the robot counts in the four quadrants; robots on the middle lines count
+    // for none
+    pub fn safety_factor(&self) -> usize {
+        let Bounds(width, height) = self.torus();
+        let mut quadrants = [0; 4];
+        for ValidPosition(x, y) in self.positions() {
+            if x == width / 2 || y == height / 2 {
+                continue;
+            }
+            quadrants[(x > width / 2) as usize + 2 * (y > height / 2) as usize] += 1;
+        }
+        quadrants.iter().product()
+    }
+
+    fn wrap(torus: IntVec2D<Number>, pos: IntVec2D<Number>) -> IntVec2D<Number> {
+        IntVec2D(pos.0.mod_floor(&torus.0), pos.1.mod_floor(&torus.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::test_input;
+
+    #[test]
+    fn test_safety_factor() {
+        let mut swarm = RobotSwarm::from_file(&test_input(14, 1), Bounds(11, 7)).unwrap();
+        swarm.advance(100);
+        assert_eq!(swarm.safety_factor(), 12);
+        assert_eq!(swarm.time(), 100 - 77);
+    }
+
+    #[test]
+    fn test_period() {
+        let swarm = RobotSwarm::from_file(&test_input(14, 1), Bounds(11, 7)).unwrap();
+        assert_eq!(swarm.period(), 77);
+
+        let start: Vec<ValidPosition> = swarm.positions().collect();
+        let mut later = swarm.clone();
+        later.advance(3 * 77 + 5);
+        assert_eq!(later.time(), 5);
+        assert_eq!(
+            later.positions().collect::<Vec<_>>(),
+            swarm.positions_after(5).collect::<Vec<_>>()
+        );
+        later.advance(-5);
+        assert_eq!(later.positions().collect::<Vec<_>>(), start);
+        assert_eq!(later.time(), 0);
+    }
+
+    #[test]
+    fn test_single_robot() {
+        // from the puzzle: p=2,4 v=2,-3 is at 1,3 after 5 seconds
+        let robot = Robot {
+            pos: IntVec2D(2, 4),
+            vel: IntVec2D(2, -3),
+        };
+        let swarm = RobotSwarm::new(vec![robot], Bounds(11, 7));
+        assert_eq!(swarm.positions_after(5).next(), Some(ValidPosition(1, 3)));
+    }
+}
@@ -0,0 +1,428 @@
+use crate::utils::{
+    cli::{Extension, Extensions},
+    map2d::grid::{Bounds, Grid},
+};
+use image::{Rgb, ToColor};
+use itertools::{Itertools, MinMaxResult};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub mod image;
+pub mod mermaid;
+pub mod overlay;
+pub mod terminal;
+
+// Looking at puzzle state as it changes, for debugging and for fun: frames in the terminal,
+// images and animations.
+
+// Where to write an animation of the simulation, e.g. `cargo run --bin day15 -- --record out.gif`
+pub const RECORD: Extension = Extension {
+    name: "record",
+    default: "",
+};
+
+// The --record path, if one was given
+pub fn record_path() -> Option<String> {
+    let path: String = Extensions::from_args(&[RECORD]).get("record");
+    (!path.is_empty()).then_some(path)
+}
+
+// A directory to write every step of the simulation to, e.g.
+// `cargo run --bin day14 -- --dump-frames frames/`
+pub const DUMP_FRAMES: Extension = Extension {
+    name: "dump-frames",
+    default: "",
+};
+
+// How the dumped frames are written: "txt", "png" or "svg"
+pub const FRAME_FORMAT: Extension = Extension {
+    name: "frame-format",
+    default: "txt",
+};
+
+// The --dump-frames directory, if one was given
+pub fn dump_dir() -> Option<String> {
+    let dir: String = Extensions::from_args(&[DUMP_FRAMES]).get("dump-frames");
+    (!dir.is_empty()).then_some(dir)
+}
+
+// Writes frames of a simulation to numbered files in a directory, one call per step:
+// frame00000.txt, frame00001.txt and so on, or pictures of them
+pub struct FrameDump {
+    dir: PathBuf,
+    format: String,
+    scale: usize,
+    count: usize,
+}
+
+impl FrameDump {
+    // Creates the directory if needed. Pictures get `scale` by `scale` pixels per cell.
+    pub fn new(dir: impl AsRef<Path>, format: &str, scale: usize) -> io::Result<Self> {
+        if !["txt", "png", "svg"].contains(&format) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown frame format '{}'", format),
+            ));
+        }
+        fs::create_dir_all(&dir)?;
+        Ok(FrameDump {
+            dir: dir.as_ref().into(),
+            format: format.into(),
+            scale,
+            count: 0,
+        })
+    }
+
+    // The path of the file written
+    pub fn dump(
+        &mut self,
+        frame: &Grid<char>,
+        color: impl Fn(&char) -> Rgb,
+    ) -> io::Result<PathBuf> {
+        let path = self
+            .dir
+            .join(format!("frame{:05}.{}", self.count, self.format));
+        match self.format.as_str() {
+            "txt" => fs::write(&path, frame.pretty_print_string() + "\n")?,
+            _ => image::save(&frame.map(color), &path, self.scale)?,
+        }
+        self.count += 1;
+        Ok(path)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.count
+    }
+}
+
+// Collects frames of a simulation, one call per step, and writes them as an animated GIF.
+// GIFs hold at most 256 colours.
+pub struct Recorder {
+    frames: Vec<Grid<Rgb>>,
+    scale: usize,
+    // in hundredths of a second
+    delay: u16,
+}
+
+impl Recorder {
+    // Each cell becomes a square of `scale` by `scale` pixels
+    pub fn new(scale: usize, fps: u32) -> Self {
+        Recorder {
+            frames: Vec::new(),
+            scale,
+            delay: (100 / fps.max(1)) as u16,
+        }
+    }
+
+    pub fn record<T: ToColor>(&mut self, grid: &Grid<T>) {
+        if let Some(first) = self.frames.first() {
+            assert_eq!(
+                first.bounds, grid.bounds,
+                "Frames should all be the same size."
+            );
+        }
+        self.frames.push(grid.map(ToColor::to_color));
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // None if the frames have more than 256 colours between them
+    pub fn to_gif(&self) -> Option<Vec<u8>> {
+        let Bounds(width, height) = self.frames.first().map_or(Bounds(0, 0), |f| f.bounds);
+        let (width, height) = (width * self.scale, height * self.scale);
+
+        let mut palette: Vec<Rgb> = Vec::new();
+        let mut index: HashMap<Rgb, u8> = HashMap::new();
+        for &color in self
+            .frames
+            .iter()
+            .flat_map(|frame| frame.data.iter().flatten())
+        {
+            if let Entry::Vacant(entry) = index.entry(color) {
+                entry.insert(u8::try_from(palette.len()).ok()?);
+                palette.push(color);
+            }
+        }
+        // the colour table has 2^bits entries, at least 4 for the sake of LZW
+        let bits = (palette.len().max(4) - 1).ilog2() as u8 + 1;
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend((width as u16).to_le_bytes());
+        gif.extend((height as u16).to_le_bytes());
+        // global colour table, 8 bits per channel, no background or aspect ratio
+        gif.extend([0xf0 | (bits - 1), 0, 0]);
+        for idx in 0..1 << bits {
+            let Rgb(r, g, b) = palette.get(idx).copied().unwrap_or(Rgb::BLACK);
+            gif.extend([r, g, b]);
+        }
+        // loop forever
+        gif.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+        for frame in &self.frames {
+            gif.extend([0x21, 0xf9, 4, 0]);
+            gif.extend(self.delay.to_le_bytes());
+            gif.extend([0, 0]);
+            gif.push(0x2c);
+            gif.extend([0, 0, 0, 0]);
+            gif.extend((width as u16).to_le_bytes());
+            gif.extend((height as u16).to_le_bytes());
+            gif.push(0);
+
+            let pixels: Vec<u8> = frame
+                .data
+                .iter()
+                .flat_map(|row| {
+                    let line: Vec<u8> = row
+                        .iter()
+                        .flat_map(|color| [index[color]].repeat(self.scale))
+                        .collect();
+                    line.repeat(self.scale)
+                })
+                .collect();
+            gif.push(bits);
+            for block in lzw(&pixels, bits).chunks(255) {
+                gif.push(block.len() as u8);
+                gif.extend(block);
+            }
+            gif.push(0);
+        }
+        gif.push(0x3b);
+        Some(gif)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let gif = self
+            .to_gif()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "more than 256 colours"))?;
+        fs::write(path, gif)
+    }
+}
+
+// Colour stops from the smallest to the largest value, after matplotlib's viridis
+const RAMP: [Rgb; 5] = [
+    Rgb(68, 1, 84),
+    Rgb(59, 82, 139),
+    Rgb(33, 145, 140),
+    Rgb(94, 201, 98),
+    Rgb(253, 231, 37),
+];
+
+// Numbers coloured from dark purple for the smallest up to yellow for the largest, e.g. the
+// distances of a search. Cells without a number are black.
+pub fn heatmap<T: Copy + Into<Option<usize>>>(grid: &Grid<T>) -> Grid<Rgb> {
+    let values = grid.map(|&value| value.into());
+    let (min, max) = match values.data.iter().flatten().flatten().minmax() {
+        MinMaxResult::NoElements => return values.map(|_| Rgb::BLACK),
+        MinMaxResult::OneElement(&value) => (value, value),
+        MinMaxResult::MinMax(&min, &max) => (min, max),
+    };
+    values.map(|value| match value {
+        None => Rgb::BLACK,
+        Some(value) => {
+            let position = match max - min {
+                0 => 0.0,
+                range => (value - min) as f64 / range as f64 * (RAMP.len() - 1) as f64,
+            };
+            let (stop, fraction) = (position as usize, position.fract());
+            let (from, to) = (RAMP[stop], RAMP[(stop + 1).min(RAMP.len() - 1)]);
+            let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+            Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+        }
+    })
+}
+
+// Variable-length GIF LZW codes, least significant bit first
+fn lzw(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = Vec::new();
+    let (mut buffer, mut buffered) = (0u32, 0);
+    let mut write = |code: u16, size: u8| {
+        buffer |= (code as u32) << buffered;
+        buffered += size;
+        while buffered >= 8 {
+            out.push(buffer as u8);
+            buffer >>= 8;
+            buffered -= 8;
+        }
+    };
+
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = min_code_size + 1;
+    let mut next = end + 1;
+    write(clear, size);
+    let mut indices = indices.iter();
+    if let Some(&first) = indices.next() {
+        let mut prefix = first as u16;
+        for &index in indices {
+            if let Some(&code) = table.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+            write(prefix, size);
+            // the decoder widens its codes once it has filled the current width
+            if next == 1 << size && size < 12 {
+                size += 1;
+            }
+            if next < 4096 {
+                table.insert((prefix, index), next);
+                next += 1;
+            } else {
+                write(clear, size);
+                table.clear();
+                size = min_code_size + 1;
+                next = end + 1;
+            }
+            prefix = index as u16;
+        }
+        write(prefix, size);
+        if next == 1 << size && size < 12 {
+            size += 1;
+        }
+    }
+    write(end, size);
+    write(0, 7);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_dump() {
+        let dir = std::env::temp_dir().join("aoc_frame_dump");
+        let _ = fs::remove_dir_all(&dir);
+        let grid: Grid<char> = Grid::from_rows(["#.", ".#"]);
+        let color = |c: &char| match c {
+            '#' => Rgb::WHITE,
+            _ => Rgb::BLACK,
+        };
+
+        let mut frames = FrameDump::new(&dir, "txt", 1).unwrap();
+        frames.dump(&grid, color).unwrap();
+        let last = frames.dump(&grid, color).unwrap();
+        assert_eq!(last, dir.join("frame00001.txt"));
+        assert_eq!(fs::read_to_string(last).unwrap(), "#.\n.#\n");
+        assert_eq!(frames.frame_count(), 2);
+
+        let mut frames = FrameDump::new(&dir, "png", 3).unwrap();
+        let path = frames.dump(&grid, color).unwrap();
+        assert!(fs::read(path).unwrap().starts_with(b"\x89PNG"));
+        assert!(FrameDump::new(&dir, "bmp", 1).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let grid: Grid<Option<usize>> = Grid::from_fn(Bounds(3, 2), |pos| match pos.1 {
+            0 => Some(10 + 5 * pos.0),
+            _ => None,
+        });
+        let colors = heatmap(&grid);
+        assert_eq!(colors.data[0], vec![RAMP[0], RAMP[2], RAMP[4]]);
+        assert_eq!(colors.data[1], vec![Rgb::BLACK; 3]);
+
+        // all the same
+        let colors = heatmap(&Grid::filled(Bounds(2, 2), 7usize));
+        assert_eq!(colors.data, vec![vec![RAMP[0]; 2]; 2]);
+    }
+
+    // A straightforward decoder, to check the encoder against
+    fn unlzw(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut bits = data
+            .iter()
+            .flat_map(|byte| (0..8).map(move |bit| byte >> bit & 1));
+        let mut read = |size: u8| -> usize {
+            (0..size)
+                .map(|bit| (bits.next().unwrap() as usize) << bit)
+                .sum()
+        };
+        let mut out = Vec::new();
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut size = min_code_size + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        loop {
+            let code = read(size);
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.extend([vec![], vec![]]);
+                size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+                (None, None) => panic!("Unknown first code."),
+            };
+            if let Some(prev) = previous {
+                if table.len() < 4096 {
+                    table.push([prev, vec![entry[0]]].concat());
+                }
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            out.extend(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        let mut rng = 12345u32;
+        // long enough to fill the table and clear it several times
+        let noise: Vec<u8> = (0..20000)
+            .map(|_| {
+                rng = rng.wrapping_mul(1103515245).wrapping_add(12345);
+                (rng >> 16) as u8 % 16
+            })
+            .collect();
+        let runs: Vec<u8> = (0..5000).map(|i| (i / 7 % 3) as u8).collect();
+        for (data, bits) in [(noise, 4), (runs, 2), (vec![], 2), (vec![1], 2)] {
+            assert_eq!(unlzw(&lzw(&data, bits), bits), data);
+        }
+    }
+
+    #[test]
+    fn test_gif() {
+        let mut recorder = Recorder::new(2, 10);
+        let frame = |on: bool| {
+            Grid::from_fn(Bounds(3, 2), |pos| match pos.0 == 1 && on {
+                true => Rgb::WHITE,
+                false => Rgb::BLACK,
+            })
+        };
+        recorder.record(&frame(false));
+        recorder.record(&frame(true));
+        assert_eq!(recorder.frame_count(), 2);
+        let gif = recorder.to_gif().unwrap();
+        assert!(gif.starts_with(b"GIF89a\x06\x00\x04\x00\xf1"));
+        // two colours, padded to four
+        assert_eq!(gif[13..25], [0, 0, 0, 255, 255, 255, 0, 0, 0, 0, 0, 0]);
+        // a tenth of a second per frame
+        assert_eq!(
+            gif.windows(6)
+                .filter(|w| w == b"\x21\xf9\x04\x00\x0a\x00")
+                .count(),
+            2
+        );
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        let mut recorder = Recorder::new(1, 10);
+        recorder.record(&Grid::from_fn(Bounds(300, 1), |pos| {
+            Rgb(pos.0 as u8, (pos.0 / 256) as u8, 0)
+        }));
+        assert_eq!(recorder.to_gif(), None);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::utils::{
+    cli::{Extension, Extensions},
+    map2d::grid::Grid,
+};
+use itertools::Itertools;
+use std::{fs, io, path::Path};
+
+// Where to write a picture instead of solving, e.g. `cargo run --bin day12 -- --render plots.png`
+pub const RENDER: Extension = Extension {
+    name: "render",
+    default: "",
+};
+
+// The --render path, if one was given
+pub fn render_path() -> Option<String> {
+    let path: String = Extensions::from_args(&[RENDER]).get("render");
+    (!path.is_empty()).then_some(path)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// How a grid value is drawn
+pub trait ToColor {
+    fn to_color(&self) -> Rgb;
+}
+
+impl ToColor for Rgb {
+    fn to_color(&self) -> Rgb {
+        *self
+    }
+}
+
+// Colours for things that have none of their own, e.g. regions, picked by number
+pub trait Palette {
+    fn color(&self, index: usize) -> Rgb;
+}
+
+// The given colours over and over
+pub struct Cycle(pub Vec<Rgb>);
+
+impl Palette for Cycle {
+    fn color(&self, index: usize) -> Rgb {
+        self.0[index % self.0.len()]
+    }
+}
+
+// Fully saturated hues, each a golden angle round the colour wheel from the one before,
+// so that consecutive indices look different
+pub struct Hues;
+
+impl Palette for Hues {
+    fn color(&self, index: usize) -> Rgb {
+        let hue = (index as f64 * 137.508) % 360.0 / 60.0;
+        let x = 1.0 - (hue % 2.0 - 1.0).abs();
+        let (r, g, b) = match hue as u32 {
+            0 => (1.0, x, 0.0),
+            1 => (x, 1.0, 0.0),
+            2 => (0.0, 1.0, x),
+            3 => (0.0, x, 1.0),
+            4 => (x, 0.0, 1.0),
+            _ => (1.0, 0.0, x),
+        };
+        let channel = |value: f64| (64.0 + value * 191.0).round() as u8;
+        Rgb(channel(r), channel(g), channel(b))
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+// A zlib stream of uncompressed deflate blocks: larger files, but no dependencies
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        out.push(blocks.peek().is_none() as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+// Every cell becomes a square of `scale` by `scale` pixels
+pub fn to_png<T: ToColor>(grid: &Grid<T>, scale: usize) -> Vec<u8> {
+    let (width, height) = (grid.bounds.0 * scale, grid.bounds.1 * scale);
+    let mut pixels = Vec::with_capacity((3 * width + 1) * height);
+    for row in &grid.data {
+        let line: Vec<u8> = row
+            .iter()
+            .flat_map(|value| {
+                let Rgb(r, g, b) = value.to_color();
+                [r, g, b].repeat(scale)
+            })
+            .collect();
+        for _ in 0..scale {
+            // no filter
+            pixels.push(0);
+            pixels.extend(&line);
+        }
+    }
+
+    let mut header = Vec::new();
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, then the default compression, filtering and no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// One rectangle per run of equal colours in a row
+pub fn to_svg<T: ToColor>(grid: &Grid<T>, scale: usize) -> String {
+    let (width, height) = (grid.bounds.0 * scale, grid.bounds.1 * scale);
+    let mut lines = vec![format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" ",
+            "shape-rendering=\"crispEdges\">"
+        ),
+        width, height
+    )];
+    for (y, row) in grid.data.iter().enumerate() {
+        let mut x = 0;
+        for (color, run) in &row.iter().chunk_by(|value| value.to_color()) {
+            let length = run.count();
+            lines.push(format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                x * scale,
+                y * scale,
+                length * scale,
+                scale,
+                color.hex()
+            ));
+            x += length;
+        }
+    }
+    lines.push(String::from("</svg>"));
+    lines.join("\n")
+}
+
+// SVG for paths ending in .svg, PNG otherwise
+pub fn save<T: ToColor>(grid: &Grid<T>, path: impl AsRef<Path>, scale: usize) -> io::Result<()> {
+    let path = path.as_ref();
+    if path.extension().is_some_and(|ext| ext == "svg") {
+        fs::write(path, to_svg(grid, scale))
+    } else {
+        fs::write(path, to_png(grid, scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::map2d::grid::Bounds;
+
+    fn checkerboard() -> Grid<Rgb> {
+        Grid::from_fn(Bounds(3, 2), |pos| {
+            if (pos.0 + pos.1) % 2 == 0 {
+                Rgb::BLACK
+            } else {
+                Rgb(255, 0, 0)
+            }
+        })
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn test_png() {
+        let png = to_png(&checkerboard(), 2);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"));
+        // 6 by 4 pixels
+        assert_eq!(png[16..24], [0, 0, 0, 6, 0, 0, 0, 4]);
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        // stored blocks keep the scanlines as they are: filter byte, then pixels
+        let first_row = [0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(png.windows(first_row.len()).any(|w| w == first_row));
+    }
+
+    #[test]
+    fn test_zlib_blocks() {
+        let data = vec![7; 70000];
+        let zlib = zlib_stored(&data);
+        // header, two block headers, data and checksum
+        assert_eq!(zlib.len(), 2 + 5 + 5 + 70000 + 4);
+        assert_eq!(zlib[2..7], [0, 0xff, 0xff, 0, 0]);
+        assert_eq!(zlib[7 + 0xffff], 1);
+    }
+
+    #[test]
+    fn test_svg() {
+        let grid: Grid<Rgb> = Grid::from_fn(Bounds(3, 1), |pos| {
+            if pos.0 < 2 {
+                Rgb::WHITE
+            } else {
+                Rgb(255, 0, 0)
+            }
+        });
+        let svg = to_svg(&grid, 10);
+        assert!(svg.contains("width=\"30\" height=\"10\""));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#ffffff\"/>"));
+        assert!(
+            svg.contains("<rect x=\"20\" y=\"0\" width=\"10\" height=\"10\" fill=\"#ff0000\"/>")
+        );
+        assert_eq!(to_svg(&checkerboard(), 1).matches("<rect").count(), 6);
+    }
+
+    #[test]
+    fn test_palettes() {
+        let cycle = Cycle(vec![Rgb::BLACK, Rgb::WHITE]);
+        assert_eq!(cycle.color(3), Rgb::WHITE);
+        let hues: Vec<Rgb> = (0..10).map(|index| Hues.color(index)).collect();
+        assert_eq!(hues[0], Rgb(255, 64, 64));
+        assert!(hues.iter().tuple_windows().all(|(a, b)| a != b));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Box,
+    Stadium,
+    Circle,
+}
+
+impl Shape {
+    fn wrap(self, label: &str) -> String {
+        let label = label.replace('"', "#quot;");
+        match self {
+            Shape::Box => format!("[\"{}\"]", label),
+            Shape::Stadium => format!("([\"{}\"])", label),
+            Shape::Circle => format!("((\"{}\"))", label),
+        }
+    }
+}
+
+// A Mermaid flowchart, e.g. for a Markdown file on GitHub. Nodes are given by id, and drawn in
+// the order they were added; each subgraph is a title and the nodes drawn inside it, and a node
+// listed twice goes in the first of its subgraphs.
+#[derive(Debug, Clone)]
+pub struct Flowchart {
+    direction: &'static str,
+    connector: &'static str,
+    nodes: Vec<(String, String, Shape)>,
+    subgraphs: Vec<(String, Vec<String>)>,
+    edges: Vec<(String, String)>,
+}
+
+impl Flowchart {
+    // "TB" for top to bottom, "LR" for left to right
+    pub fn new(direction: &'static str) -> Self {
+        Flowchart {
+            direction,
+            connector: "-->",
+            nodes: Vec::new(),
+            subgraphs: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    // Edges without arrow heads
+    pub fn undirected(mut self) -> Self {
+        self.connector = "---";
+        self
+    }
+
+    pub fn node(mut self, node: impl Display, label: &str, shape: Shape) -> Self {
+        self.nodes.push((node.to_string(), label.into(), shape));
+        self
+    }
+
+    pub fn nodes<N: Display>(
+        self,
+        nodes: impl IntoIterator<Item = N>,
+        label: impl Fn(&N) -> String,
+        shape: Shape,
+    ) -> Self {
+        nodes
+            .into_iter()
+            .fold(self, |chart, node| chart.node(&node, &label(&node), shape))
+    }
+
+    pub fn edges<N: Display>(mut self, edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        self.edges.extend(
+            edges
+                .into_iter()
+                .map(|(from, to)| (from.to_string(), to.to_string())),
+        );
+        self
+    }
+
+    pub fn subgraph<N: Display>(mut self, title: &str, nodes: impl IntoIterator<Item = N>) -> Self {
+        self.subgraphs.push((
+            title.into(),
+            nodes.into_iter().map(|node| node.to_string()).collect(),
+        ));
+        self
+    }
+}
+
+// Node ids are used as they are, except for "end", which would close a subgraph
+fn id(node: &str) -> &str {
+    match node {
+        "end" => "END",
+        _ => node,
+    }
+}
+
+impl Display for Flowchart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "flowchart {}", self.direction)?;
+        for (node, label, shape) in &self.nodes {
+            write!(f, "\n    {}{}", id(node), shape.wrap(label))?;
+        }
+        let known: HashSet<&str> = self
+            .nodes
+            .iter()
+            .map(|(node, _, _)| node.as_str())
+            .collect();
+        let mut placed: HashSet<&str> = HashSet::new();
+        for (title, nodes) in &self.subgraphs {
+            write!(f, "\n    subgraph {}", title)?;
+            for node in nodes {
+                if known.contains(node.as_str()) && placed.insert(node) {
+                    write!(f, "\n        {}", id(node))?;
+                }
+            }
+            write!(f, "\n    end")?;
+        }
+        for (from, to) in &self.edges {
+            write!(f, "\n    {} {} {}", id(from), self.connector, id(to))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flowchart() {
+        let chart = Flowchart::new("LR")
+            .nodes(["a", "end"], |node| node.to_uppercase(), Shape::Box)
+            .nodes([1], |_| String::from("say \"hi\""), Shape::Circle)
+            .subgraph("first", ["end", "missing"])
+            .subgraph("second", ["end", "a"])
+            .edges([("a", "end")])
+            .edges([(1, 1)]);
+        assert_eq!(
+            chart.to_string(),
+            [
+                "flowchart LR",
+                "    a[\"A\"]",
+                "    END[\"END\"]",
+                "    1((\"say #quot;hi#quot;\"))",
+                "    subgraph first",
+                "        END",
+                "    end",
+                "    subgraph second",
+                "        a",
+                "    end",
+                "    a --> END",
+                "    1 --> 1",
+            ]
+            .join("\n")
+        );
+
+        let chart = Flowchart::new("TB").undirected().edges([("x", "y")]);
+        assert_eq!(chart.to_string(), "flowchart TB\n    x --- y");
+    }
+}
@@ -0,0 +1,145 @@
+use crate::utils::{
+    map2d::{
+        direction::Direction,
+        grid::{Grid, ToChar, ValidPosition},
+    },
+    viz::image::Rgb,
+};
+use itertools::Itertools;
+
+// A grid with search results drawn over it: paths as arrows, sets of cells as a glyph, each
+// in a colour of its own. Later marks cover earlier ones.
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    chars: Grid<char>,
+    colors: Grid<Option<Rgb>>,
+}
+
+impl Overlay {
+    pub fn new<T: ToChar>(grid: &Grid<T>) -> Self {
+        Overlay {
+            chars: grid.map(ToChar::to_char),
+            colors: grid.map(|_| None),
+        }
+    }
+
+    pub fn cells<'a>(
+        mut self,
+        cells: impl IntoIterator<Item = &'a ValidPosition>,
+        glyph: char,
+        color: Rgb,
+    ) -> Self {
+        for pos in cells {
+            *self.chars.value_mut(pos) = glyph;
+            *self.colors.value_mut(pos) = Some(color);
+        }
+        self
+    }
+
+    // Each cell points to the next one; cells followed by a jump get a '*', and the last cell
+    // keeps its glyph
+    pub fn path(mut self, path: &[ValidPosition], color: Rgb) -> Self {
+        for (pos, next) in path.iter().tuple_windows() {
+            *self.chars.value_mut(pos) = Direction::iter_all()
+                .find(|direction| pos.try_step(direction, &self.chars.bounds) == Some(*next))
+                .map_or('*', char::from);
+        }
+        for pos in path {
+            *self.colors.value_mut(pos) = Some(color);
+        }
+        self
+    }
+
+    pub fn chars(&self) -> &Grid<char> {
+        &self.chars
+    }
+
+    // For the terminal: marked cells in bold and their colour
+    pub fn to_ansi(&self) -> String {
+        self.chars
+            .data
+            .iter()
+            .zip(&self.colors.data)
+            .map(|(chars, colors)| {
+                chars
+                    .iter()
+                    .zip(colors)
+                    .map(|(c, color)| match color {
+                        Some(Rgb(r, g, b)) => format!("\x1b[1;38;2;{};{};{}m{}\x1b[0m", r, g, b, c),
+                        None => c.to_string(),
+                    })
+                    .join("")
+            })
+            .join("\n")
+    }
+
+    // For viz::image: marked cells in their colour, the rest by their glyph
+    pub fn to_colors(&self, background: impl Fn(char) -> Rgb) -> Grid<Rgb> {
+        Grid::from_fn(self.chars.bounds, |pos| {
+            self.colors
+                .value(&pos)
+                .unwrap_or_else(|| background(*self.chars.value(&pos)))
+        })
+    }
+}
+
+// The cells from one position to another going horizontally first, then vertically; both
+// ends included
+pub fn elbow(from: ValidPosition, to: ValidPosition) -> Vec<ValidPosition> {
+    let steps = |a: usize, b: usize| -> Vec<usize> {
+        if a <= b {
+            (a..=b).collect()
+        } else {
+            (b..=a).rev().collect()
+        }
+    };
+    let horizontal = steps(from.0, to.0)
+        .into_iter()
+        .map(|x| ValidPosition(x, from.1));
+    let vertical = steps(from.1, to.1)
+        .into_iter()
+        .skip(1)
+        .map(|y| ValidPosition(to.0, y));
+    horizontal.chain(vertical).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let grid: Grid<char> = Grid::from_rows(["....", "....", "...."]);
+        let route = elbow(ValidPosition(0, 0), ValidPosition(2, 2));
+        assert_eq!(route.len(), 5);
+        let overlay = Overlay::new(&grid).path(&route, Rgb::WHITE).cells(
+            &[ValidPosition(3, 0)],
+            'O',
+            Rgb::BLACK,
+        );
+        assert_eq!(overlay.chars().pretty_print_string(), ">>vO\n..v.\n....");
+
+        // a jump, e.g. a cheat through a wall
+        let overlay =
+            Overlay::new(&grid).path(&[ValidPosition(0, 0), ValidPosition(2, 0)], Rgb::WHITE);
+        assert_eq!(overlay.chars().pretty_print_string(), "*...\n....\n....");
+    }
+
+    #[test]
+    fn test_colors() {
+        let grid: Grid<char> = Grid::from_rows(["#.", ".#"]);
+        let overlay = Overlay::new(&grid).cells(&[ValidPosition(1, 0)], 'x', Rgb(255, 0, 0));
+        assert_eq!(overlay.to_ansi(), "#\x1b[1;38;2;255;0;0mx\x1b[0m\n.#");
+        let colors = overlay.to_colors(|c| match c {
+            '#' => Rgb::BLACK,
+            _ => Rgb::WHITE,
+        });
+        assert_eq!(
+            colors.data,
+            vec![
+                vec![Rgb::BLACK, Rgb(255, 0, 0)],
+                vec![Rgb::WHITE, Rgb::BLACK]
+            ]
+        );
+    }
+}
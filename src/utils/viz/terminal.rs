@@ -0,0 +1,230 @@
+use crate::utils::{
+    cli::Extension,
+    map2d::{
+        direction::Direction,
+        grid::{Grid, ToChar},
+    },
+    viz::image::{Rgb, ToColor},
+};
+use std::{
+    io::{self, BufRead, Stdout, Write},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+// Frames per second, e.g. `cargo run --bin day15 -- --visualize --fps 30`
+pub const FPS: Extension = Extension {
+    name: "fps",
+    default: "10",
+};
+
+// Moves the cursor to the top left and clears the screen
+const CLEAR: &str = "\x1b[H\x1b[2J";
+
+// Draws frames over each other in the terminal, no faster than the frame rate allows.
+// A frame rate of 0 draws as fast as possible.
+pub struct Animation<W: Write = Stdout> {
+    out: W,
+    frame_time: Duration,
+    last_frame: Option<Instant>,
+    paused: Option<Arc<AtomicBool>>,
+}
+
+impl Animation {
+    pub fn new(fps: u32) -> Self {
+        Animation::with_writer(io::stdout(), fps)
+    }
+
+    // Pressing Enter pauses the animation, pressing it again resumes. Reads stdin in the
+    // background from then on.
+    pub fn pause_on_enter(mut self) -> Self {
+        let paused = Arc::new(AtomicBool::new(false));
+        let toggle = Arc::clone(&paused);
+        thread::spawn(move || {
+            for _ in io::stdin().lock().lines().map_while(Result::ok) {
+                toggle.fetch_xor(true, Ordering::Relaxed);
+            }
+        });
+        self.paused = Some(paused);
+        self
+    }
+}
+
+impl<W: Write> Animation<W> {
+    pub fn with_writer(out: W, fps: u32) -> Self {
+        Animation {
+            out,
+            frame_time: match fps {
+                0 => Duration::ZERO,
+                fps => Duration::from_secs(1) / fps,
+            },
+            last_frame: None,
+            paused: None,
+        }
+    }
+
+    // Waits for the next frame, then replaces what is on screen with the text
+    pub fn draw_text(&mut self, text: &str) -> io::Result<()> {
+        if let Some(last_frame) = self.last_frame {
+            thread::sleep(self.frame_time.saturating_sub(last_frame.elapsed()));
+        }
+        while self
+            .paused
+            .as_ref()
+            .is_some_and(|paused| paused.load(Ordering::Relaxed))
+        {
+            thread::sleep(Duration::from_millis(50));
+        }
+        self.last_frame = Some(Instant::now());
+        writeln!(self.out, "{}{}", CLEAR, text)?;
+        self.out.flush()
+    }
+
+    // A grid with a caption line below it, e.g. the step count
+    pub fn draw<T: ToChar>(&mut self, grid: &Grid<T>, caption: &str) -> io::Result<()> {
+        self.draw_text(&format!("{}\n{}", grid.pretty_print_string(), caption))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+// The nearest of the 6x6x6 colour cube in the 256 colour palette
+fn ansi_256(Rgb(r, g, b): Rgb) -> u16 {
+    let level = |channel: u8| (channel as u16 * 5 + 127) / 255;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+// Every cell as a block of two spaces in its colour, for terminals with 256 colours
+pub fn to_ansi_256<T: ToColor>(grid: &Grid<T>) -> String {
+    grid.data
+        .iter()
+        .map(|row| {
+            let cells: String = row
+                .iter()
+                .map(|cell| format!("\x1b[48;5;{}m  ", ansi_256(cell.to_color())))
+                .collect();
+            cells + "\x1b[0m"
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Arrow(Direction),
+    Char(char),
+}
+
+// The keys pressed, with arrow keys decoded from their escape sequences. Bytes that are not
+// ASCII are dropped.
+pub fn keys(input: impl BufRead) -> impl Iterator<Item = Key> {
+    let mut bytes = input.bytes().map_while(Result::ok).peekable();
+    std::iter::from_fn(move || loop {
+        let byte = bytes.next()?;
+        if byte == 0x1b && bytes.next_if_eq(&b'[').is_some() {
+            let direction = match bytes.next()? {
+                b'A' => Direction::UP,
+                b'B' => Direction::DOWN,
+                b'C' => Direction::RIGHT,
+                b'D' => Direction::LEFT,
+                _ => continue,
+            };
+            return Some(Key::Arrow(direction));
+        }
+        if byte.is_ascii() {
+            return Some(Key::Char(byte as char));
+        }
+    })
+}
+
+// Hands keys to the program as they are pressed rather than line by line, without echoing
+// them. The terminal is restored when this is dropped.
+pub struct RawInput {
+    saved: String,
+}
+
+impl RawInput {
+    pub fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo"])?;
+        Ok(RawInput { saved })
+    }
+}
+
+impl Drop for RawInput {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::map2d::grid::Bounds;
+
+    #[test]
+    fn test_frames() {
+        let mut animation = Animation::with_writer(Vec::new(), 0);
+        let grid: Grid<char> = Grid::from_rows(["#.", ".#"]);
+        animation.draw(&grid, "step 1").unwrap();
+        animation.draw_text("done").unwrap();
+        let out = String::from_utf8(animation.into_inner()).unwrap();
+        assert_eq!(out, format!("{CLEAR}#.\n.#\nstep 1\n{CLEAR}done\n"));
+    }
+
+    #[test]
+    fn test_ansi_256() {
+        assert_eq!(ansi_256(Rgb::BLACK), 16);
+        assert_eq!(ansi_256(Rgb::WHITE), 231);
+        let grid = Grid::from_fn(Bounds(2, 1), |pos| match pos.0 {
+            0 => Rgb(255, 0, 0),
+            _ => Rgb(0, 0, 250),
+        });
+        assert_eq!(to_ansi_256(&grid), "\x1b[48;5;196m  \x1b[48;5;21m  \x1b[0m");
+    }
+
+    #[test]
+    fn test_keys() {
+        let pressed: Vec<Key> = keys(&b"\x1b[Au\x1b[D\x1b[Zq"[..]).collect();
+        assert_eq!(
+            pressed,
+            vec![
+                Key::Arrow(Direction::UP),
+                Key::Char('u'),
+                Key::Arrow(Direction::LEFT),
+                Key::Char('q')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_rate() {
+        let mut animation = Animation::with_writer(io::sink(), 100);
+        let start = Instant::now();
+        for _ in 0..4 {
+            animation.draw_text("").unwrap();
+        }
+        // the first frame is drawn straight away
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+}
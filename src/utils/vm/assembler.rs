@@ -0,0 +1,156 @@
+use crate::utils::vm::chronospatial::{Opcode, Register};
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AssembleError {
+    // 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn operand(opcode: Opcode, word: Option<&str>) -> Result<u8, String> {
+    let Some(word) = word else {
+        return match opcode {
+            // bxc ignores its operand
+            Opcode::Bxc => Ok(0),
+            _ => Err(format!("{} needs an operand", opcode.mnemonic())),
+        };
+    };
+    if opcode.takes_combo() {
+        if let Some(register) = Register::ALL
+            .iter()
+            .find(|register| word.eq_ignore_ascii_case(&register.name().to_string()))
+        {
+            return Ok(*register as u8 + 4);
+        }
+        if word == "?7" {
+            return Ok(7);
+        }
+    }
+    let limit = if opcode.takes_combo() { 3 } else { 7 };
+    match word.parse::<u8>() {
+        Ok(value) if value <= limit => Ok(value),
+        _ if opcode.takes_combo() => Err(format!(
+            "{} takes 0-3 or a register, not '{}'",
+            opcode.mnemonic(),
+            word
+        )),
+        _ => Err(format!("{} takes 0-7, not '{}'", opcode.mnemonic(), word)),
+    }
+}
+
+// A code given as is, as in the "???" and trailing code lines of a listing
+fn raw_code(word: Option<&str>) -> Result<u8, String> {
+    let word = word.ok_or_else(|| String::from("??? needs two codes"))?;
+    word.parse()
+        .map_err(|_| format!("expected a code of 0-255, not '{}'", word))
+}
+
+fn assemble_line(line: &str) -> Result<Vec<u8>, String> {
+    // comments and the addresses in disassembler listings are ignored
+    let code = line.split(';').next().unwrap_or_default();
+    let code = match code.split_once(':') {
+        Some((address, rest)) if address.trim().parse::<usize>().is_ok() => rest,
+        _ => code,
+    };
+    let mut words = code.split_whitespace();
+    let Some(mnemonic) = words.next() else {
+        return Ok(Vec::new());
+    };
+    let codes = match mnemonic {
+        "???" => vec![raw_code(words.next())?, raw_code(words.next())?],
+        _ if mnemonic.starts_with(|c: char| c.is_ascii_digit()) => vec![raw_code(Some(mnemonic))?],
+        _ => assemble_instruction(mnemonic, &mut words)?.to_vec(),
+    };
+    if let Some(extra) = words.next() {
+        return Err(format!("unexpected '{}'", extra));
+    }
+    Ok(codes)
+}
+
+fn assemble_instruction<'a>(
+    mnemonic: &str,
+    words: &mut impl Iterator<Item = &'a str>,
+) -> Result<[u8; 2], String> {
+    let opcode = Opcode::ALL
+        .into_iter()
+        .find(|opcode| mnemonic.eq_ignore_ascii_case(opcode.mnemonic()))
+        .ok_or_else(|| format!("unknown instruction '{}'", mnemonic))?;
+    Ok([opcode.code(), operand(opcode, words.next())?])
+}
+
+// Turns one instruction per line, e.g. "bst A\nout B", into program codes. Accepts what
+// vm::disasm::disassemble produces, including its "??? code operand" lines and a lone trailing
+// code, so any program survives the round trip.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssembleError> {
+    let mut program = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let codes = assemble_line(line).map_err(|message| AssembleError {
+            line: index + 1,
+            message,
+        })?;
+        program.extend(codes);
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::vm::disasm::disassemble;
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble(
+            "
+            bst A    ; B = A % 8
+            bxl 5
+            cdv b
+            bxc
+            OUT B
+            adv 3
+            jnz 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(program, vec![2, 4, 1, 5, 7, 5, 4, 0, 5, 5, 0, 3, 3, 0]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let program = vec![2, 4, 1, 5, 7, 5, 4, 3, 1, 6, 0, 3, 5, 5, 3, 0, 5, 7];
+        assert_eq!(assemble(&disassemble(&program)), Ok(program));
+
+        // odd length, invalid opcodes and operands out of range
+        for program in [
+            vec![0, 3, 5],
+            vec![2, 4, 9, 1, 1, 8, 5, 7],
+            vec![255, 255, 0],
+        ] {
+            assert_eq!(assemble(&disassemble(&program)), Ok(program));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| assemble(source).unwrap_err().to_string();
+        assert_eq!(error("adv 1\nmul 2"), "line 2: unknown instruction 'mul'");
+        assert_eq!(
+            error("bst 4"),
+            "line 1: bst takes 0-3 or a register, not '4'"
+        );
+        assert_eq!(error("bxl A"), "line 1: bxl takes 0-7, not 'A'");
+        assert_eq!(error("jnz 8"), "line 1: jnz takes 0-7, not '8'");
+        assert_eq!(error("out"), "line 1: out needs an operand");
+        assert_eq!(error("out A B"), "line 1: unexpected 'B'");
+        assert_eq!(error("??? 9"), "line 1: ??? needs two codes");
+        assert_eq!(error("256"), "line 1: expected a code of 0-255, not '256'");
+        assert_eq!(error("5 3"), "line 1: unexpected '3'");
+    }
+}
@@ -0,0 +1,510 @@
+use crate::utils::parse::{
+    captures::captures_as,
+    numbers::{parse_number, NumberError},
+};
+use itertools::Itertools;
+use std::{fmt::Display, str::FromStr};
+
+// The 3-bit computer from day 17: three unbounded registers and a program of 3-bit codes,
+// read as (opcode, operand) pairs
+
+pub type Number = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+    A,
+    B,
+    C,
+}
+
+impl Register {
+    pub const ALL: [Register; 3] = [Register::A, Register::B, Register::C];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> char {
+        ['A', 'B', 'C'][self.index()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 8] = [
+        Opcode::Adv,
+        Opcode::Bxl,
+        Opcode::Bst,
+        Opcode::Jnz,
+        Opcode::Bxc,
+        Opcode::Out,
+        Opcode::Bdv,
+        Opcode::Cdv,
+    ];
+
+    pub fn from_code(code: u8) -> Option<Opcode> {
+        Opcode::ALL.get(code as usize).copied()
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        ["adv", "bxl", "bst", "jnz", "bxc", "out", "bdv", "cdv"][self as usize]
+    }
+
+    // Whether the operand is a combo operand (0-3 literal, 4-6 registers) rather than a literal
+    pub fn takes_combo(self) -> bool {
+        matches!(
+            self,
+            Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    InvalidOpcode { ip: usize, code: u8 },
+    ReservedCombo { ip: usize },
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fault::InvalidOpcode { ip, code } => write!(f, "invalid opcode {} at {}", code, ip),
+            Fault::ReservedCombo { ip } => write!(f, "reserved combo operand 7 at {}", ip),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    Output(Number),
+    Halt,
+}
+
+// Where a running machine sends its outputs
+pub trait OutputSink {
+    fn output(&mut self, value: Number);
+}
+
+impl OutputSink for Vec<Number> {
+    fn output(&mut self, value: Number) {
+        self.push(value);
+    }
+}
+
+// Calls the function with every output, e.g. to stop comparing early
+impl<F: FnMut(Number)> OutputSink for F {
+    fn output(&mut self, value: Number) {
+        self(value)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoadError {
+    MissingRegister(char),
+    InvalidRegister(char, String),
+    MissingProgram,
+    InvalidProgram(NumberError),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::MissingRegister(name) => write!(f, "register {} not found", name),
+            LoadError::InvalidRegister(name, value) => {
+                write!(f, "register {} has invalid value '{}'", name, value)
+            }
+            LoadError::MissingProgram => write!(f, "program not found"),
+            LoadError::InvalidProgram(err) => write!(f, "invalid program: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    registers: [Number; 3],
+    program: Vec<u8>,
+    ip: usize,
+}
+
+impl Machine {
+    // All registers start at zero
+    pub fn new(program: Vec<u8>) -> Self {
+        Machine {
+            registers: [0; 3],
+            program,
+            ip: 0,
+        }
+    }
+
+    pub fn with_register(mut self, register: Register, value: Number) -> Self {
+        self.set_register(register, value);
+        self
+    }
+
+    pub fn register(&self, register: Register) -> Number {
+        self.registers[register.index()]
+    }
+
+    pub fn set_register(&mut self, register: Register, value: Number) {
+        self.registers[register.index()] = value;
+    }
+
+    pub fn registers(&self) -> [Number; 3] {
+        self.registers
+    }
+
+    pub fn program(&self) -> &[u8] {
+        &self.program
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn set_ip(&mut self, ip: usize) {
+        self.ip = ip;
+    }
+
+    // A jump or an ip past the last full pair halts the machine
+    pub fn is_halted(&self) -> bool {
+        self.ip + 1 >= self.program.len()
+    }
+
+    // The instruction about to be executed, as raw (opcode, operand) codes
+    pub fn current(&self) -> Option<(u8, u8)> {
+        (!self.is_halted()).then(|| (self.program[self.ip], self.program[self.ip + 1]))
+    }
+
+    fn combo(&self, operand: u8) -> Result<Number, Fault> {
+        match operand {
+            0..=3 => Ok(operand as Number),
+            4..=6 => Ok(self.registers[operand as usize - 4]),
+            _ => Err(Fault::ReservedCombo { ip: self.ip }),
+        }
+    }
+
+    // Executes one instruction. A faulting instruction leaves the machine unchanged.
+    pub fn step(&mut self) -> Result<Step, Fault> {
+        let Some((code, operand)) = self.current() else {
+            return Ok(Step::Halt);
+        };
+        let opcode = Opcode::from_code(code).ok_or(Fault::InvalidOpcode { ip: self.ip, code })?;
+        let value = if opcode.takes_combo() {
+            self.combo(operand)?
+        } else {
+            operand as Number
+        };
+        // shifting by 64 or more clears the register
+        let shifted = u32::try_from(value)
+            .ok()
+            .and_then(|shift| self.registers[0].checked_shr(shift))
+            .unwrap_or(0);
+
+        let mut next_ip = self.ip + 2;
+        let mut step = Step::Continue;
+        match opcode {
+            Opcode::Adv => self.registers[0] = shifted,
+            Opcode::Bxl => self.registers[1] ^= value,
+            Opcode::Bst => self.registers[1] = value % 8,
+            Opcode::Jnz => {
+                if self.registers[0] != 0 {
+                    next_ip = value as usize;
+                }
+            }
+            Opcode::Bxc => self.registers[1] ^= self.registers[2],
+            Opcode::Out => step = Step::Output(value % 8),
+            Opcode::Bdv => self.registers[1] = shifted,
+            Opcode::Cdv => self.registers[2] = shifted,
+        }
+        self.ip = next_ip;
+        Ok(step)
+    }
+
+    // Runs until the machine halts, sending every output to the sink
+    pub fn run(&mut self, sink: &mut impl OutputSink) -> Result<(), Fault> {
+        loop {
+            match self.step()? {
+                Step::Continue => (),
+                Step::Output(value) => sink.output(value),
+                Step::Halt => return Ok(()),
+            }
+        }
+    }
+
+    pub fn run_to_outputs(&mut self) -> Result<Vec<Number>, Fault> {
+        let mut outputs = Vec::new();
+        self.run(&mut outputs)?;
+        Ok(outputs)
+    }
+
+    // Runs until the machine halts or faults, recording every executed instruction
+    pub fn run_traced(&mut self) -> Trace {
+        let mut trace = Trace::default();
+        while let Some((opcode, operand)) = self.current() {
+            let ip = self.ip;
+            let output = match self.step() {
+                Ok(Step::Output(value)) => Some(value),
+                Ok(_) => None,
+                Err(fault) => {
+                    trace.fault = Some(fault);
+                    break;
+                }
+            };
+            trace.steps.push(TraceStep {
+                ip,
+                opcode,
+                operand,
+                registers: self.registers,
+                output,
+            });
+        }
+        trace
+    }
+}
+
+// One executed instruction; the registers are those after it ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceStep {
+    pub ip: usize,
+    pub opcode: u8,
+    pub operand: u8,
+    pub registers: [Number; 3],
+    pub output: Option<Number>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+    // set if the run ended on an instruction that could not execute
+    pub fault: Option<Fault>,
+}
+
+impl Trace {
+    pub fn outputs(&self) -> Vec<Number> {
+        self.steps.iter().filter_map(|step| step.output).collect()
+    }
+
+    // One object per line inside the steps array, so traces diff well
+    pub fn to_json(&self) -> String {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                let [a, b, c] = step.registers;
+                format!(
+                    r#"    {{"ip": {}, "opcode": {}, "operand": {}, "registers": {{"A": {}, "B": {}, "C": {}}}, "output": {}}}"#,
+                    step.ip,
+                    step.opcode,
+                    step.operand,
+                    a,
+                    b,
+                    c,
+                    step.output.map_or(String::from("null"), |value| value.to_string())
+                )
+            })
+            .join(",\n");
+        let fault = self
+            .fault
+            .map_or(String::from("null"), |fault| format!("\"{}\"", fault));
+        format!(
+            "{{\n  \"steps\": [\n{}\n  ],\n  \"fault\": {}\n}}",
+            steps, fault
+        )
+    }
+}
+
+fn register_from(text: &str, name: char) -> Result<Number, LoadError> {
+    let (value,): (String,) = captures_as(text, &format!(r"Register {}: (\S*)", name))
+        .map_err(|_| LoadError::MissingRegister(name))?;
+    value
+        .parse()
+        .map_err(|_| LoadError::InvalidRegister(name, value))
+}
+
+// The puzzle input format: "Register A: ..." lines, then "Program: 0,1,..."
+impl FromStr for Machine {
+    type Err = LoadError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (program,): (String,) =
+            captures_as(text, r"Program: (.*)").map_err(|_| LoadError::MissingProgram)?;
+        // strictly comma-separated, so junk around the codes is an error rather than skipped
+        let program = program
+            .trim()
+            .split(',')
+            .map(parse_number)
+            .collect::<Result<_, _>>()
+            .map_err(LoadError::InvalidProgram)?;
+        let mut machine = Machine::new(program);
+        for register in Register::ALL {
+            machine.set_register(register, register_from(text, register.name())?);
+        }
+        Ok(machine)
+    }
+}
+
+impl Display for Machine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for register in Register::ALL {
+            writeln!(
+                f,
+                "Register {}: {}",
+                register.name(),
+                self.register(register)
+            )?;
+        }
+        write!(f, "\nProgram: {}", self.program.iter().join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine(program: &[u8]) -> Machine {
+        Machine::new(program.to_vec())
+    }
+
+    #[test]
+    fn test_single_steps() {
+        let mut m = machine(&[0, 2, 5, 4, 3, 0]).with_register(Register::A, 12);
+        assert_eq!(m.current(), Some((0, 2)));
+        assert_eq!(m.step(), Ok(Step::Continue));
+        assert_eq!(m.register(Register::A), 3);
+        assert_eq!(m.step(), Ok(Step::Output(3)));
+        assert_eq!(m.step(), Ok(Step::Continue));
+        assert_eq!(m.ip(), 0);
+
+        m.set_register(Register::A, 0);
+        m.set_ip(4);
+        assert_eq!(m.step(), Ok(Step::Continue));
+        assert_eq!(m.ip(), 6);
+        assert!(m.is_halted());
+        assert_eq!(m.step(), Ok(Step::Halt));
+    }
+
+    #[test]
+    fn test_faults() {
+        let mut m = machine(&[5, 7]);
+        assert_eq!(m.step(), Err(Fault::ReservedCombo { ip: 0 }));
+        assert_eq!(m.ip(), 0);
+        assert_eq!(
+            machine(&[1, 1, 8, 0]).run_to_outputs(),
+            Err(Fault::InvalidOpcode { ip: 2, code: 8 })
+        );
+
+        // a huge shift clears the register rather than overflowing
+        let mut m = machine(&[0, 4]).with_register(Register::A, 100);
+        m.step().unwrap();
+        assert_eq!(m.register(Register::A), 0);
+    }
+
+    #[test]
+    fn test_sinks() {
+        let mut m = machine(&[5, 0, 5, 1, 5, 4]).with_register(Register::A, 10);
+        let mut seen = String::new();
+        m.run(&mut |value: Number| seen.push_str(&value.to_string()))
+            .unwrap();
+        assert_eq!(seen, "012");
+    }
+
+    #[test]
+    fn test_run_traced() {
+        let mut m = machine(&[0, 1, 5, 4, 3, 0]).with_register(Register::A, 4);
+        let trace = m.run_traced();
+        assert_eq!(trace.steps.len(), 9);
+        assert_eq!(trace.outputs(), vec![2, 1, 0]);
+        assert_eq!(trace.fault, None);
+        assert_eq!(
+            trace.steps[1],
+            TraceStep {
+                ip: 2,
+                opcode: 5,
+                operand: 4,
+                registers: [2, 0, 0],
+                output: Some(2),
+            }
+        );
+        assert_eq!(trace.steps[2].ip, 4);
+        assert_eq!(trace.steps[3].ip, 0);
+
+        let trace = machine(&[0, 1, 5, 4, 3, 0])
+            .with_register(Register::A, 1)
+            .run_traced();
+        let golden = r#"{
+  "steps": [
+    {"ip": 0, "opcode": 0, "operand": 1, "registers": {"A": 0, "B": 0, "C": 0}, "output": null},
+    {"ip": 2, "opcode": 5, "operand": 4, "registers": {"A": 0, "B": 0, "C": 0}, "output": 0},
+    {"ip": 4, "opcode": 3, "operand": 0, "registers": {"A": 0, "B": 0, "C": 0}, "output": null}
+  ],
+  "fault": null
+}"#;
+        assert_eq!(trace.to_json(), golden);
+
+        let trace = machine(&[1, 3, 6, 7]).run_traced();
+        assert_eq!(trace.steps.len(), 1);
+        assert_eq!(trace.fault, Some(Fault::ReservedCombo { ip: 2 }));
+        assert!(trace.to_json().ends_with(
+            r#""fault": "reserved combo operand 7 at 2"
+}"#
+        ));
+    }
+
+    #[test]
+    fn test_load() {
+        let text = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+        let m: Machine = text.parse().unwrap();
+        assert_eq!(m.registers(), [729, 0, 0]);
+        assert_eq!(m.program(), &[0, 1, 5, 4, 3, 0]);
+        assert_eq!(m.to_string() + "\n", text);
+
+        assert_eq!(
+            "Register A: 1\nProgram: 0".parse::<Machine>(),
+            Err(LoadError::MissingRegister('B'))
+        );
+        assert_eq!(
+            "Register A: x\nProgram: 0".parse::<Machine>(),
+            Err(LoadError::InvalidRegister('A', "x".into()))
+        );
+        assert_eq!(
+            "Register A: 1".parse::<Machine>(),
+            Err(LoadError::MissingProgram)
+        );
+
+        let program_error = |program: &str| {
+            format!(
+                "Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+                program
+            )
+            .parse::<Machine>()
+            .unwrap_err()
+            .to_string()
+        };
+        assert_eq!(
+            program_error("0,1 junk"),
+            "invalid program: '1 junk' is not a number"
+        );
+        assert_eq!(program_error("0,,1"), "invalid program: number not found");
+        assert_eq!(
+            program_error("0;1"),
+            "invalid program: '0;1' is not a number"
+        );
+        assert_eq!(program_error("0,-1"), "invalid program: '-1' is negative");
+    }
+}
@@ -0,0 +1,328 @@
+use crate::utils::vm::{
+    chronospatial::{Fault, Machine, Number, Register, Step},
+    disasm::{disassemble, instruction},
+};
+use itertools::Itertools;
+use std::{collections::BTreeSet, str::FromStr};
+
+// Continuing stops after this many instructions, in case the program never halts
+const STEP_LIMIT: usize = 1_000_000;
+
+pub const HELP: &str = "\
+step [N]       (s)  execute N instructions, default 1
+continue       (c)  run to the next breakpoint or halt
+break IP       (b)  stop before executing the instruction at IP
+delete IP      (d)  remove a breakpoint
+watch R        (w)  report changes to register A, B or C
+unwatch R           stop reporting changes to a register
+set R VALUE         set a register
+registers      (r)  show the registers
+outputs        (o)  show everything output so far
+list           (l)  disassemble, marking breakpoints and the next instruction
+reset               restart with the initial registers
+quit           (q)  leave the debugger";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    Break(usize),
+    Delete(usize),
+    Watch(Register),
+    Unwatch(Register),
+    Set(Register, Number),
+    Registers,
+    Outputs,
+    List,
+    Reset,
+    Help,
+    Quit,
+}
+
+fn register(word: Option<&str>) -> Result<Register, String> {
+    match word.map(str::to_ascii_uppercase).as_deref() {
+        Some("A") => Ok(Register::A),
+        Some("B") => Ok(Register::B),
+        Some("C") => Ok(Register::C),
+        _ => Err(String::from("expected a register: A, B or C")),
+    }
+}
+
+fn number<T: FromStr>(word: Option<&str>, what: &str) -> Result<T, String> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| format!("expected {}", what))
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err(String::from("empty command"));
+        };
+        let command = match name {
+            "s" | "step" => match words.next() {
+                None => Command::Step(1),
+                count => Command::Step(number(count, "a step count")?),
+            },
+            "c" | "continue" => Command::Continue,
+            "b" | "break" => Command::Break(number(words.next(), "an instruction address")?),
+            "d" | "delete" => Command::Delete(number(words.next(), "an instruction address")?),
+            "w" | "watch" => Command::Watch(register(words.next())?),
+            "unwatch" => Command::Unwatch(register(words.next())?),
+            "set" => Command::Set(register(words.next())?, number(words.next(), "a value")?),
+            "r" | "registers" => Command::Registers,
+            "o" | "outputs" => Command::Outputs,
+            "l" | "list" => Command::List,
+            "reset" => Command::Reset,
+            "h" | "help" => Command::Help,
+            "q" | "quit" => Command::Quit,
+            _ => return Err(format!("unknown command '{}', try 'help'", name)),
+        };
+        match words.next() {
+            Some(extra) => Err(format!("unexpected '{}'", extra)),
+            None => Ok(command),
+        }
+    }
+}
+
+// A day 17 machine under interactive control. Every command returns the text to show.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    initial: Machine,
+    machine: Machine,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<Register>,
+    outputs: Vec<Number>,
+    fault: Option<Fault>,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine) -> Self {
+        Debugger {
+            initial: machine.clone(),
+            machine,
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+            outputs: Vec::new(),
+            fault: None,
+        }
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    pub fn outputs(&self) -> &[Number] {
+        &self.outputs
+    }
+
+    // Where the machine is and what it will do next
+    fn location(&self) -> String {
+        if let Some(fault) = self.fault {
+            return format!("faulted: {}", fault);
+        }
+        match self.machine.current() {
+            Some((code, operand)) => {
+                format!("{}: {}", self.machine.ip(), instruction(code, operand))
+            }
+            None => String::from("halted"),
+        }
+    }
+
+    fn registers(&self) -> String {
+        Register::ALL
+            .iter()
+            .map(|&register| format!("{}: {}", register.name(), self.machine.register(register)))
+            .join(", ")
+    }
+
+    // Runs up to `limit` instructions, stopping early at breakpoints (other than the one
+    // it starts on), halts and faults. Reports outputs, watched changes and the location.
+    fn run(&mut self, limit: usize, at_breakpoints: bool) -> String {
+        let before = self.machine.registers();
+        let output_count = self.outputs.len();
+        let mut report = Vec::new();
+        let mut executed = 0;
+        while executed < limit && self.fault.is_none() {
+            if executed > 0 && at_breakpoints && self.breakpoints.contains(&self.machine.ip()) {
+                report.push(format!("breakpoint at {}", self.machine.ip()));
+                break;
+            }
+            match self.machine.step() {
+                Ok(Step::Halt) => break,
+                Ok(Step::Output(value)) => self.outputs.push(value),
+                Ok(Step::Continue) => (),
+                Err(fault) => self.fault = Some(fault),
+            }
+            executed += 1;
+        }
+        if executed == limit && at_breakpoints && !self.machine.is_halted() {
+            report.push(format!("still running after {} instructions", limit));
+        }
+
+        if self.outputs.len() > output_count {
+            report.push(format!(
+                "output: {}",
+                self.outputs[output_count..].iter().join(",")
+            ));
+        }
+        let after = self.machine.registers();
+        for &register in &self.watches {
+            let (old, new) = (before[register as usize], after[register as usize]);
+            if old != new {
+                report.push(format!("{}: {} -> {}", register.name(), old, new));
+            }
+        }
+        report.push(self.location());
+        report.join("\n")
+    }
+
+    fn list(&self) -> String {
+        let current = self.machine.current().map(|_| self.machine.ip());
+        disassemble(self.machine.program())
+            .lines()
+            .enumerate()
+            .map(|(pair, line)| {
+                let address = pair * 2;
+                let marker = if Some(address) == current { '>' } else { ' ' };
+                let stop = if self.breakpoints.contains(&address) {
+                    '*'
+                } else {
+                    ' '
+                };
+                format!("{}{} {}", marker, stop, line)
+            })
+            .join("\n")
+    }
+
+    pub fn execute(&mut self, command: Command) -> String {
+        match command {
+            Command::Step(count) => self.run(count, false),
+            Command::Continue => self.run(STEP_LIMIT, true),
+            Command::Break(ip) if ip % 2 == 1 || ip >= self.machine.program().len() => {
+                format!("no instruction starts at {}", ip)
+            }
+            Command::Break(ip) => {
+                self.breakpoints.insert(ip);
+                format!("breakpoint at {}", ip)
+            }
+            Command::Delete(ip) => match self.breakpoints.remove(&ip) {
+                true => format!("removed breakpoint at {}", ip),
+                false => format!("no breakpoint at {}", ip),
+            },
+            Command::Watch(register) => {
+                if !self.watches.contains(&register) {
+                    self.watches.push(register);
+                }
+                format!("watching {}", register.name())
+            }
+            Command::Unwatch(register) => {
+                self.watches.retain(|&watched| watched != register);
+                format!("not watching {}", register.name())
+            }
+            Command::Set(register, value) => {
+                self.machine.set_register(register, value);
+                self.registers()
+            }
+            Command::Registers => self.registers(),
+            Command::Outputs => self.outputs.iter().join(","),
+            Command::List => self.list(),
+            Command::Reset => {
+                self.machine = self.initial.clone();
+                self.outputs.clear();
+                self.fault = None;
+                format!("{}\n{}", self.registers(), self.location())
+            }
+            Command::Help => String::from(HELP),
+            Command::Quit => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debugger() -> Debugger {
+        // outputs A % 8 while shifting A right by 3
+        let machine = Machine::new(vec![0, 3, 5, 4, 3, 0]).with_register(Register::A, 2024);
+        Debugger::new(machine)
+    }
+
+    fn run(debugger: &mut Debugger, line: &str) -> String {
+        debugger.execute(line.parse().unwrap())
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!("s".parse(), Ok(Command::Step(1)));
+        assert_eq!("step 5".parse(), Ok(Command::Step(5)));
+        assert_eq!("set b 7".parse(), Ok(Command::Set(Register::B, 7)));
+        assert_eq!("b 4".parse(), Ok(Command::Break(4)));
+        assert!("watch D".parse::<Command>().is_err());
+        assert!("step 1 2".parse::<Command>().is_err());
+        assert!("jump".parse::<Command>().is_err());
+        assert!("".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut debugger = debugger();
+        run(&mut debugger, "watch A");
+        assert_eq!(run(&mut debugger, "step"), "A: 2024 -> 253\n2: out A");
+        assert_eq!(run(&mut debugger, "s"), "output: 5\n4: jnz 0");
+        assert_eq!(run(&mut debugger, "r"), "A: 253, B: 0, C: 0");
+
+        assert_eq!(run(&mut debugger, "break 2"), "breakpoint at 2");
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "breakpoint at 2\nA: 253 -> 31\n2: out A"
+        );
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "breakpoint at 2\noutput: 7\nA: 31 -> 3\n2: out A"
+        );
+
+        run(&mut debugger, "delete 2");
+        assert_eq!(run(&mut debugger, "c"), "output: 3,0\nA: 3 -> 0\nhalted");
+        assert_eq!(run(&mut debugger, "outputs"), "5,7,3,0");
+
+        assert_eq!(run(&mut debugger, "reset"), "A: 2024, B: 0, C: 0\n0: adv 3");
+        assert!(debugger.outputs().is_empty());
+    }
+
+    #[test]
+    fn test_list_and_faults() {
+        let mut debugger = debugger();
+        run(&mut debugger, "b 4");
+        run(&mut debugger, "s");
+        assert_eq!(
+            run(&mut debugger, "l"),
+            [
+                "   0: adv 3   ; A = A >> 3",
+                ">  2: out A   ; output A % 8",
+                " * 4: jnz 0   ; if A != 0 jump 0",
+            ]
+            .join("\n")
+        );
+        assert_eq!(run(&mut debugger, "b 3"), "no instruction starts at 3");
+
+        let mut faulty = Debugger::new(Machine::new(vec![5, 7]));
+        assert_eq!(
+            run(&mut faulty, "c"),
+            "faulted: reserved combo operand 7 at 0"
+        );
+        assert_eq!(
+            run(&mut faulty, "s"),
+            "faulted: reserved combo operand 7 at 0"
+        );
+
+        let mut endless = Debugger::new(Machine::new(vec![3, 0]).with_register(Register::A, 1));
+        assert_eq!(
+            run(&mut endless, "c"),
+            format!("still running after {} instructions\n0: jnz 0", STEP_LIMIT)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::utils::vm::chronospatial::{Opcode, Register};
+
+// How an operand reads in the listing: combo operands 4-6 name registers, 7 is reserved
+fn operand(opcode: Opcode, operand: u8) -> String {
+    match operand {
+        4..=6 if opcode.takes_combo() => Register::ALL[operand as usize - 4].name().to_string(),
+        7 if opcode.takes_combo() => String::from("?7"),
+        _ => operand.to_string(),
+    }
+}
+
+// What the instruction does, in terms of the registers
+fn effect(opcode: Opcode, operand: &str) -> String {
+    match opcode {
+        Opcode::Adv => format!("A = A >> {}", operand),
+        Opcode::Bxl => format!("B = B ^ {}", operand),
+        Opcode::Bst => format!("B = {} % 8", operand),
+        Opcode::Jnz => format!("if A != 0 jump {}", operand),
+        Opcode::Bxc => String::from("B = B ^ C"),
+        Opcode::Out => format!("output {} % 8", operand),
+        Opcode::Bdv => format!("B = A >> {}", operand),
+        Opcode::Cdv => format!("C = A >> {}", operand),
+    }
+}
+
+// A single instruction as "mnemonic operand"; bxc ignores its operand, so it is left out if 0.
+// Codes that are no instruction, with an invalid opcode or an operand above 7, show as
+// "??? code operand".
+pub fn instruction(code: u8, operand_code: u8) -> String {
+    match Opcode::from_code(code) {
+        Some(Opcode::Bxc) if operand_code == 0 => String::from("bxc"),
+        Some(opcode) if operand_code <= 7 => {
+            format!("{} {}", opcode.mnemonic(), operand(opcode, operand_code))
+        }
+        _ => format!("??? {} {}", code, operand_code),
+    }
+}
+
+// One line per instruction: address, mnemonic with symbolic operand, and its effect
+pub fn disassemble(program: &[u8]) -> String {
+    let width = program.len().saturating_sub(1).to_string().len();
+    let mut lines = Vec::new();
+    for (pair, codes) in program.chunks(2).enumerate() {
+        let address = pair * 2;
+        let text = match *codes {
+            [code, operand_code] => {
+                let comment = match Opcode::from_code(code) {
+                    Some(opcode) if operand_code <= 7 => {
+                        effect(opcode, &operand(opcode, operand_code))
+                    }
+                    Some(_) => String::from("invalid operand"),
+                    None => String::from("invalid opcode"),
+                };
+                format!("{:<8}; {}", instruction(code, operand_code), comment)
+            }
+            [code] => format!("{:<8}; trailing code, never executed", code),
+            _ => unreachable!("chunks(2) yields one or two codes"),
+        };
+        lines.push(format!("{:>width$}: {}", address, text));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction() {
+        assert_eq!(instruction(2, 4), "bst A");
+        assert_eq!(instruction(1, 5), "bxl 5");
+        assert_eq!(instruction(7, 5), "cdv B");
+        assert_eq!(instruction(5, 7), "out ?7");
+        assert_eq!(instruction(4, 0), "bxc");
+        assert_eq!(instruction(4, 3), "bxc 3");
+        assert_eq!(instruction(3, 6), "jnz 6");
+        assert_eq!(instruction(9, 1), "??? 9 1");
+        assert_eq!(instruction(1, 8), "??? 1 8");
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&[2, 4, 1, 5, 7, 5, 4, 3, 1, 6, 0, 3, 5, 5, 3, 0]);
+        let expected = [
+            " 0: bst A   ; B = A % 8",
+            " 2: bxl 5   ; B = B ^ 5",
+            " 4: cdv B   ; C = A >> B",
+            " 6: bxc 3   ; B = B ^ C",
+            " 8: bxl 6   ; B = B ^ 6",
+            "10: adv 3   ; A = A >> 3",
+            "12: out B   ; output B % 8",
+            "14: jnz 0   ; if A != 0 jump 0",
+        ];
+        assert_eq!(listing, expected.join("\n"));
+
+        assert_eq!(
+            disassemble(&[0, 3, 5]),
+            "0: adv 3   ; A = A >> 3\n2: 5       ; trailing code, never executed"
+        );
+        assert_eq!(disassemble(&[]), "");
+    }
+}
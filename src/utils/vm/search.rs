@@ -0,0 +1,136 @@
+use crate::utils::vm::chronospatial::{Machine, Number, Register, Step};
+use std::ops::Range;
+
+// Instructions a candidate may run for before it is assumed never to halt
+pub const STEP_LIMIT: usize = 100_000;
+
+// The outputs of the machine with one register set, or None if the run faults, does not
+// halt within the step limit, or outputs more than max_len values
+pub fn outputs_with(
+    machine: &Machine,
+    register: Register,
+    value: Number,
+    max_len: usize,
+) -> Option<Vec<Number>> {
+    let mut machine = machine.clone().with_register(register, value);
+    let mut outputs = Vec::new();
+    for _ in 0..STEP_LIMIT {
+        match machine.step().ok()? {
+            Step::Continue => (),
+            Step::Output(value) if outputs.len() < max_len => outputs.push(value),
+            Step::Output(_) => return None,
+            Step::Halt => return Some(outputs),
+        }
+    }
+    None
+}
+
+// Tries every value in the range for the free register, in order, and returns the first
+// for which the machine outputs exactly the target. Other registers keep their values.
+pub fn forward(
+    machine: &Machine,
+    register: Register,
+    target: &[Number],
+    range: Range<Number>,
+) -> Option<Number> {
+    range.into_iter().find(|&value| {
+        outputs_with(machine, register, value, target.len())
+            .is_some_and(|outputs| outputs == target)
+    })
+}
+
+// The nibble search: builds the free register from its most significant bits down, `bits`
+// at a time. Programs that consume the register from the bottom produce, for its top bits
+// alone, the tail end of the full output, so every prefix whose output is not a suffix of
+// the target is dropped. That only holds if B and C do not carry over from one pass to the
+// next; solver::solve_register_a checks for that first. Searches one length at a time, so the first solution is the smallest.
+pub fn backward(
+    machine: &Machine,
+    register: Register,
+    target: &[Number],
+    bits: u32,
+) -> Option<Number> {
+    let mut prefixes: Vec<Number> = vec![0];
+    for _ in 0..Number::BITS.div_ceil(bits) {
+        let mut next = Vec::new();
+        let mut solutions = Vec::new();
+        for prefix in prefixes {
+            if prefix.leading_zeros() < bits {
+                continue;
+            }
+            for low in 0..1 << bits {
+                let value = (prefix << bits) | low;
+                if value == 0 {
+                    // the same as leaving the chunk out
+                    continue;
+                }
+                let Some(outputs) = outputs_with(machine, register, value, target.len()) else {
+                    continue;
+                };
+                if outputs == target {
+                    solutions.push(value);
+                } else if target.ends_with(&outputs) {
+                    next.push(value);
+                }
+            }
+        }
+        if let Some(&smallest) = solutions.iter().min() {
+            return Some(smallest);
+        }
+        if next.is_empty() {
+            return None;
+        }
+        prefixes = next;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::vm::assembler::assemble;
+
+    fn machine(source: &str) -> Machine {
+        Machine::new(assemble(source).unwrap())
+    }
+
+    #[test]
+    fn test_outputs_with() {
+        let m = machine("adv 1\nout A\njnz 0");
+        assert_eq!(outputs_with(&m, Register::A, 6, 10), Some(vec![3, 1, 0]));
+        assert_eq!(outputs_with(&m, Register::A, 6, 2), None);
+        // adv 0 never shrinks A, so this never halts
+        let endless = machine("bst B\nout A\nadv 0\njnz 0");
+        assert_eq!(outputs_with(&endless, Register::A, 1, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_forward() {
+        let m = machine("adv 1\nout A\njnz 0");
+        assert_eq!(forward(&m, Register::A, &[3, 1, 0], 0..100), Some(6));
+        assert_eq!(forward(&m, Register::A, &[3, 1, 0], 7..100), Some(7));
+        assert_eq!(forward(&m, Register::A, &[3, 1, 0], 8..100), None);
+
+        // the free register need not be A: C is mixed into a single output
+        let m = machine("bst A\nbxc\nout B").with_register(Register::A, 5);
+        assert_eq!(forward(&m, Register::C, &[0], 0..8), Some(5));
+    }
+
+    #[test]
+    fn test_backward() {
+        let m = machine("adv 3\nout A\njnz 0");
+        let target = [0, 3, 5, 4, 3, 0];
+        assert_eq!(backward(&m, Register::A, &target, 3), Some(117440));
+        assert_eq!(
+            forward(&m, Register::A, &target, 117000..118000),
+            Some(117440)
+        );
+
+        // two bits per output, with A's bits mixed with a non-zero B
+        let m = machine("bst A\nbxc\nout B\nadv 2\njnz 0").with_register(Register::C, 3);
+        let target = outputs_with(&m, Register::A, 0b10_01_11_00_01, 10).unwrap();
+        let found = backward(&m, Register::A, &target, 2).unwrap();
+        assert_eq!(outputs_with(&m, Register::A, found, 10).unwrap(), target);
+        assert_eq!(forward(&m, Register::A, &target, 0..1 << 12), Some(found));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::utils::vm::{
+    chronospatial::{Machine, Number, Opcode, Register},
+    search::{backward, forward},
+};
+use itertools::Itertools;
+use std::{error::Error, fmt::Display};
+
+// Values of A tried one by one for programs the backward search cannot handle
+pub const FORWARD_LIMIT: Number = 1 << 16;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveError {
+    // B or C (or both) keep their value from one pass to the next, and no A below the forward
+    // limit worked
+    CarriesState(Vec<Register>),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::CarriesState(registers) => {
+                let names: String = registers.iter().map(|register| register.name()).collect();
+                write!(
+                    f,
+                    "register(s) {} carry state between passes and no A below {} works",
+                    names, FORWARD_LIMIT
+                )
+            }
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+// How far A moves right per pass through the program, if every adv has a literal operand
+fn shift_per_pass(program: &[u8]) -> Option<u32> {
+    let mut shift = 0;
+    for pair in program.chunks_exact(2) {
+        if Opcode::from_code(pair[0]) == Some(Opcode::Adv) {
+            match pair[1] {
+                literal @ 0..=3 => shift += literal as u32,
+                _ => return None,
+            }
+        }
+    }
+    (shift > 0).then_some(shift)
+}
+
+// The registers B and C that a pass reads before writing them, when they are also written in
+// the loop, so each pass starts from what the one before left behind. Only a single jnz at
+// the end is understood as a loop; anything else counts as carrying both.
+fn carried_registers(program: &[u8]) -> Vec<Register> {
+    let instructions: Vec<(Option<Opcode>, u8)> = program
+        .chunks_exact(2)
+        .map(|pair| (Opcode::from_code(pair[0]), pair[1]))
+        .collect();
+    let jumps: Vec<usize> = instructions
+        .iter()
+        .positions(|&(opcode, _)| opcode == Some(Opcode::Jnz))
+        .collect();
+    let body = match jumps[..] {
+        [] => return Vec::new(),
+        [last] if last + 1 == instructions.len() && instructions[last].1 & 1 == 0 => {
+            let start = (instructions[last].1 / 2) as usize;
+            match instructions.get(start..last) {
+                Some(body) => body,
+                None => return vec![Register::B, Register::C],
+            }
+        }
+        _ => return vec![Register::B, Register::C],
+    };
+
+    let combo = |operand: u8| match operand {
+        5 => Some(Register::B),
+        6 => Some(Register::C),
+        _ => None,
+    };
+    let mut written: Vec<Register> = Vec::new();
+    let mut read_first: Vec<Register> = Vec::new();
+    for &(opcode, operand) in body {
+        let (reads, writes): (Vec<Option<Register>>, Option<Register>) = match opcode {
+            Some(Opcode::Adv) | Some(Opcode::Out) => (vec![combo(operand)], None),
+            Some(Opcode::Bxl) => (vec![Some(Register::B)], Some(Register::B)),
+            Some(Opcode::Bst) | Some(Opcode::Bdv) => (vec![combo(operand)], Some(Register::B)),
+            Some(Opcode::Cdv) => (vec![combo(operand)], Some(Register::C)),
+            Some(Opcode::Bxc) => (
+                vec![Some(Register::B), Some(Register::C)],
+                Some(Register::B),
+            ),
+            Some(Opcode::Jnz) | None => (vec![], None),
+        };
+        for register in reads.into_iter().flatten() {
+            if !written.contains(&register) && !read_first.contains(&register) {
+                read_first.push(register);
+            }
+        }
+        written.extend(writes);
+    }
+    [Register::B, Register::C]
+        .into_iter()
+        .filter(|register| read_first.contains(register) && written.contains(register))
+        .collect()
+}
+
+// The smallest initial A for which the machine (with its other registers as given) outputs
+// exactly the target, or None if there is none. The number of bits A loses per pass is read
+// off the program where possible and otherwise tried from 1 to 8 bits, so the program only
+// needs to consume A from the low end; what it does with the bits in between is up to it.
+// The backward search needs every pass to start afresh; programs that carry B or C from one
+// pass to the next are searched forward up to FORWARD_LIMIT instead, and fail beyond it.
+pub fn solve_register_a(
+    machine: &Machine,
+    target: &[Number],
+) -> Result<Option<Number>, SolveError> {
+    if target.is_empty() {
+        return Ok(None);
+    }
+    let carried = carried_registers(machine.program());
+    if !carried.is_empty() {
+        return match forward(machine, Register::A, target, 0..FORWARD_LIMIT) {
+            Some(a) => Ok(Some(a)),
+            None => Err(SolveError::CarriesState(carried)),
+        };
+    }
+    Ok(match shift_per_pass(machine.program()) {
+        Some(shift) => backward(machine, Register::A, target, shift),
+        None => (1..=8)
+            .filter_map(|shift| backward(machine, Register::A, target, shift))
+            .min(),
+    })
+}
+
+// The smallest A that makes the program output itself
+pub fn find_quine(machine: &Machine) -> Result<Option<Number>, SolveError> {
+    let target: Vec<Number> = machine
+        .program()
+        .iter()
+        .map(|&code| code as Number)
+        .collect();
+    solve_register_a(machine, &target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs the program with a known A, solves for its output and checks the answer
+    fn check_round_trip(program: &[u8], b: Number, a: Number) {
+        let machine = Machine::new(program.to_vec()).with_register(Register::B, b);
+        let target = machine
+            .clone()
+            .with_register(Register::A, a)
+            .run_to_outputs()
+            .unwrap();
+        let solution = solve_register_a(&machine, &target)
+            .unwrap()
+            .unwrap_or_else(|| panic!("No solution for {:?} with A = {}", program, a));
+        assert!(solution <= a, "{} is larger than {}", solution, a);
+        assert_eq!(
+            machine
+                .with_register(Register::A, solution)
+                .run_to_outputs()
+                .unwrap(),
+            target
+        );
+    }
+
+    #[test]
+    fn test_shift_per_pass() {
+        assert_eq!(shift_per_pass(&[0, 3, 5, 4, 3, 0]), Some(3));
+        assert_eq!(shift_per_pass(&[0, 1, 5, 4, 0, 2, 3, 0]), Some(3));
+        assert_eq!(shift_per_pass(&[0, 5, 5, 4, 3, 0]), None);
+        assert_eq!(shift_per_pass(&[5, 4]), None);
+    }
+
+    #[test]
+    fn test_quine() {
+        let machine = Machine::new(vec![0, 3, 5, 4, 3, 0]);
+        assert_eq!(find_quine(&machine), Ok(Some(117440)));
+    }
+
+    #[test]
+    fn test_variants() {
+        // the usual shape: output depends on A's low bits and higher bits via C
+        check_round_trip(
+            &[2, 4, 1, 5, 7, 5, 4, 3, 1, 6, 0, 3, 5, 5, 3, 0],
+            0,
+            0o3_5_1_7_0_6_2_4,
+        );
+        // two bits per pass
+        check_round_trip(&[0, 2, 5, 4, 3, 0], 0, 0b11_01_10_00_01_11);
+        // the shift split over two instructions
+        check_round_trip(&[0, 1, 5, 4, 0, 2, 3, 0], 0, 0o7_1_2_6_5);
+        // two outputs per pass
+        check_round_trip(&[6, 1, 5, 5, 5, 4, 0, 3, 3, 0], 0, 0o1_7_7_3_0_4);
+        // shifting A by B, set to 3 before the loop, so the shift has to be tried out
+        check_round_trip(&[1, 3, 5, 4, 0, 5, 3, 2], 0, 0o5_2_0_4_4);
+        // B starts out non-zero and is mixed into every output
+        check_round_trip(&[2, 4, 4, 0, 5, 5, 0, 3, 3, 0], 6, 0o6_6_1_0_3);
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // always outputs 0, so cannot output a 1
+        let machine = Machine::new(vec![0, 3, 5, 0, 3, 0]);
+        assert_eq!(solve_register_a(&machine, &[1]), Ok(None));
+        assert_eq!(solve_register_a(&machine, &[0, 0]), Ok(Some(8)));
+    }
+
+    #[test]
+    fn test_carried_registers() {
+        assert!(carried_registers(&[2, 4, 1, 5, 7, 5, 4, 3, 1, 6, 0, 3, 5, 5, 3, 0]).is_empty());
+        // C is read but never written, so it is the same on every pass
+        assert!(carried_registers(&[2, 4, 4, 0, 5, 5, 0, 3, 3, 0]).is_empty());
+        // B is set before the loop starts at 2
+        assert!(carried_registers(&[1, 3, 5, 4, 0, 5, 3, 2]).is_empty());
+        assert_eq!(
+            carried_registers(&[1, 1, 5, 5, 0, 3, 3, 0]),
+            vec![Register::B]
+        );
+        assert_eq!(
+            carried_registers(&[3, 0, 0, 3, 3, 0]),
+            vec![Register::B, Register::C]
+        );
+    }
+
+    #[test]
+    fn test_carried_state() {
+        // B flips every pass: with A = 64 it outputs 1,0,1, which its top bits alone do not end
+        let machine = Machine::new(vec![1, 1, 5, 5, 0, 3, 3, 0]);
+        assert_eq!(
+            machine
+                .clone()
+                .with_register(Register::A, 64)
+                .run_to_outputs()
+                .unwrap(),
+            vec![1, 0, 1]
+        );
+        assert_eq!(solve_register_a(&machine, &[1, 0, 1]), Ok(Some(64)));
+        // the first output is always 1
+        assert_eq!(
+            solve_register_a(&machine, &[0]),
+            Err(SolveError::CarriesState(vec![Register::B]))
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use crate::utils::{
+    file_io::{self, AocError},
+    map2d::{
+        direction::{Direction, DirectionSequence},
+        grid::{Convert, Grid, ToChar, ValidPosition},
+        history::GridHistory,
+    },
+};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+// The warehouse from day 15: a robot pushing boxes around, either one tile wide (part 1) or
+// two tiles wide (part 2)
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Tile {
+    Empty,
+    Box,
+    Wall,
+}
+
+impl ToChar for Tile {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Wall => '#',
+            Self::Box => 'O',
+            Self::Empty => '.',
+        }
+    }
+}
+
+impl From<char> for Tile {
+    fn from(c: char) -> Self {
+        match c {
+            '#' => Self::Wall,
+            'O' => Self::Box,
+            _ => Self::Empty,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HalfTile {
+    Empty,
+    BoxHalfLeft,
+    BoxHalfRight,
+    Wall,
+}
+
+impl ToChar for HalfTile {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Wall => '#',
+            Self::BoxHalfLeft => '[',
+            Self::BoxHalfRight => ']',
+            Self::Empty => '.',
+        }
+    }
+}
+
+impl From<char> for HalfTile {
+    fn from(c: char) -> Self {
+        match c {
+            '#' => Self::Wall,
+            '[' => Self::BoxHalfLeft,
+            ']' => Self::BoxHalfRight,
+            _ => Self::Empty,
+        }
+    }
+}
+
+pub trait IsTile: Sized + Copy + PartialEq + ToChar + From<char> {
+    fn process_input_line(line: &str) -> String;
+    fn adds_to_gps(&self) -> bool;
+    // Pushes whatever is in front of the robot out of the way, if it can be
+    fn push(warehouse: &mut Warehouse<Self>, direction: Direction) -> bool;
+}
+
+impl IsTile for Tile {
+    fn process_input_line(line: &str) -> String {
+        line.into()
+    }
+
+    fn adds_to_gps(&self) -> bool {
+        *self == Self::Box
+    }
+
+    fn push(warehouse: &mut Warehouse<Self>, direction: Direction) -> bool {
+        warehouse.try_move(warehouse.robot, direction)
+    }
+}
+
+impl IsTile for HalfTile {
+    fn process_input_line(line: &str) -> String {
+        line.replace(".", "..")
+            .replace("O", "[]")
+            .replace("#", "##")
+            .replace("@", "@.")
+    }
+
+    fn adds_to_gps(&self) -> bool {
+        *self == Self::BoxHalfLeft
+    }
+
+    fn push(warehouse: &mut Warehouse<Self>, direction: Direction) -> bool {
+        match direction {
+            Direction::RIGHT | Direction::LEFT => {
+                warehouse.try_move_horizontally(warehouse.robot, direction)
+            }
+            Direction::UP | Direction::DOWN => {
+                warehouse.try_move_vertically([warehouse.robot].into(), direction)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Warehouse<T: IsTile> {
+    pub room: Grid<T>,
+    pub robot: ValidPosition,
+}
+
+impl Warehouse<Tile> {
+    fn try_move(&mut self, start_pos: ValidPosition, direction: Direction) -> bool {
+        let start_value = *self.room.value(&start_pos);
+        start_pos
+            .try_step(&direction, &self.room.bounds)
+            .and_then(|next_pos| {
+                let next_value = *self.room.value(&next_pos);
+                match next_value {
+                    Tile::Empty => Some(next_pos),
+                    Tile::Box if self.try_move(next_pos, direction) => Some(next_pos),
+                    _ => None,
+                }
+            })
+            .map(|next_pos| {
+                *self.room.value_mut(&next_pos) = start_value;
+            })
+            .is_some()
+    }
+}
+
+impl Warehouse<HalfTile> {
+    fn try_move_horizontally(&mut self, start_pos: ValidPosition, direction: Direction) -> bool {
+        let start_value = *self.room.value(&start_pos);
+        start_pos
+            .try_step(&direction, &self.room.bounds)
+            .and_then(|next_pos| {
+                let next_value = *self.room.value(&next_pos);
+                match next_value {
+                    HalfTile::Empty => Some(next_pos),
+                    HalfTile::BoxHalfLeft | HalfTile::BoxHalfRight
+                        if self.try_move_horizontally(next_pos, direction) =>
+                    {
+                        Some(next_pos)
+                    }
+                    _ => None,
+                }
+            })
+            .map(|next_pos| {
+                *self.room.value_mut(&next_pos) = start_value;
+            })
+            .is_some()
+    }
+
+    fn try_move_vertically(
+        &mut self,
+        start_positions: HashSet<ValidPosition>,
+        direction: Direction,
+    ) -> bool {
+        if start_positions.is_empty() {
+            return true;
+        }
+
+        // we go row-by-row here. nothing can move unless everything moves, so must check first, then move
+        // 1. collect obstacles in next row
+        let mut obstacles: HashSet<ValidPosition> = HashSet::new();
+        for start_pos in &start_positions {
+            let next_pos = start_pos
+                .try_step(&direction, &self.room.bounds)
+                .expect("Stepped out of bounds - invalid state.");
+            let next_value = *self.room.value(&next_pos);
+            match next_value {
+                HalfTile::Wall => return false,
+                HalfTile::BoxHalfLeft => {
+                    obstacles.insert(next_pos);
+                    obstacles.insert(
+                        next_pos
+                            .try_step(&Direction::RIGHT, &self.room.bounds)
+                            .expect("Box did not have right half - invalid state."),
+                    );
+                }
+                HalfTile::BoxHalfRight => {
+                    obstacles.insert(next_pos);
+                    obstacles.insert(
+                        next_pos
+                            .try_step(&Direction::LEFT, &self.room.bounds)
+                            .expect("Box did not have right half - invalid state."),
+                    );
+                }
+                _ => (),
+            };
+        }
+
+        // 2. try move obstacles, move if possible
+        // TODO: refactor - have already computed next_pos
+        self.try_move_vertically(obstacles, direction)
+            .then(|| {
+                for start_pos in start_positions {
+                    let next_pos = start_pos
+                        .try_step(&direction, &self.room.bounds)
+                        .expect("Stepped out of bounds - invalid state.");
+                    let start_value = *self.room.value(&start_pos);
+                    *self.room.value_mut(&next_pos) = start_value;
+                    *self.room.value_mut(&start_pos) = HalfTile::Empty;
+                }
+            })
+            .is_some()
+    }
+}
+
+impl<T: IsTile> Warehouse<T> {
+    // Whether the robot moved; it stays put if walls are in the way
+    pub fn try_step(&mut self, direction: Direction) -> bool {
+        T::push(self, direction)
+            .then(|| {
+                self.robot = self
+                    .robot
+                    .try_step(&direction, &self.room.bounds)
+                    .expect("Error executing robot step.")
+            })
+            .is_some()
+    }
+
+    pub fn gps(&self) -> usize {
+        self.room
+            .position_iter()
+            .filter(|pos| T::adds_to_gps(self.room.value(pos)))
+            .map(|ValidPosition(x, y)| x + 100 * y)
+            .sum()
+    }
+
+    // The room as in the input, robot included
+    pub fn frame(&self) -> Grid<char> {
+        self.room.map(ToChar::to_char).overlay(&[self.robot], '@')
+    }
+}
+
+pub fn load_input<T: IsTile>(path: &str) -> Result<(Warehouse<T>, Vec<Direction>), AocError> {
+    let (map_block, instruction_block) = file_io::blocks_from_file(path)?
+        .collect_tuple()
+        .expect("Input should consist of a map block and an instruction block.");
+
+    let mut map: Grid<char> =
+        Grid::from_rows(map_block.iter().map(|line| T::process_input_line(line)));
+    let markers = map.take_markers(&['@'], '.');
+
+    let instructions: Vec<Direction> = instruction_block
+        .join("")
+        .parse::<DirectionSequence>()
+        .expect("Instructions should consist of ^, >, v and <.")
+        .0;
+
+    let [robot] = markers[&'@'][..] else {
+        panic!("Could not find unique robot position.");
+    };
+
+    let warehouse = Warehouse {
+        robot,
+        room: map.convert(),
+    };
+
+    Ok((warehouse, instructions))
+}
+
+// A warehouse driven by hand, where every move can be taken back. Bumping into a wall is not
+// a move.
+#[derive(Debug, Clone)]
+pub struct Player<T: IsTile> {
+    warehouse: Warehouse<T>,
+    history: GridHistory<T>,
+    robots: Vec<ValidPosition>,
+}
+
+impl<T: IsTile> Player<T> {
+    pub fn new(warehouse: Warehouse<T>) -> Self {
+        Player {
+            history: GridHistory::new(warehouse.room.clone()),
+            warehouse,
+            robots: Vec::new(),
+        }
+    }
+
+    pub fn warehouse(&self) -> &Warehouse<T> {
+        &self.warehouse
+    }
+
+    pub fn moves(&self) -> usize {
+        self.robots.len()
+    }
+
+    pub fn step(&mut self, direction: Direction) -> bool {
+        let robot = self.warehouse.robot;
+        let moved = self.warehouse.try_step(direction);
+        if moved {
+            self.history.record(&self.warehouse.room);
+            self.robots.push(robot);
+        }
+        moved
+    }
+
+    // Whether there was a move to take back
+    pub fn undo(&mut self) -> bool {
+        let Some(robot) = self.robots.pop() else {
+            return false;
+        };
+        self.warehouse.room = self
+            .history
+            .undo()
+            .expect("History should hold every move.")
+            .clone();
+        self.warehouse.robot = robot;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::test_input;
+
+    #[test]
+    fn test_both_widths() {
+        let (mut warehouse, instructions): (Warehouse<Tile>, _) =
+            load_input(&test_input(15, 2)).unwrap();
+        for direction in &instructions {
+            warehouse.try_step(*direction);
+        }
+        assert_eq!(warehouse.gps(), 10092);
+
+        let (mut warehouse, _): (Warehouse<HalfTile>, _) = load_input(&test_input(15, 2)).unwrap();
+        for direction in instructions {
+            warehouse.try_step(direction);
+        }
+        assert_eq!(warehouse.gps(), 9021);
+    }
+
+    #[test]
+    fn test_player_undo() {
+        let (warehouse, instructions): (Warehouse<HalfTile>, _) =
+            load_input(&test_input(15, 2)).unwrap();
+        let start = warehouse.frame().pretty_print_string();
+        let mut player = Player::new(warehouse);
+        let moved = instructions
+            .into_iter()
+            .filter(|&direction| player.step(direction))
+            .count();
+        assert_eq!(player.moves(), moved);
+        assert_ne!(player.warehouse().frame().pretty_print_string(), start);
+
+        while player.undo() {}
+        assert_eq!(player.moves(), 0);
+        assert_eq!(player.warehouse().frame().pretty_print_string(), start);
+    }
+}